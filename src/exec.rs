@@ -1,122 +1,584 @@
-use std::ffi::OsStr;
+pub mod capture;
+mod extra_fds;
+pub mod pty;
+mod waiter;
+
 use std::ffi::OsString;
+use std::os::fd::{OwnedFd, RawFd};
 use std::os::unix::ffi::OsStringExt;
 use std::os::unix::process::ExitStatusExt;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use nix::sys::signal::kill;
-use tokio::io::AsyncReadExt;
-use tokio::io::AsyncWriteExt;
-use tokio::process::Child;
+use indexmap::IndexMap;
+use nix::sys::resource::{setrlimit, Resource};
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::process::Command;
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::test_case::{
+    action::Action, stdin::StdinChunk, BackgroundConfig, Limits, Process, ProcessMode,
+};
 
-use crate::test_case::WaitCondition;
+use self::capture::{Arrivals, Capture, Merged, Tee};
+use self::extra_fds::ExtraFds;
+use self::waiter::{signal_group, Waiter};
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub enum Status {
     Exit(i32),
-    Signal(i32),
+    // signal number and whether core is dumped
+    Signal(i32, bool),
     Timeout,
 }
 
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum StopStage {
+    // exited before stopping
+    Exited,
+    // exited after stop signal is sent
+    Signal(Signal),
+    // killed by SIGKILL after stop timeout
+    Kill,
+}
+
+// resource usage of the process (max_rss is in bytes)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Usage {
+    pub max_rss: u64,
+    pub user_time: Duration,
+    pub sys_time: Duration,
+    pub wall_time: Duration,
+}
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct Output {
     pub status: Status,
     pub stdout: OsString,
     pub stderr: OsString,
+    // stdout and stderr in the order they were written
+    pub output: OsString,
+    // when each part of stdout and stderr arrived
+    pub stdout_arrivals: Arrivals,
+    pub stderr_arrivals: Arrivals,
+    // outputs of captured extra fds
+    pub fds: IndexMap<RawFd, OsString>,
+    pub usage: Usage,
+    pub stop_stage: Option<StopStage>,
+    // whether processes in the process group were alive after exit (checked only when required)
+    pub leftover: bool,
+    // number of actions performed before exit
+    pub performed_actions: usize,
 }
 
+// ends of pipes which connect streams of the process to other processes
+#[derive(Debug, Default)]
+pub struct Pipes {
+    pub stdin: Option<OwnedFd>,
+    pub stdout: Option<OwnedFd>,
+    pub stderr: Option<OwnedFd>,
+}
+
+// performs actions in background until stopped by `stop`.
+// the task returns the number of performed actions.
 #[derive(Debug)]
-pub struct BackgroundExec {
-    pub child: Child,
-    timeout: Duration,
-    pub tee_stdout: bool,
-    pub tee_stderr: bool,
-    buffered_stdout: String,
+struct ActionsTask {
+    stop: Option<oneshot::Sender<()>>,
+    handle: JoinHandle<usize>,
 }
 
-impl BackgroundExec {
-    pub fn new(child: Child, timeout: Duration, tee_stdout: bool, tee_stderr: bool) -> Self {
+impl ActionsTask {
+    fn start(actions: Vec<Action>, mut stdout: Capture, waiter: &Waiter) -> Self {
+        let (stop, stop_rx) = oneshot::channel::<()>();
+        let pgid = waiter.pgid();
+        let started = waiter.started();
+
+        let handle = tokio::spawn(async move {
+            let mut performed = 0;
+            tokio::select! {
+                _ = run_actions(&actions, pgid, started, &mut stdout, &mut performed) => {}
+                _ = stop_rx => {}
+            }
+            performed
+        });
+
         Self {
-            child,
-            timeout,
-            tee_stdout,
-            tee_stderr,
-            buffered_stdout: String::new(),
+            stop: Some(stop),
+            handle,
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
         }
     }
 
-    pub fn append_buffered_stdout(&mut self, stdout: &str) {
-        self.buffered_stdout.push_str(stdout);
+    async fn finish(mut self) -> Result<usize, String> {
+        self.stop();
+        self.handle.await.map_err(|err| err.to_string())
+    }
+}
+
+// stdin is written and stdout and stderr are captured concurrently with the process
+// so that it is not blocked by full pipes
+#[derive(Debug)]
+struct Streams {
+    stdin: Option<JoinHandle<()>>,
+    stdout: Capture,
+    stderr: Capture,
+    merged: Merged,
+    fds: ExtraFds,
+    actions: Option<ActionsTask>,
+}
+
+#[derive(Debug)]
+pub struct BackgroundExec {
+    child: Child,
+    waiter: Waiter,
+    stop_signal: Signal,
+    stop_timeout: Duration,
+    check_leftover: bool,
+    pub stdout: Capture,
+    stderr: Capture,
+    merged: Merged,
+    fds: ExtraFds,
+    stdin_writer: Option<JoinHandle<()>>,
+    actions: Option<ActionsTask>,
+}
+
+impl BackgroundExec {
+    pub fn new(
+        mut child: Child,
+        stop_signal: Signal,
+        stop_timeout: Duration,
+        check_leftover: bool,
+        tee: (Option<Tee>, Option<Tee>),
+    ) -> Result<Self, String> {
+        let waiter = Waiter::new(&child)?;
+        let (stdout, stderr, merged) = start_captures(&mut child, tee);
+
+        Ok(Self {
+            child,
+            waiter,
+            stop_signal,
+            stop_timeout,
+            check_leftover,
+            stdout,
+            stderr,
+            merged,
+            fds: ExtraFds::default(),
+            stdin_writer: None,
+            actions: None,
+        })
     }
 
     pub async fn terminate(self) -> Result<Output, String> {
-        let BackgroundExec { child, timeout, .. } = self;
-        let pid = child
-            .id()
-            .map(|id| nix::unistd::Pid::from_raw(id as i32))
-            .ok_or_else(|| "cound not get pid".to_string())?;
+        let BackgroundExec {
+            child,
+            waiter,
+            stop_signal,
+            stop_timeout,
+            check_leftover,
+            stdout,
+            stderr,
+            merged,
+            fds,
+            stdin_writer,
+            mut actions,
+            ..
+        } = self;
 
-        kill(pid, nix::sys::signal::Signal::SIGTERM)
-            .map_err(|err| format!("cound not send signal to {}: {}", pid, err))?;
+        if let Some(actions) = &mut actions {
+            actions.stop();
+        }
+
+        let stop_stage = if waiter.has_exited() {
+            StopStage::Exited
+        } else {
+            StopStage::Signal(stop_signal)
+        };
+        // descendants may be still running after the leader exited, so the group is stopped anyway
+        signal_group(waiter.pgid(), stop_signal)?;
+        let deadline = Instant::now() + stop_timeout;
+
+        let streams = Streams {
+            stdin: stdin_writer,
+            stdout,
+            stderr,
+            merged,
+            fds,
+            actions,
+        };
+        let mut output =
+            wait_with_timeout(child, waiter, deadline, deadline, streams, check_leftover).await?;
+        output.stop_stage = Some(if let Status::Timeout = output.status {
+            StopStage::Kill
+        } else {
+            stop_stage
+        });
 
-        wait_with_timeout(child, timeout, &self.buffered_stdout).await
+        Ok(output)
+    }
+
+    async fn kill(self) {
+        if let Some(stdin) = &self.stdin_writer {
+            stdin.abort();
+        }
+        self.fds.abort();
+        let _ = self.waiter.kill().await;
     }
 }
 
-pub async fn execute_command<S: AsRef<OsStr>, E: IntoIterator<Item = (S, S)>>(
-    command: &str,
-    args: &[String],
-    stdin: String,
-    env: E,
-    timeout: Duration,
+pub async fn execute_command(
+    process: &Process,
+    tee: (Option<Tee>, Option<Tee>),
+    pipes: Pipes,
 ) -> Result<Output, String> {
-    let mut cmd = Command::new(command)
-        .args(args)
-        .stdin(std::process::Stdio::piped())
-        .envs(env)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
-        .spawn()
-        .map_err(|err| error_message_of_execution(command, args, err))?;
+    let (mut cmd, fds) = spawn(process, pipes)?;
+    // dialogue is counted in the timeout of the process
+    let deadline = Instant::now() + process.timeout;
+    let waiter = Waiter::new(&cmd)?;
+    let (mut stdout, stderr, merged) = start_captures(&mut cmd, tee);
 
-    let mut cmd_stdin = cmd.stdin.take().ok_or("cannot get stdin".to_string())?;
-    let _ = tokio::task::spawn(async move { cmd_stdin.write_all(stdin.as_bytes()).await })
-        .await
-        .map_err(|err| err.to_string())?;
+    let stdin = match feed_stdin(&mut cmd, process, &mut stdout, Some(deadline)).await {
+        Ok(stdin) => stdin,
+        Err(err) => {
+            fds.abort();
+            let _ = waiter.kill().await;
+            return Err(err);
+        }
+    };
 
-    wait_with_timeout(cmd, timeout, "").await
+    let actions = ActionsTask::start(process.actions.clone(), stdout.clone(), &waiter);
+    let streams = Streams {
+        stdin,
+        stdout,
+        stderr,
+        merged,
+        fds,
+        actions: Some(actions),
+    };
+
+    // descendants are killed as soon as the command exits
+    wait_with_timeout(
+        cmd,
+        waiter,
+        deadline,
+        Instant::now(),
+        streams,
+        process.check_leftover,
+    )
+    .await
 }
 
-pub async fn execute_background_command<S: AsRef<OsStr>, E: IntoIterator<Item = (S, S)>>(
-    command: &str,
-    args: &[String],
-    stdin: String,
-    env: E,
-    timeout: Duration,
-    wait_condition: &WaitCondition,
-    tee: (bool, bool),
+pub async fn execute_background_command(
+    process: &Process,
+    cfg: &BackgroundConfig,
+    tee: (Option<Tee>, Option<Tee>),
+    pipes: Pipes,
 ) -> Result<BackgroundExec, String> {
-    let mut child = Command::new(command)
-        .args(args)
-        .stdin(std::process::Stdio::piped())
-        .envs(env)
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::piped())
+    let (child, fds) = spawn(process, pipes)?;
+
+    let mut exec = BackgroundExec::new(
+        child,
+        cfg.stop_signal,
+        cfg.stop_timeout.unwrap_or(process.timeout),
+        process.check_leftover,
+        tee,
+    )?;
+    exec.fds = fds;
+
+    let mut stdout = exec.stdout.clone();
+    match feed_stdin(&mut exec.child, process, &mut stdout, None).await {
+        Ok(stdin) => exec.stdin_writer = stdin,
+        Err(err) => {
+            exec.kill().await;
+            return Err(err);
+        }
+    };
+
+    if let Err(err) = cfg.wait_condition.wait(&mut exec).await {
+        exec.kill().await;
+        return Err(err);
+    }
+
+    exec.actions = Some(ActionsTask::start(
+        process.actions.clone(),
+        stdout,
+        &exec.waiter,
+    ));
+
+    Ok(exec)
+}
+
+fn spawn(process: &Process, pipes: Pipes) -> Result<(Child, ExtraFds), String> {
+    let mut cmd = Command::new(&process.command);
+    cmd.args(&process.args)
+        .envs(process.env.iter().map(|(k, v)| (k, v)))
+        .stderr(std::process::Stdio::piped());
+
+    if let Some(dir) = &process.dir {
+        cmd.current_dir(dir);
+    }
+
+    let rlimits = rlimits_of(&process.limits);
+    if !rlimits.is_empty() {
+        unsafe {
+            cmd.pre_exec(move || {
+                for (resource, limit) in &rlimits {
+                    setrlimit(*resource, *limit, *limit)?;
+                }
+                Ok(())
+            });
+        }
+    }
+
+    let fds = ExtraFds::open(&mut cmd, &process.fds)?;
+
+    let pty = process.tty.as_ref().map(pty::Pty::open).transpose()?;
+    // run in own process group so that signals are delivered to its descendants too
+    // (with tty, setsid makes new process group)
+    match &pty {
+        Some(pty) => pty.attach(&mut cmd)?,
+        None => {
+            cmd.stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .process_group(0);
+        }
+    }
+
+    // pipes are connected instead of the streams read or written by texest
+    let (stdout_piped, stderr_piped) = (pipes.stdout.is_some(), pipes.stderr.is_some());
+    if let Some(fd) = pipes.stdin {
+        cmd.stdin(std::process::Stdio::from(fd));
+    }
+    if let Some(fd) = pipes.stdout {
+        cmd.stdout(std::process::Stdio::from(fd));
+    }
+    if let Some(fd) = pipes.stderr {
+        cmd.stderr(std::process::Stdio::from(fd));
+    }
+
+    let mut child = cmd
         .spawn()
-        .map_err(|err| error_message_of_execution(command, args, err))?;
+        .map_err(|err| error_message_of_execution(&process.command, &process.args, err))?;
+
+    if let Some(pty) = pty {
+        pty.connect(&mut child)?;
+    }
+
+    // streams connected to other processes are captured as empty
+    if stdout_piped {
+        let reader = std::process::ChildStdout::from(closed_pipe()?);
+        child.stdout = Some(ChildStdout::from_std(reader).map_err(|err| err.to_string())?);
+    }
+    if stderr_piped {
+        let reader = std::process::ChildStderr::from(closed_pipe()?);
+        child.stderr = Some(ChildStderr::from_std(reader).map_err(|err| err.to_string())?);
+    }
+
+    Ok((child, fds))
+}
+
+// returns reader of the pipe whose writer is already closed
+fn closed_pipe() -> Result<OwnedFd, String> {
+    let (reader, _) = std::io::pipe().map_err(|err| format!("cannot create pipe: {}", err))?;
+    Ok(reader.into())
+}
+
+fn start_captures(
+    child: &mut Child,
+    tee: (Option<Tee>, Option<Tee>),
+) -> (Capture, Capture, Merged) {
+    let (tee_stdout, tee_stderr) = tee;
+    let merged = Merged::default();
+    let stdout = Capture::start(child.stdout.take(), "stdout", tee_stdout, merged.clone());
+    let stderr = Capture::start(child.stderr.take(), "stderr", tee_stderr, merged.clone());
+
+    (stdout, stderr, merged)
+}
+
+fn rlimits_of(limits: &Limits) -> Vec<(Resource, u64)> {
+    // RLIMIT_CPU is in seconds, so round up
+    let cpu = limits
+        .cpu
+        .map(|cpu| cpu.as_secs() + u64::from(cpu.subsec_nanos() > 0));
+
+    [
+        (Resource::RLIMIT_CPU, cpu),
+        (Resource::RLIMIT_AS, limits.memory),
+        (Resource::RLIMIT_NOFILE, limits.nofile),
+        (Resource::RLIMIT_FSIZE, limits.fsize),
+        (Resource::RLIMIT_CORE, limits.core),
+    ]
+    .into_iter()
+    .filter_map(|(resource, limit)| limit.map(|limit| (resource, limit)))
+    .collect()
+}
+
+// runs dialogue and then starts writing stdin in background. returns the writer task,
+// or none when stdin is connected to other process.
+async fn feed_stdin(
+    child: &mut Child,
+    process: &Process,
+    stdout: &mut Capture,
+    deadline: Option<Instant>,
+) -> Result<Option<JoinHandle<()>>, String> {
+    if process.stdin_from.is_some() {
+        return Ok(None);
+    }
 
     let mut cmd_stdin = child.stdin.take().ok_or("cannot get stdin".to_string())?;
-    let _ = tokio::task::spawn(async move { cmd_stdin.write_all(stdin.as_bytes()).await })
+    let dialogue = run_dialogue(&mut cmd_stdin, process, stdout);
+    let finished = match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), dialogue)
+            .await
+            .ok(),
+        None => Some(dialogue.await),
+    };
+    if finished.transpose()?.is_none() {
+        // stdin is kept open until the process is killed as timed out
+        let writer = tokio::spawn(async move {
+            let _stdin = cmd_stdin;
+            std::future::pending::<()>().await;
+        });
+        return Ok(Some(writer));
+    }
+
+    let stdin = process.stdin.clone();
+    let file = match &process.stdin_file {
+        Some(path) => Some(open_stdin_file(path, process.dir.as_deref()).await?),
+        None => None,
+    };
+    let chunks = process.stdin_chunks.clone();
+    let tty = process.tty.is_some();
+    let keep_open = matches!(process.mode, ProcessMode::Background(_));
+    let writer = tokio::spawn(async move {
+        let mut last = stdin.last().copied();
+        write_input(&mut cmd_stdin, &stdin).await;
+        if let Some(file) = file {
+            if let Ok(Some(byte)) = stream_file(file, &mut cmd_stdin).await {
+                last = Some(byte);
+            }
+        }
+        if let Some(chunks) = chunks {
+            write_chunks(cmd_stdin, chunks, tty, keep_open, last).await;
+            return;
+        }
+        if tty {
+            write_input(&mut cmd_stdin, &pty::eof_after(last)).await;
+            // closing pty master hangs up the process, so it is kept until the writer is aborted after exit
+            std::future::pending::<()>().await;
+        }
+    });
+
+    Ok(Some(writer))
+}
+
+// waits for each prompt of dialogue and sends the answer
+async fn run_dialogue(
+    cmd_stdin: &mut ChildStdin,
+    process: &Process,
+    stdout: &mut Capture,
+) -> Result<(), String> {
+    let mut start = 0;
+
+    for (i, step) in process.dialogue.iter().enumerate() {
+        if let Some(expect) = &step.expect {
+            start = expect.wait_output(stdout, start).await.map_err(|err| {
+                format!(
+                    "dialogue[{}]: {}\n\noutput so far:\n{}",
+                    i,
+                    err,
+                    String::from_utf8_lossy(&stdout.snapshot())
+                )
+            })?;
+        }
+
+        cmd_stdin
+            .write_all(step.send.as_bytes())
+            .await
+            .map_err(|err| format!("dialogue[{}]: cannot send: {}", i, err))?;
+    }
+
+    Ok(())
+}
+
+// relative path is resolved from the working directory of the process
+async fn open_stdin_file(path: &str, dir: Option<&str>) -> Result<File, String> {
+    let path = match dir {
+        Some(dir) => Path::new(dir).join(path),
+        None => PathBuf::from(path),
+    };
+    File::open(&path)
         .await
-        .map_err(|err| err.to_string())?;
+        .map_err(|err| format!("cannot open stdin file {}: {}", path.display(), err))
+}
 
-    let (tee_stdout, tee_stderr) = tee;
-    let mut exec = BackgroundExec::new(child, timeout, tee_stdout, tee_stderr);
+// streams the file to stdin as raw bytes, and returns the last byte written
+async fn stream_file(mut file: File, cmd_stdin: &mut ChildStdin) -> std::io::Result<Option<u8>> {
+    let mut buf = [0u8; 8192];
+    let mut last = None;
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(last);
+        }
+        cmd_stdin.write_all(&buf[..n]).await?;
+        last = Some(buf[n - 1]);
+    }
+}
 
-    wait_condition.wait(&mut exec).await?;
+// writes chunks in order. stdin is kept open until the writer is aborted after exit
+// unless it is closed by a chunk.
+// stdin of background process is kept open after the last chunk unless it is closed explicitly
+async fn write_chunks(
+    mut cmd_stdin: ChildStdin,
+    chunks: Vec<StdinChunk>,
+    tty: bool,
+    keep_open: bool,
+    mut last: Option<u8>,
+) {
+    let close = !keep_open || matches!(chunks.last(), Some(StdinChunk::Close));
+    for chunk in &chunks {
+        let written = match chunk {
+            StdinChunk::Data(data) => {
+                last = data.as_bytes().last().copied().or(last);
+                write_input(&mut cmd_stdin, data.as_bytes()).await
+            }
+            StdinChunk::Delay(delay) => {
+                tokio::time::sleep(*delay).await;
+                true
+            }
+            // close is the last chunk, so it is closed after the loop
+            StdinChunk::Close => true,
+        };
+        if !written {
+            return;
+        }
+    }
 
-    Ok(exec)
+    if close {
+        if !tty {
+            return;
+        }
+        write_input(&mut cmd_stdin, &pty::eof_after(last)).await;
+    }
+    // closing pty master hangs up the process, so it is kept until the writer is aborted after exit
+    std::future::pending::<()>().await;
+}
+
+// writes input to stdin or fd of the process, and returns whether it is written.
+// error is ignored because the process may exit without reading its input.
+async fn write_input<W: AsyncWrite + Unpin>(writer: &mut W, input: &[u8]) -> bool {
+    writer.write_all(input).await.is_ok()
 }
 
 fn error_message_of_execution(command: &str, args: &[String], err: std::io::Error) -> String {
@@ -125,63 +587,131 @@ fn error_message_of_execution(command: &str, args: &[String], err: std::io::Erro
     format!("cannot execute {:?}: {}", command_and_args, err)
 }
 
+// performs actions in order. stops at the first action which cannot be performed,
+// e.g. stdout is closed before the pattern is output.
+async fn run_actions(
+    actions: &[Action],
+    pgid: Pid,
+    started: Instant,
+    stdout: &mut Capture,
+    performed: &mut usize,
+) {
+    let mut start = 0;
+
+    for action in actions {
+        start = match action.wait(started, stdout, start).await {
+            Ok(start) => start,
+            Err(_) => return,
+        };
+
+        if killpg(pgid, action.signal).is_err() {
+            return;
+        }
+        *performed += 1;
+    }
+}
+
+// waits the command until `deadline`, and then gives its descendants until `descendants_deadline`
+// to close stdout and stderr. remaining descendants are killed, since they may hold the streams open.
 async fn wait_with_timeout(
-    mut child: Child,
-    timeout: Duration,
-    buffered_stdout: &str,
+    child: Child,
+    mut waiter: Waiter,
+    deadline: Instant,
+    descendants_deadline: Instant,
+    streams: Streams,
+    check_leftover: bool,
 ) -> Result<Output, String> {
-    match tokio::time::timeout(timeout, child.wait()).await {
-        Ok(Ok(status)) => {
+    let pgid = waiter.pgid();
+    let timeout = deadline.saturating_duration_since(Instant::now());
+    let (status, usage) = match tokio::time::timeout(timeout, waiter.wait()).await {
+        Ok(result) => {
+            let (status, usage) = result?;
             let status = if let Some(code) = status.code() {
                 Ok(Status::Exit(code))
             } else if let Some(signal) = status.signal() {
-                Ok(Status::Signal(signal))
+                Ok(Status::Signal(signal, status.core_dumped()))
             } else {
                 Err(format!("unknown process status: {}", status))
             }?;
-
-            let mut stdout: Vec<u8> = buffered_stdout.as_bytes().to_vec();
-            child
-                .stdout
-                .ok_or_else(|| "cannot get stdout".to_string())?
-                .read_to_end(&mut stdout)
-                .await
-                .map_err(|err| err.to_string())?;
-
-            let mut stderr: Vec<u8> = vec![];
-            child
-                .stderr
-                .ok_or_else(|| "cannot get stderr".to_string())?
-                .read_to_end(&mut stderr)
-                .await
-                .map_err(|err| err.to_string())?;
-
-            Ok(Output {
-                status,
-                stdout: OsString::from_vec(stdout),
-                stderr: OsString::from_vec(stderr),
-            })
+            (status, usage)
         }
-        Ok(Err(err)) => Err(err.to_string()),
         // timeout
         Err(_) => {
-            child.kill().await.map_err(|err| err.to_string())?;
-            let output = child
-                .wait_with_output()
-                .await
-                .map_err(|err| format!("command execution failed: {}", err))?;
-            Ok(Output {
-                status: Status::Timeout,
-                stdout: OsString::from_vec(output.stdout),
-                stderr: OsString::from_vec(output.stderr),
-            })
+            let (_, usage) = waiter.kill().await?;
+            (Status::Timeout, usage)
         }
+    };
+
+    // processes in the group are still alive after exit
+    let leftover = check_leftover && killpg(pgid, None).is_ok();
+
+    // rest of stdin is no longer consumed
+    if let Some(stdin) = streams.stdin {
+        stdin.abort();
     }
+
+    let performed_actions = match streams.actions {
+        Some(actions) => actions.finish().await?,
+        None => 0,
+    };
+    let Streams {
+        mut stdout,
+        mut stderr,
+        merged,
+        fds,
+        ..
+    } = streams;
+    let _ = tokio::time::timeout_at(descendants_deadline.into(), async {
+        let _ = stdout.wait_closed().await;
+        let _ = stderr.wait_closed().await;
+    })
+    .await;
+    signal_group(pgid, Signal::SIGKILL)?;
+
+    let stdout_bytes = stdout.wait_closed().await?;
+    let stderr_bytes = stderr.wait_closed().await?;
+    let fd_outputs = fds.finish().await?;
+    // both streams are closed, so no more output is appended
+    let output = merged.snapshot();
+
+    // child is dropped after reaped, otherwise tokio may reap it instead of waiter
+    drop(child);
+
+    Ok(Output {
+        status,
+        stdout: OsString::from_vec(stdout_bytes),
+        stderr: OsString::from_vec(stderr_bytes),
+        output: OsString::from_vec(output),
+        stdout_arrivals: stdout.arrivals(),
+        stderr_arrivals: stderr.arrivals(),
+        fds: fd_outputs,
+        usage,
+        stop_stage: None,
+        leftover,
+        performed_actions,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_case::action::Trigger;
+    use crate::test_case::dialogue::DialogueStep;
+    use crate::test_case::extra_fd::ExtraFd;
+    use crate::test_case::testutil::ProcessTemplate;
+    use crate::test_case::wait_condition::StdoutCondition;
+    use crate::test_case::WindowSize;
+    use indexmap::indexmap;
+
+    // resource usage and arrival times are not deterministic, so they are ignored in comparison
+    fn without_nondeterministic(output: Output) -> Output {
+        Output {
+            stdout_arrivals: Arrivals::default(),
+            stderr_arrivals: Arrivals::default(),
+            usage: Usage::default(),
+            ..output
+        }
+    }
 
     mod execute_command {
         use super::*;
@@ -198,32 +728,650 @@ mod tests {
         #[tokio::test]
         #[case("printenv MESSAGE", "", vec![("MESSAGE", "hello")], 5, Status::Exit(0), "hello\n", "")]
         #[tokio::test]
-        #[case("kill -TERM $$", "", vec![], 5, Status::Signal(15), "", "")]
+        #[case("kill -TERM $$", "", vec![], 5, Status::Signal(15, false), "", "")]
         #[tokio::test]
         #[case("sleep 5", "", vec![], 1, Status::Timeout, "", "")]
         async fn success_cases(
-            #[case] command: &str,
-            #[case] stdin: &str,
-            #[case] env: Vec<(&str, &str)>,
+            #[case] command: &'static str,
+            #[case] stdin: &'static str,
+            #[case] env: Vec<(&'static str, &'static str)>,
             #[case] timeout: u64,
             #[case] status: Status,
             #[case] stdout: &str,
             #[case] stderr: &str,
         ) {
-            let actual = execute_command(
-                "bash",
-                &["-c".to_string(), command.to_string()],
-                stdin.to_string(),
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                stdin,
                 env,
-                Duration::from_secs(timeout),
-            )
-            .await;
+                timeout,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
 
             assert_eq!(
                 Ok(Output {
                     status,
                     stdout: stdout.into(),
-                    stderr: stderr.into()
+                    stderr: stderr.into(),
+                    output: [stdout, stderr].concat().into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case("no descendant is left", "echo hello", true, "hello\n", false)]
+        #[tokio::test]
+        #[case("descendant is left", "sleep 5 >/dev/null 2>&1 &", true, "", true)]
+        #[tokio::test]
+        #[case(
+            "descendant holding stdout is left",
+            "echo hello; sleep 100 &",
+            true,
+            "hello\n",
+            true
+        )]
+        #[tokio::test]
+        #[case(
+            "descendant holding stdout is killed without check",
+            "echo hello; sleep 100 &",
+            false,
+            "hello\n",
+            false
+        )]
+        async fn with_check_leftover(
+            #[case] title: &str,
+            #[case] command: &'static str,
+            #[case] check_leftover: bool,
+            #[case] stdout: &str,
+            #[case] leftover: bool,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                check_leftover,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status: Status::Exit(0),
+                    stdout: stdout.into(),
+                    stderr: "".into(),
+                    output: stdout.into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover,
+                    performed_actions: 0,
+                }),
+                actual,
+                "{}",
+                title
+            );
+        }
+
+        static LARGE_INPUT: once_cell::sync::Lazy<String> =
+            once_cell::sync::Lazy::new(|| "x".repeat(1024 * 1024));
+
+        #[rstest]
+        #[tokio::test]
+        #[case("cat", &LARGE_INPUT, 1024 * 1024)]
+        #[tokio::test]
+        #[case("head -c 1048576 /dev/zero; cat", "hello", 1024 * 1024 + 5)]
+        async fn with_large_streams(
+            #[case] command: &'static str,
+            #[case] stdin: &'static str,
+            #[case] expected_len: usize,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                stdin,
+                timeout: 5,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .unwrap();
+
+            assert_eq!(Status::Exit(0), actual.status);
+            assert_eq!(expected_len, actual.stdout.len());
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn with_interleaved_streams() {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", "echo a; sleep 0.1; echo b >&2; sleep 0.1; echo c"],
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .unwrap();
+
+            assert_eq!(OsString::from("a\nc\n"), actual.stdout);
+            assert_eq!(OsString::from("b\n"), actual.stderr);
+            assert_eq!(OsString::from("a\nb\nc\n"), actual.output);
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn with_usage() {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", "sleep 0.2"],
+                ..Default::default()
+            }
+            .build();
+
+            let usage = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .unwrap()
+                .usage;
+
+            assert!(usage.max_rss > 0, "{:?}", usage);
+            assert!(usage.wall_time >= Duration::from_millis(200), "{:?}", usage);
+            assert!(usage.wall_time < Duration::from_secs(5), "{:?}", usage);
+            assert!(
+                usage.user_time + usage.sys_time < usage.wall_time,
+                "{:?}",
+                usage
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn with_timeout_kills_descendants() {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", "sleep 5 & wait"],
+                timeout: 1,
+                ..Default::default()
+            }
+            .build();
+
+            let started = std::time::Instant::now();
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status: Status::Timeout,
+                    stdout: "".into(),
+                    stderr: "".into(),
+                    output: "".into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+            );
+            // stdout is closed without waiting for sleep
+            assert!(started.elapsed() < Duration::from_secs(3));
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case("cpu", "ulimit -t", Limits { cpu: Some(Duration::from_millis(1500)), ..Default::default() }, "2\n")]
+        #[tokio::test]
+        #[case("memory", "ulimit -v", Limits { memory: Some(256 * 1024 * 1024), ..Default::default() }, "262144\n")]
+        #[tokio::test]
+        #[case("nofile", "ulimit -n", Limits { nofile: Some(64), ..Default::default() }, "64\n")]
+        #[tokio::test]
+        #[case("fsize", "ulimit -f", Limits { fsize: Some(1024 * 1024), ..Default::default() }, "1024\n")]
+        #[tokio::test]
+        #[case("core", "ulimit -c", Limits { core: Some(0), ..Default::default() }, "0\n")]
+        async fn with_limits(
+            #[case] title: &str,
+            #[case] command: &'static str,
+            #[case] limits: Limits,
+            #[case] stdout: &str,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                limits,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status: Status::Exit(0),
+                    stdout: stdout.into(),
+                    stderr: "".into(),
+                    output: stdout.into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+                "{}",
+                title
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn with_binary_stdin() {
+            let mut process = ProcessTemplate {
+                command: "cat",
+                args: vec![],
+                ..Default::default()
+            }
+            .build();
+            process.stdin = vec![0x00, 0xff, 0x0a];
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(OsString::from_vec(vec![0x00, 0xff, 0x0a])),
+                actual.map(|output| output.stdout)
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case("with absolute path", None, "input.bin", Ok(vec![0x00, 0xff, 0x0a]))]
+        #[tokio::test]
+        #[case("with relative path from dir", Some("."), "input.bin", Ok(vec![0x00, 0xff, 0x0a]))]
+        #[tokio::test]
+        #[case("with not existing file", None, "missing.bin", Err("cannot open stdin file".to_string()))]
+        async fn with_stdin_file(
+            #[case] title: &str,
+            #[case] dir: Option<&str>,
+            #[case] file: &str,
+            #[case] expected: Result<Vec<u8>, String>,
+        ) {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let path = tmp_dir.path().join("input.bin");
+            std::fs::write(&path, [0x00, 0xff, 0x0a]).unwrap();
+
+            let mut process = ProcessTemplate {
+                command: "cat",
+                args: vec![],
+                ..Default::default()
+            }
+            .build();
+            process.stdin_file = Some(match dir {
+                Some(dir) => {
+                    process.dir = Some(tmp_dir.path().join(dir).to_string_lossy().to_string());
+                    file.to_string()
+                }
+                None => tmp_dir.path().join(file).to_string_lossy().to_string(),
+            });
+
+            let actual = execute_command(&process, (None, None), Pipes::default()).await;
+
+            match expected {
+                Ok(stdout) => assert_eq!(
+                    Ok(OsString::from_vec(stdout)),
+                    actual.map(|output| output.stdout),
+                    "{}",
+                    title
+                ),
+                Err(prefix) => assert!(
+                    actual.as_ref().is_err_and(|err| err.starts_with(&prefix)),
+                    "{}: {:?}",
+                    title,
+                    actual.map(|output| output.stdout)
+                ),
+            }
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case("input is read", "cat <&3", indexmap! { 3 => ExtraFd::Input("hello".to_string()) }, "hello", indexmap! {})]
+        #[tokio::test]
+        #[case("output is captured", "echo hello >&3", indexmap! { 3 => ExtraFd::Capture }, "", indexmap! { 3 => "hello\n" })]
+        #[tokio::test]
+        #[case("input is connected to output", "cat <&3 >&4", indexmap! { 3 => ExtraFd::Input("hello".to_string()), 4 => ExtraFd::Capture }, "", indexmap! { 4 => "hello" })]
+        #[tokio::test]
+        #[case("fds are swapped", "cat <&4 >&3", indexmap! { 4 => ExtraFd::Input("hello".to_string()), 3 => ExtraFd::Capture }, "", indexmap! { 3 => "hello" })]
+        #[tokio::test]
+        #[case("input is not read", "echo done", indexmap! { 3 => ExtraFd::Input(LARGE_INPUT.clone()) }, "done\n", indexmap! {})]
+        async fn with_extra_fds(
+            #[case] title: &str,
+            #[case] command: &'static str,
+            #[case] fds: IndexMap<RawFd, ExtraFd>,
+            #[case] stdout: &str,
+            #[case] captured: IndexMap<RawFd, &str>,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                fds,
+                timeout: 5,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .unwrap();
+
+            assert_eq!(Status::Exit(0), actual.status, "{}", title);
+            assert_eq!(OsString::from(stdout), actual.stdout, "{}", title);
+            assert_eq!(
+                captured
+                    .into_iter()
+                    .map(|(fd, output)| (fd, OsString::from(output)))
+                    .collect::<IndexMap<_, _>>(),
+                actual.fds,
+                "{}",
+                title
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn with_dir() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().canonicalize().unwrap();
+
+            let mut process = ProcessTemplate {
+                command: "pwd",
+                args: vec![],
+                ..Default::default()
+            }
+            .build();
+            process.dir = Some(path.to_string_lossy().to_string());
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status: Status::Exit(0),
+                    stdout: format!("{}\n", path.display()).into(),
+                    stderr: "".into(),
+                    output: format!("{}\n", path.display()).into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case(
+            "test -t 0 && test -t 1 && ! test -t 2 && echo tty",
+            "",
+            Status::Exit(0),
+            "tty\n",
+            ""
+        )]
+        #[tokio::test]
+        #[case("stty size", "", Status::Exit(0), "30 100\n", "")]
+        #[tokio::test]
+        #[case("cat", "hello", Status::Exit(0), "hello", "")]
+        #[tokio::test]
+        #[case("cat", "hello\nworld\n", Status::Exit(0), "hello\nworld\n", "")]
+        #[tokio::test]
+        #[case("echo hello >&2", "", Status::Exit(0), "", "hello\n")]
+        #[tokio::test]
+        #[case("echo hello; sleep 5", "", Status::Timeout, "hello\n", "")]
+        async fn with_tty(
+            #[case] command: &'static str,
+            #[case] stdin: &'static str,
+            #[case] status: Status,
+            #[case] stdout: &str,
+            #[case] stderr: &str,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                stdin,
+                timeout: 1,
+                tty: Some(WindowSize {
+                    rows: 30,
+                    cols: 100,
+                }),
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status,
+                    stdout: stdout.into(),
+                    stderr: stderr.into(),
+                    output: [stdout, stderr].concat().into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+            );
+        }
+
+        fn dialogue_step(expect: Option<&str>, send: &str, timeout_ms: u64) -> DialogueStep {
+            DialogueStep {
+                expect: expect.map(|pattern| StdoutCondition {
+                    pattern: regex::Regex::new(pattern).unwrap(),
+                    timeout: Duration::from_millis(timeout_ms),
+                }),
+                send: send.to_string(),
+            }
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case("echo -n 'Continue? [y/N] '; read ans; echo \"answer: $ans\"", None, vec![dialogue_step(Some("\\[y/N\\]"), "y\n", 1000)], "", Ok((Status::Exit(0), "Continue? [y/N] answer: y\n")))]
+        #[tokio::test]
+        #[case("echo -n 'name: '; read name; echo -n 'password: '; read pass; cat; echo \"$name:$pass\"", None, vec![dialogue_step(Some("name: "), "alice\n", 1000), dialogue_step(Some("password: "), "secret\n", 1000)], "rest\n", Ok((Status::Exit(0), "name: password: rest\nalice:secret\n")))]
+        #[tokio::test]
+        #[case("read ans; echo $ans", None, vec![dialogue_step(None, "y\n", 1000)], "", Ok((Status::Exit(0), "y\n")))]
+        #[tokio::test]
+        #[case("echo -n 'Continue? [y/N] '; read ans; echo \"answer: $ans\"", Some(WindowSize::default()), vec![dialogue_step(Some("\\[y/N\\]"), "y\n", 1000)], "", Ok((Status::Exit(0), "Continue? [y/N] answer: y\n")))]
+        #[tokio::test]
+        #[case("echo hello; sleep 5", None, vec![dialogue_step(Some("\\[y/N\\]"), "y\n", 100)], "", Err("dialogue[0]: stdout did not output \"\\[y/N\\]\" in 100ms\n\noutput so far:\nhello\n".to_string()))]
+        #[tokio::test]
+        #[case("echo hello", None, vec![dialogue_step(Some("\\[y/N\\]"), "y\n", 1000)], "", Err("dialogue[0]: stdout never output \"\\[y/N\\]\"\n\noutput so far:\nhello\n".to_string()))]
+        #[tokio::test]
+        #[case("echo hello; sleep 5", None, vec![dialogue_step(Some("\\[y/N\\]"), "y\n", 5000)], "", Ok((Status::Timeout, "hello\n")))]
+        #[tokio::test]
+        #[case("echo -n 'Continue? [y/N] '; sleep 5", Some(WindowSize::default()), vec![dialogue_step(Some("\\[y/N\\]"), "y\n", 1000), dialogue_step(Some("never"), "y\n", 5000)], "", Ok((Status::Timeout, "Continue? [y/N] ")))]
+        async fn with_dialogue(
+            #[case] command: &'static str,
+            #[case] tty: Option<WindowSize>,
+            #[case] dialogue: Vec<DialogueStep>,
+            #[case] stdin: &'static str,
+            #[case] expected: Result<(Status, &str), String>,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                stdin,
+                tty,
+                dialogue,
+                timeout: 3,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                expected.map(|(status, stdout)| Output {
+                    status,
+                    stdout: stdout.into(),
+                    stderr: "".into(),
+                    output: stdout.into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+            );
+        }
+
+        fn data(data: &str) -> StdinChunk {
+            StdinChunk::Data(data.to_string())
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case("cat", None, vec![data("hello\n"), data("world\n"), StdinChunk::Close], Status::Exit(0), "hello\nworld\n")]
+        #[tokio::test]
+        #[case("read a; read -t 0.1 b || echo waiting; read b; echo $a $b", None, vec![data("hello\n"), StdinChunk::Delay(Duration::from_millis(500)), data("world\n"), StdinChunk::Close], Status::Exit(0), "waiting\nhello world\n")]
+        #[tokio::test]
+        #[case("cat", None, vec![data("hello")], Status::Exit(0), "hello")]
+        #[tokio::test]
+        #[case("cat", Some(WindowSize::default()), vec![data("hello"), StdinChunk::Close], Status::Exit(0), "hello")]
+        #[tokio::test]
+        #[case("cat", Some(WindowSize::default()), vec![data("hello\n")], Status::Exit(0), "hello\n")]
+        async fn with_stdin_chunks(
+            #[case] command: &'static str,
+            #[case] tty: Option<WindowSize>,
+            #[case] chunks: Vec<StdinChunk>,
+            #[case] status: Status,
+            #[case] stdout: &str,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                stdin_chunks: Some(chunks),
+                tty,
+                timeout: 1,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status,
+                    stdout: stdout.into(),
+                    stderr: "".into(),
+                    output: stdout.into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case("trap 'echo hup; exit 0' HUP; echo ready; while true; do true; done", Trigger::OnStdout(regex::Regex::new("ready").unwrap()), Status::Exit(0), "ready\nhup\n", 1)]
+        #[tokio::test]
+        #[case(
+            "trap 'echo hup; exit 0' HUP; while true; do true; done",
+            Trigger::After(Duration::from_millis(100)),
+            Status::Exit(0),
+            "hup\n",
+            1
+        )]
+        #[tokio::test]
+        #[case("echo hello", Trigger::OnStdout(regex::Regex::new("ready").unwrap()), Status::Exit(0), "hello\n", 0)]
+        async fn with_actions(
+            #[case] command: &'static str,
+            #[case] trigger: Trigger,
+            #[case] status: Status,
+            #[case] stdout: &str,
+            #[case] performed_actions: usize,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                actions: vec![Action {
+                    trigger,
+                    signal: Signal::SIGHUP,
+                }],
+                timeout: 3,
+                ..Default::default()
+            }
+            .build();
+
+            let actual = execute_command(&process, (None, None), Pipes::default())
+                .await
+                .map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status,
+                    stdout: stdout.into(),
+                    stderr: "".into(),
+                    output: stdout.into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: None,
+                    leftover: false,
+                    performed_actions,
                 }),
                 actual,
             );
@@ -234,53 +1382,218 @@ mod tests {
     mod execute_background_command {
         use super::*;
         use crate::test_case::wait_condition::SleepCondition;
+        use crate::test_case::{BackgroundConfig, WaitCondition};
         use pretty_assertions::assert_eq;
         use rstest::*;
 
         #[rstest]
         #[tokio::test]
-        #[case("trap 'echo termed >&2; exit 1' TERM; echo hello; while true; do true; done", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(1), "hello\n", "termed\n")]
+        #[case("trap 'echo termed >&2; exit 1' TERM; echo hello; while true; do true; done", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(1), "hello\n", "termed\n", StopStage::Signal(Signal::SIGTERM))]
         #[tokio::test]
-        #[case("trap 'echo termed >&2; exit 1' TERM; echo hello", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(0), "hello\n", "")]
+        #[case("trap 'echo termed >&2; exit 1' TERM; echo hello", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(0), "hello\n", "", StopStage::Exited)]
         #[tokio::test]
-        #[case("trap 'echo termed >&2; sleep 2; echo sleeped; exit 1' TERM; echo hello; while true; do true; done", "", vec![], 1, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Timeout, "hello\n", "termed\n")]
+        #[case("trap 'echo termed >&2; sleep 2; echo sleeped; exit 1' TERM; echo hello; while true; do true; done", "", vec![], 1, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Timeout, "hello\n", "termed\n", StopStage::Kill)]
         #[tokio::test]
-        #[case("trap 'echo termed >&2; exit 1' TERM; cat; while true; do true; done", "hello", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(1), "hello", "termed\n")]
+        #[case("trap 'echo termed >&2; exit 1' TERM; cat; while true; do true; done", "hello", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(1), "hello", "termed\n", StopStage::Signal(Signal::SIGTERM))]
         #[tokio::test]
-        #[case("trap 'echo termed >&2; exit 1' TERM; printenv MESSAGE; while true; do true; done", "", vec![("MESSAGE", "hello")], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(1), "hello\n", "termed\n")]
+        #[case("trap 'echo termed >&2; exit 1' TERM; printenv MESSAGE; while true; do true; done", "", vec![("MESSAGE", "hello")], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Exit(1), "hello\n", "termed\n", StopStage::Signal(Signal::SIGTERM))]
         #[tokio::test]
-        #[case("trap 'echo termed >&2; exit 1' TERM; kill -INT $$", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Signal(2), "", "")]
+        #[case("trap 'echo termed >&2; exit 1' TERM; kill -INT $$", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Signal(2, false), "", "", StopStage::Exited)]
         #[tokio::test]
-        #[case("trap 'echo termed >&2; kill -INT $$' TERM; echo hello; while true; do true; done", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Signal(2), "hello\n", "termed\n")]
+        #[case("trap 'echo termed >&2; kill -INT $$' TERM; echo hello; while true; do true; done", "", vec![], 5, WaitCondition::Sleep(SleepCondition{ duration: Duration::from_millis(50) }), Status::Signal(2, false), "hello\n", "termed\n", StopStage::Signal(Signal::SIGTERM))]
         async fn success_cases(
-            #[case] command: &str,
-            #[case] stdin: &str,
-            #[case] env: Vec<(&str, &str)>,
+            #[case] command: &'static str,
+            #[case] stdin: &'static str,
+            #[case] env: Vec<(&'static str, &'static str)>,
             #[case] timeout: u64,
             #[case] wait_condition: WaitCondition,
             #[case] status: Status,
             #[case] stdout: &str,
             #[case] stderr: &str,
+            #[case] stop_stage: StopStage,
         ) {
-            let bg = execute_background_command(
-                "bash",
-                &["-c".to_string(), command.to_string()],
-                stdin.to_string(),
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                stdin,
                 env,
-                Duration::from_secs(timeout),
-                &wait_condition,
-                (false, false),
-            )
-            .await
-            .unwrap();
+                timeout,
+                ..Default::default()
+            }
+            .build();
+
+            let cfg = BackgroundConfig {
+                wait_condition,
+                ..Default::default()
+            };
+
+            let bg = execute_background_command(&process, &cfg, (None, None), Pipes::default())
+                .await
+                .unwrap();
+
+            let actual = bg.terminate().await.map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status,
+                    stdout: stdout.into(),
+                    stderr: stderr.into(),
+                    output: [stdout, stderr].concat().into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: Some(stop_stage),
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case(
+            "with stop_signal, sends it",
+            "trap 'echo inted >&2; exit 1' INT; echo hello; while true; do true; done",
+            Signal::SIGINT,
+            None,
+            Status::Exit(1),
+            "hello\n",
+            "inted\n",
+            StopStage::Signal(Signal::SIGINT)
+        )]
+        #[tokio::test]
+        #[case(
+            "with stop_timeout, kills after it",
+            "trap 'echo termed >&2' TERM; echo hello; while true; do true; done",
+            Signal::SIGTERM,
+            Some(100),
+            Status::Timeout,
+            "hello\n",
+            "termed\n",
+            StopStage::Kill
+        )]
+        #[tokio::test]
+        #[case(
+            "with exited leader, sends it to descendants",
+            "echo hello; bash -c \"trap 'echo termed >&2; exit' TERM; while true; do true; done\" &",
+            Signal::SIGTERM,
+            None,
+            Status::Exit(0),
+            "hello\n",
+            "termed\n",
+            StopStage::Exited
+        )]
+        #[tokio::test]
+        #[case(
+            "with exited leader, kills descendants after stop_timeout",
+            "echo hello; bash -c \"trap '' TERM; while true; do true; done\" &",
+            Signal::SIGTERM,
+            Some(100),
+            Status::Exit(0),
+            "hello\n",
+            "",
+            StopStage::Exited
+        )]
+        async fn stop_config_cases(
+            #[case] title: &str,
+            #[case] command: &'static str,
+            #[case] stop_signal: Signal,
+            #[case] stop_timeout: Option<u64>,
+            #[case] status: Status,
+            #[case] stdout: &str,
+            #[case] stderr: &str,
+            #[case] stop_stage: StopStage,
+        ) {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec!["-c", command],
+                timeout: 5,
+                ..Default::default()
+            }
+            .build();
+
+            let cfg = BackgroundConfig {
+                wait_condition: WaitCondition::Sleep(SleepCondition {
+                    duration: Duration::from_millis(50),
+                }),
+                stop_signal,
+                stop_timeout: stop_timeout.map(Duration::from_millis),
+                ..Default::default()
+            };
+
+            let bg = execute_background_command(&process, &cfg, (None, None), Pipes::default())
+                .await
+                .unwrap();
 
-            let actual = bg.terminate().await;
+            let actual = bg.terminate().await.map(without_nondeterministic);
 
             assert_eq!(
                 Ok(Output {
                     status,
                     stdout: stdout.into(),
-                    stderr: stderr.into()
+                    stderr: stderr.into(),
+                    output: [stdout, stderr].concat().into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: Some(stop_stage),
+                    leftover: false,
+                    performed_actions: 0,
+                }),
+                actual,
+                "{}",
+                title
+            );
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn with_actions() {
+            let process = ProcessTemplate {
+                command: "bash",
+                args: vec![
+                    "-c",
+                    "trap 'echo hup' HUP; echo hello; while true; do true; done",
+                ],
+                actions: vec![Action {
+                    trigger: Trigger::OnStdout(regex::Regex::new("hello").unwrap()),
+                    signal: Signal::SIGHUP,
+                }],
+                timeout: 5,
+                ..Default::default()
+            }
+            .build();
+
+            let cfg = BackgroundConfig {
+                wait_condition: WaitCondition::Sleep(SleepCondition {
+                    duration: Duration::from_millis(50),
+                }),
+                ..Default::default()
+            };
+
+            let bg = execute_background_command(&process, &cfg, (None, None), Pipes::default())
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+
+            let actual = bg.terminate().await.map(without_nondeterministic);
+
+            assert_eq!(
+                Ok(Output {
+                    status: Status::Signal(15, false),
+                    stdout: "hello\nhup\n".into(),
+                    stderr: "".into(),
+                    output: "hello\nhup\n".into(),
+                    stdout_arrivals: Arrivals::default(),
+                    stderr_arrivals: Arrivals::default(),
+                    fds: indexmap! {},
+                    usage: Usage::default(),
+                    stop_stage: Some(StopStage::Signal(Signal::SIGTERM)),
+                    leftover: false,
+                    performed_actions: 1,
                 }),
                 actual,
             );
@@ -0,0 +1,375 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use regex::Regex;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::watch;
+
+use super::pty;
+
+#[derive(Debug, Default)]
+struct Captured {
+    buf: Vec<u8>,
+    arrivals: Vec<(usize, Instant)>,
+    closed: bool,
+    error: Option<String>,
+}
+
+// prints lines of the stream while the process runs,
+// prefixed with the label (may be colored) and elapsed time from the start of the test case
+#[derive(Clone, Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Tee {
+    pub label: String,
+    pub started: Instant,
+}
+
+impl Tee {
+    fn print_lines(&self, pending: &mut Vec<u8>, closed: bool) {
+        let mut out = std::io::stdout().lock();
+        let _ = self.write_lines(
+            &mut out,
+            self.started.elapsed().as_secs_f64(),
+            pending,
+            closed,
+        );
+        let _ = out.flush();
+    }
+
+    // writes complete lines in `pending` and removes them.
+    // when the stream is closed, the last line without newline is also written.
+    fn write_lines<W: Write>(
+        &self,
+        w: &mut W,
+        elapsed: f64,
+        pending: &mut Vec<u8>,
+        closed: bool,
+    ) -> std::io::Result<()> {
+        while let Some(i) = pending.iter().position(|b| *b == b'\n') {
+            write!(w, "[{} +{:.3}s] ", self.label, elapsed)?;
+            w.write_all(&pending.drain(..=i).collect::<Vec<_>>())?;
+        }
+        if closed && !pending.is_empty() {
+            write!(w, "[{} +{:.3}s] ", self.label, elapsed)?;
+            w.write_all(pending)?;
+            writeln!(w)?;
+            pending.clear();
+        }
+        Ok(())
+    }
+}
+
+// when each part of the output arrived.
+// each entry is the end position of the bytes read at once and the time.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Arrivals(Vec<(usize, Instant)>);
+
+impl Arrivals {
+    // returns when the byte at `pos` arrived
+    fn time_of(&self, pos: usize) -> Option<Instant> {
+        let i = self.0.partition_point(|(end, _)| *end <= pos);
+        self.0.get(i).map(|(_, at)| *at)
+    }
+
+    // returns when the first line matching the pattern in `buf` arrived
+    pub fn first_line_matching(&self, pattern: &Regex, buf: &[u8]) -> Option<Instant> {
+        let mut start = 0;
+        for line in buf.split_inclusive(|b| *b == b'\n') {
+            let end = start + line.len();
+            let text = String::from_utf8_lossy(line.strip_suffix(b"\n").unwrap_or(line));
+            if pattern.is_match(&text) {
+                return self.time_of(end - 1);
+            }
+            start = end;
+        }
+        None
+    }
+}
+
+// stdout and stderr in the order they were read, like `2>&1`.
+// captures of both streams append to the same buffer.
+#[derive(Clone, Debug, Default)]
+pub struct Merged {
+    buf: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Merged {
+    fn append(&self, chunk: &[u8]) {
+        self.buf.lock().unwrap().extend_from_slice(chunk);
+    }
+
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.buf.lock().unwrap().clone()
+    }
+}
+
+// output of a stream which is read continuously by a background task from spawn.
+// it can be shared, so wait conditions, actions and final matchers see the same bytes.
+#[derive(Clone, Debug)]
+pub struct Capture {
+    rx: watch::Receiver<Captured>,
+}
+
+impl Capture {
+    pub fn start<R: AsyncRead + Unpin + Send + 'static>(
+        reader: Option<R>,
+        name: &str,
+        tee: Option<Tee>,
+        merged: Merged,
+    ) -> Self {
+        let (tx, rx) = watch::channel(Captured::default());
+        let name = name.to_string();
+
+        tokio::spawn(async move {
+            let mut pending = vec![];
+            let result = match reader {
+                Some(mut reader) => {
+                    let mut chunk = [0u8; 8192];
+                    loop {
+                        match reader.read(&mut chunk).await {
+                            Ok(0) => break Ok(()),
+                            Ok(n) => {
+                                merged.append(&chunk[..n]);
+                                tx.send_modify(|c| {
+                                    c.buf.extend_from_slice(&chunk[..n]);
+                                    c.arrivals.push((c.buf.len(), Instant::now()));
+                                });
+                                if let Some(tee) = &tee {
+                                    pending.extend_from_slice(&chunk[..n]);
+                                    tee.print_lines(&mut pending, false);
+                                }
+                            }
+                            Err(err) if pty::is_closed(&err) => break Ok(()),
+                            Err(err) => break Err(err.to_string()),
+                        }
+                    }
+                }
+                None => Err(format!("cannot get {}", name)),
+            };
+            if let Some(tee) = &tee {
+                tee.print_lines(&mut pending, true);
+            }
+
+            tx.send_modify(|c| {
+                c.closed = true;
+                c.error = result.err();
+            });
+        });
+
+        Self { rx }
+    }
+
+    // waits until `f` returns Some for the output so far.
+    // returns None when the stream is closed before that.
+    pub async fn wait_for<T>(
+        &mut self,
+        mut f: impl FnMut(&[u8]) -> Option<T>,
+    ) -> Result<Option<T>, String> {
+        loop {
+            {
+                let captured = self.rx.borrow_and_update();
+                if let Some(x) = f(&captured.buf) {
+                    return Ok(Some(x));
+                }
+                if captured.closed {
+                    return captured.error.clone().map_or(Ok(None), Err);
+                }
+            }
+
+            if self.rx.changed().await.is_err() {
+                // reader task is finished, so the last state is already seen
+                let captured = self.rx.borrow();
+                return Ok(f(&captured.buf));
+            }
+        }
+    }
+
+    // waits until the stream is closed, and returns whole output
+    pub async fn wait_closed(&mut self) -> Result<Vec<u8>, String> {
+        self.wait_for(|_| None::<()>).await?;
+        Ok(self.snapshot())
+    }
+
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.rx.borrow().buf.clone()
+    }
+
+    pub fn arrivals(&self) -> Arrivals {
+        Arrivals(self.rx.borrow().arrivals.clone())
+    }
+}
+
+// finds the pattern in `buf` from `start`, and returns end position of the matched part
+pub fn find_from(pattern: &regex::Regex, buf: &[u8], start: usize) -> Option<usize> {
+    // nothing may be captured yet after `start`
+    let unmatched = buf.get(start..)?;
+    let text = match std::str::from_utf8(unmatched) {
+        Ok(text) => text,
+        // incomplete multibyte character may be at the end
+        Err(err) => std::str::from_utf8(&unmatched[..err.valid_up_to()]).unwrap(),
+    };
+
+    pattern.find(text).map(|m| start + m.end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use std::time::Duration;
+
+    fn spawn_bash(command: &str) -> tokio::process::Child {
+        tokio::process::Command::new("bash")
+            .arg("-c")
+            .arg(command)
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case(
+        "with complete lines",
+        "hello\nworld\n",
+        false,
+        "[main:stdout +1.500s] hello\n[main:stdout +1.500s] world\n",
+        ""
+    )]
+    #[case(
+        "with incomplete line",
+        "hello\nwor",
+        false,
+        "[main:stdout +1.500s] hello\n",
+        "wor"
+    )]
+    #[case(
+        "with incomplete line when closed",
+        "hello\nwor",
+        true,
+        "[main:stdout +1.500s] hello\n[main:stdout +1.500s] wor\n",
+        ""
+    )]
+    fn write_lines(
+        #[case] title: &str,
+        #[case] pending: &str,
+        #[case] closed: bool,
+        #[case] expected_written: &str,
+        #[case] expected_pending: &str,
+    ) {
+        let tee = Tee {
+            label: "main:stdout".to_string(),
+            started: Instant::now(),
+        };
+        let mut w = vec![];
+        let mut pending = pending.as_bytes().to_vec();
+
+        tee.write_lines(&mut w, 1.5, &mut pending, closed).unwrap();
+
+        assert_eq!(expected_written, String::from_utf8(w).unwrap(), "{}", title);
+        assert_eq!(expected_pending.as_bytes(), pending, "{}", title);
+    }
+
+    #[rstest]
+    #[case("in first chunk", "^hello$", Some(0))]
+    #[case("in second chunk", "^world$", Some(1))]
+    #[case("across chunks", "^wide$", Some(1))]
+    #[case("without newline at the end", "^bye$", Some(2))]
+    #[case("not found", "^hel$", None)]
+    fn first_line_matching(
+        #[case] title: &str,
+        #[case] pattern: &str,
+        #[case] expected_secs: Option<u64>,
+    ) {
+        let started = Instant::now();
+        let at = |secs| started + Duration::from_secs(secs);
+        let arrivals = Arrivals(vec![(8, at(0)), (17, at(1)), (20, at(2))]);
+
+        let actual =
+            arrivals.first_line_matching(&Regex::new(pattern).unwrap(), b"hello\nwide\nworld\nbye");
+
+        assert_eq!(expected_secs.map(at), actual, "{}", title);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn wait_closed() {
+        let mut child = spawn_bash("echo hello; echo world");
+        let mut capture = Capture::start(child.stdout.take(), "stdout", None, Merged::default());
+
+        assert_eq!(Ok(b"hello\nworld\n".to_vec()), capture.wait_closed().await);
+        // clones share the same output
+        assert_eq!(b"hello\nworld\n".to_vec(), capture.clone().snapshot());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn wait_closed_with_merged() {
+        let mut child = tokio::process::Command::new("bash")
+            .arg("-c")
+            .arg("echo a; sleep 0.1; echo b >&2; sleep 0.1; echo c")
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        let merged = Merged::default();
+        let mut stdout = Capture::start(child.stdout.take(), "stdout", None, merged.clone());
+        let mut stderr = Capture::start(child.stderr.take(), "stderr", None, merged.clone());
+
+        assert_eq!(Ok(b"a\nc\n".to_vec()), stdout.wait_closed().await);
+        assert_eq!(Ok(b"b\n".to_vec()), stderr.wait_closed().await);
+        assert_eq!(b"a\nb\nc\n".to_vec(), merged.snapshot());
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn wait_closed_without_reader() {
+        let mut capture = Capture::start(
+            None::<tokio::process::ChildStdout>,
+            "stdout",
+            None,
+            Merged::default(),
+        );
+
+        assert_eq!(
+            Err("cannot get stdout".to_string()),
+            capture.wait_closed().await
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    #[case(
+        "when found, returns end position",
+        "echo -n hello; sleep 1; echo world",
+        0,
+        Ok(Some(10))
+    )]
+    #[tokio::test]
+    #[case(
+        "when found after start, returns end position",
+        "echo -n 'world hello world'",
+        1,
+        Ok(Some(17))
+    )]
+    #[tokio::test]
+    #[case("when closed before found, returns None", "echo -n hello", 0, Ok(None))]
+    async fn wait_for(
+        #[case] title: &str,
+        #[case] command: &str,
+        #[case] start: usize,
+        #[case] expected: Result<Option<usize>, String>,
+    ) {
+        let mut child = spawn_bash(command);
+        let mut capture = Capture::start(child.stdout.take(), "stdout", None, Merged::default());
+        let pattern = Regex::new("wo.ld").unwrap();
+
+        let actual = capture
+            .wait_for(|buf| find_from(&pattern, buf, start))
+            .await;
+
+        assert_eq!(expected, actual, "{}", title);
+    }
+}
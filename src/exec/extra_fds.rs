@@ -0,0 +1,100 @@
+use std::ffi::OsString;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::ffi::OsStringExt;
+
+use indexmap::{indexmap, IndexMap};
+use nix::fcntl::{fcntl, FcntlArg};
+use nix::unistd::dup2;
+use tokio::net::unix::pipe;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+use crate::test_case::extra_fd::ExtraFd;
+
+use super::capture::{Capture, Merged};
+use super::write_input;
+
+// other ends of pipes connected to extra fds of the process.
+// inputs are written and outputs are captured concurrently with the process.
+#[derive(Debug, Default)]
+pub struct ExtraFds {
+    writers: Vec<JoinHandle<()>>,
+    captures: IndexMap<RawFd, Capture>,
+}
+
+impl ExtraFds {
+    // connects pipes to the fds of the command when it is spawned
+    pub fn open(cmd: &mut Command, fds: &IndexMap<RawFd, ExtraFd>) -> Result<Self, String> {
+        let mut extra_fds = Self::default();
+        if fds.is_empty() {
+            return Ok(extra_fds);
+        }
+
+        // ends for the child are numbered above all fds to be set,
+        // so that no end is overwritten by dup2 before it is moved
+        let min = fds.keys().max().copied().unwrap_or_default() + 1;
+        let mut child_ends = vec![];
+        for (fd, extra_fd) in fds {
+            let (reader, writer) =
+                std::io::pipe().map_err(|err| format!("cannot create pipe: {}", err))?;
+            match extra_fd {
+                ExtraFd::Input(input) => {
+                    child_ends.push((*fd, dup_above(reader.into(), min)?));
+                    let mut sender = pipe::Sender::from_owned_fd(writer.into())
+                        .map_err(|err| format!("cannot open fd {}: {}", fd, err))?;
+                    let input = input.clone().into_bytes();
+                    extra_fds.writers.push(tokio::spawn(async move {
+                        write_input(&mut sender, &input).await;
+                    }));
+                }
+                ExtraFd::Capture => {
+                    child_ends.push((*fd, dup_above(writer.into(), min)?));
+                    let receiver = pipe::Receiver::from_owned_fd(reader.into())
+                        .map_err(|err| format!("cannot open fd {}: {}", fd, err))?;
+                    let capture = Capture::start(
+                        Some(receiver),
+                        &format!("fd{}", fd),
+                        None,
+                        Merged::default(),
+                    );
+                    extra_fds.captures.insert(*fd, capture);
+                }
+            }
+        }
+
+        // ends are closed in texest when the command is dropped after spawn
+        unsafe {
+            cmd.pre_exec(move || {
+                for (fd, end) in &child_ends {
+                    dup2(end.as_raw_fd(), *fd)?;
+                }
+                Ok(())
+            });
+        }
+
+        Ok(extra_fds)
+    }
+
+    // rest of inputs is no longer consumed
+    pub fn abort(&self) {
+        self.writers.iter().for_each(|writer| writer.abort());
+    }
+
+    // waits until all captured fds are closed, and returns their outputs
+    pub async fn finish(self) -> Result<IndexMap<RawFd, OsString>, String> {
+        self.abort();
+
+        let mut outputs = indexmap! {};
+        for (fd, mut capture) in self.captures {
+            outputs.insert(fd, OsString::from_vec(capture.wait_closed().await?));
+        }
+        Ok(outputs)
+    }
+}
+
+// duplicates the fd to the lowest number from `min`, which is closed on exec
+fn dup_above(fd: OwnedFd, min: RawFd) -> Result<OwnedFd, String> {
+    let raw = fcntl(fd.as_raw_fd(), FcntlArg::F_DUPFD_CLOEXEC(min))
+        .map_err(|err| format!("cannot duplicate fd: {}", err))?;
+    Ok(unsafe { OwnedFd::from_raw_fd(raw) })
+}
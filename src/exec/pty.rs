@@ -0,0 +1,91 @@
+use std::io;
+use std::os::fd::OwnedFd;
+use std::process::Stdio;
+
+use nix::errno::Errno;
+use nix::libc;
+use nix::pty::{openpty, Winsize};
+use nix::sys::termios::{tcgetattr, tcsetattr, LocalFlags, OutputFlags, SetArg};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+use crate::test_case::WindowSize;
+
+pub struct Pty {
+    master: OwnedFd,
+    slave: OwnedFd,
+}
+
+impl Pty {
+    pub fn open(size: &WindowSize) -> Result<Self, String> {
+        let winsize = Winsize {
+            ws_row: size.rows,
+            ws_col: size.cols,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let pty = openpty(&winsize, None).map_err(|err| format!("cannot open pty: {}", err))?;
+
+        // disable echo back and CRLF conversion so that outputs are same as without tty
+        let mut termios =
+            tcgetattr(&pty.slave).map_err(|err| format!("cannot get pty attributes: {}", err))?;
+        termios.local_flags.remove(LocalFlags::ECHO);
+        termios.output_flags.remove(OutputFlags::ONLCR);
+        tcsetattr(&pty.slave, SetArg::TCSANOW, &termios)
+            .map_err(|err| format!("cannot set pty attributes: {}", err))?;
+
+        // openpty does not set close-on-exec, so replace them with duplicated ones
+        Ok(Self {
+            master: clone_fd(&pty.master)?,
+            slave: clone_fd(&pty.slave)?,
+        })
+    }
+
+    pub fn attach(&self, cmd: &mut Command) -> Result<(), String> {
+        cmd.stdin(Stdio::from(clone_fd(&self.slave)?))
+            .stdout(Stdio::from(clone_fd(&self.slave)?));
+
+        unsafe {
+            cmd.pre_exec(|| {
+                nix::unistd::setsid()?;
+                if libc::ioctl(0, libc::TIOCSCTTY, 0) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn connect(self, child: &mut Child) -> Result<(), String> {
+        let Pty { master, slave } = self;
+        drop(slave);
+
+        let stdout = std::process::ChildStdout::from(clone_fd(&master)?);
+        let stdin = std::process::ChildStdin::from(master);
+        child.stdout = Some(ChildStdout::from_std(stdout).map_err(|err| err.to_string())?);
+        child.stdin = Some(ChildStdin::from_std(stdin).map_err(|err| err.to_string())?);
+
+        Ok(())
+    }
+}
+
+// terminal cannot be closed from writer side, so EOF is sent as VEOF character.
+// VEOF flushes pending line instead of closing when line is not empty, so send it twice in such case.
+// `last` is the last byte written before.
+pub fn eof_after(last: Option<u8>) -> Vec<u8> {
+    match last {
+        None | Some(b'\n') => vec![0x04],
+        Some(_) => vec![0x04, 0x04],
+    }
+}
+
+// reading master after all slaves are closed fails with EIO instead of EOF
+pub fn is_closed(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(Errno::EIO as i32)
+}
+
+fn clone_fd(fd: &OwnedFd) -> Result<OwnedFd, String> {
+    fd.try_clone()
+        .map_err(|err| format!("cannot duplicate pty: {}", err))
+}
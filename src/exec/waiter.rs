@@ -0,0 +1,130 @@
+use std::io;
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+
+use nix::errno::Errno;
+use nix::libc;
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
+use tokio::process::Child;
+use tokio::task::JoinHandle;
+
+use super::Usage;
+
+// waits the process with wait4 in blocking thread to get its resource usage.
+// Child::wait of tokio must not be used together because the process is reaped here.
+#[derive(Debug)]
+pub struct Waiter {
+    pid: Pid,
+    started: Instant,
+    handle: JoinHandle<io::Result<(ExitStatus, Usage)>>,
+}
+
+impl Waiter {
+    pub fn new(child: &Child) -> Result<Self, String> {
+        let pid = child
+            .id()
+            .map(|id| Pid::from_raw(id as i32))
+            .ok_or_else(|| "could not get pid".to_string())?;
+        let started = Instant::now();
+        let handle = tokio::task::spawn_blocking(move || wait4(pid, started));
+
+        Ok(Self {
+            pid,
+            started,
+            handle,
+        })
+    }
+
+    // process runs in own process group, so its id is same as pid
+    pub fn pgid(&self) -> Pid {
+        self.pid
+    }
+
+    pub fn started(&self) -> Instant {
+        self.started
+    }
+
+    pub fn has_exited(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub async fn wait(&mut self) -> Result<(ExitStatus, Usage), String> {
+        (&mut self.handle)
+            .await
+            .map_err(|err| err.to_string())?
+            .map_err(|err| err.to_string())
+    }
+
+    pub async fn kill(mut self) -> Result<(ExitStatus, Usage), String> {
+        signal_group(self.pid, Signal::SIGKILL)?;
+        self.wait().await
+    }
+}
+
+// sends the signal to the process group. the group may be already gone when the leader
+// is reaped in the blocking thread, and it is not an error.
+pub fn signal_group(pgid: Pid, signal: Signal) -> Result<(), String> {
+    match killpg(pgid, signal) {
+        Ok(()) | Err(Errno::ESRCH) => Ok(()),
+        Err(err) => Err(format!("could not send signal to {}: {}", pgid, err)),
+    }
+}
+
+fn wait4(pid: Pid, started: Instant) -> io::Result<(ExitStatus, Usage)> {
+    let mut status = 0;
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+
+    loop {
+        if unsafe { libc::wait4(pid.as_raw(), &mut status, 0, &mut rusage) } >= 0 {
+            let wall_time = started.elapsed();
+            return Ok((ExitStatus::from_raw(status), usage_of(&rusage, wall_time)));
+        }
+
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+fn usage_of(rusage: &libc::rusage, wall_time: Duration) -> Usage {
+    // ru_maxrss is in kilobytes except macOS
+    #[cfg(target_os = "macos")]
+    let max_rss = rusage.ru_maxrss as u64;
+    #[cfg(not(target_os = "macos"))]
+    let max_rss = rusage.ru_maxrss as u64 * 1024;
+
+    Usage {
+        max_rss,
+        user_time: duration_of(&rusage.ru_utime),
+        sys_time: duration_of(&rusage.ru_stime),
+        wall_time,
+    }
+}
+
+fn duration_of(tv: &libc::timeval) -> Duration {
+    Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[tokio::test]
+    async fn kill_exited_process() {
+        let child = tokio::process::Command::new("true")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let mut waiter = Waiter::new(&child).unwrap();
+        let (status, _) = waiter.wait().await.unwrap();
+
+        assert_eq!(Some(0), status.code());
+        assert_eq!(Ok(()), signal_group(waiter.pgid(), Signal::SIGKILL));
+    }
+}
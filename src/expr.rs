@@ -15,11 +15,13 @@ pub enum Expr {
     Json(Box<Expr>),
     TmpFile(String, Box<Expr>),
     TmpPort,
+    WorkDir,
     Var(String),
 }
 
 pub struct Context<'a, 'b, T: TmpDirSupplier> {
     tmp_dir_cell: OnceCell<PathBuf>,
+    work_dir_cell: OnceCell<PathBuf>,
     tmp_dir_supplier: &'a mut T,
     tmp_port_reservers: &'b mut IndexMap<u16, TcpListener>,
     variables: IndexMap<String, Yaml>,
@@ -38,6 +40,7 @@ impl<'a, 'b, T: TmpDirSupplier> Context<'a, 'b, T> {
     ) -> Self {
         Context {
             tmp_dir_cell: OnceCell::new(),
+            work_dir_cell: OnceCell::new(),
             tmp_dir_supplier,
             tmp_port_reservers,
             variables: IndexMap::new(),
@@ -106,6 +109,10 @@ impl<'a, 'b, T: TmpDirSupplier> Context<'a, 'b, T> {
                     setup_hooks: vec![],
                 })
             }
+            Expr::WorkDir => self.force_work_dir().map(|work_dir| EvalOutput {
+                value: Yaml::String(work_dir.to_string_lossy().to_string()),
+                setup_hooks: vec![],
+            }),
             Expr::Var(name) => self.lookup_var(name).map(|value| EvalOutput {
                 value,
                 setup_hooks: vec![],
@@ -129,6 +136,10 @@ impl<'a, 'b, T: TmpDirSupplier> Context<'a, 'b, T> {
             .ok_or_else(|| format!("variable {} is not defined", name))
     }
 
+    pub fn work_dir(&self) -> Option<&PathBuf> {
+        self.work_dir_cell.get()
+    }
+
     fn force_tmp_dir(&mut self) -> Result<&PathBuf, String> {
         self.tmp_dir_cell.get_or_try_init(|| {
             self.tmp_dir_supplier
@@ -136,10 +147,18 @@ impl<'a, 'b, T: TmpDirSupplier> Context<'a, 'b, T> {
                 .map(|path| path.to_path_buf())
         })
     }
+
+    fn force_work_dir(&mut self) -> Result<&PathBuf, String> {
+        self.work_dir_cell.get_or_try_init(|| {
+            self.tmp_dir_supplier
+                .create()
+                .map(|path| path.to_path_buf())
+        })
+    }
 }
 
 // FIXME: too naive implementation
-fn to_json_value(yaml: &Yaml) -> Result<serde_json::Value, String> {
+pub fn to_json_value(yaml: &Yaml) -> Result<serde_json::Value, String> {
     match yaml {
         Yaml::Null => Ok(serde_json::Value::Null),
         Yaml::Boolean(b) => Ok(serde_json::Value::Bool(*b)),
@@ -168,7 +187,7 @@ fn to_json_value(yaml: &Yaml) -> Result<serde_json::Value, String> {
             })
             .collect::<Result<_, _>>()
             .map(serde_json::Value::Object),
-        _ => panic!("unsupported type: {:?}", yaml),
+        _ => Err(format!("unsupported type: {:?}", yaml)),
     }
 }
 
@@ -344,6 +363,28 @@ x:
             assert_eq!(*port, listener_port);
         }
 
+        #[rstest]
+        fn eval_expr_work_dir() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let tmp_dir_path = tmp_dir.path().to_path_buf();
+            let mut tmp_dir_suppilier = StubTmpDirFactory { tmp_dir: &tmp_dir };
+            let mut tmp_port_reservers = indexmap! {};
+            let mut ctx = Context::new(&mut tmp_dir_suppilier, &mut tmp_port_reservers);
+
+            assert_eq!(None, ctx.work_dir());
+
+            let actual = ctx.eval_expr(&Expr::WorkDir);
+
+            assert_eq!(
+                Ok(EvalOutput {
+                    value: Yaml::String(tmp_dir_path.to_string_lossy().to_string()),
+                    setup_hooks: vec![]
+                }),
+                actual
+            );
+            assert_eq!(Some(&tmp_dir_path), ctx.work_dir());
+        }
+
         #[rstest]
         fn lookup_var_when_not_defined() {
             let tmp_dir = tempfile::tempdir().unwrap();
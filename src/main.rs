@@ -42,6 +42,8 @@ struct Args {
     tee_stdout: bool,
     #[clap(long = "tee-stderr", default_value = "false")]
     tee_stderr: bool,
+    #[clap(long = "update-snapshots", default_value = "false")]
+    update_snapshots: bool,
 }
 
 fn main() {
@@ -85,6 +87,7 @@ fn main() {
         std::io::stderr(),
         args.tee_stdout,
         args.tee_stderr,
+        args.update_snapshots,
     )
     .run(inputs)
     {
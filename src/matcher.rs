@@ -1,10 +1,12 @@
+mod signal;
 mod status;
 mod stream;
+mod usage;
 
-use crate::validator::Validator;
-
+pub use signal::SignalMatcher;
 pub use status::StatusMatcher;
 pub use stream::StreamMatcher;
+pub use usage::UsageMatcher;
 
 const NOT_PREFIX: &str = "not.";
 
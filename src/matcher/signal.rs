@@ -0,0 +1,115 @@
+use nix::sys::signal::Signal;
+use saphyr::Yaml;
+
+use crate::validator::Validator;
+
+use super::parse_name;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum SignalMatcher {
+    Eq(Signal),
+}
+
+impl SignalMatcher {
+    // actual is None when the process is not signaled
+    pub fn matches(&self, actual: Option<i32>) -> Result<(bool, String), String> {
+        match self {
+            SignalMatcher::Eq(expected) => {
+                let matched = actual == Some(*expected as i32);
+
+                Ok((
+                    matched,
+                    if matched {
+                        format!(
+                            "should not be signaled with {}, but got it",
+                            expected.as_str()
+                        )
+                    } else if let Some(actual) = actual {
+                        format!(
+                            "should be signaled with {}, but got {}",
+                            expected.as_str(),
+                            signal_name(actual)
+                        )
+                    } else {
+                        format!(
+                            "should be signaled with {}, but not signaled",
+                            expected.as_str()
+                        )
+                    },
+                ))
+            }
+        }
+    }
+
+    pub fn parse(v: &mut Validator, name: &str, param: &Yaml) -> Option<(Self, bool)> {
+        let (name, expected_passed) = parse_name(name);
+
+        match name {
+            "eq" => v.in_field(name, |v| v.must_be_signal(param).map(SignalMatcher::Eq)),
+            _ => {
+                v.add_violation(format!("signal matcher \"{}\" is not defined", name));
+                None
+            }
+        }
+        .map(|m| (m, expected_passed))
+    }
+}
+
+fn signal_name(signal: i32) -> String {
+    Signal::try_from(signal)
+        .map(|signal| signal.as_str().to_string())
+        .unwrap_or_else(|_| signal.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::testutil;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(Some(11), true, "should not be signaled with SIGSEGV, but got it")]
+    #[case(Some(15), false, "should be signaled with SIGSEGV, but got SIGTERM")]
+    #[case(Some(100), false, "should be signaled with SIGSEGV, but got 100")]
+    #[case(None, false, "should be signaled with SIGSEGV, but not signaled")]
+    fn matches(
+        #[case] given: Option<i32>,
+        #[case] expected_matched: bool,
+        #[case] expected_message: &str,
+    ) {
+        let m = SignalMatcher::Eq(Signal::SIGSEGV);
+
+        assert_eq!(
+            Ok((expected_matched, expected_message.to_string())),
+            m.matches(given)
+        );
+    }
+
+    #[rstest]
+    #[case("with eq", "eq", Yaml::String("SEGV".to_string()), Some((SignalMatcher::Eq(Signal::SIGSEGV), true)), vec![])]
+    #[case("with not.eq", "not.eq", Yaml::String("SIGHUP".to_string()), Some((SignalMatcher::Eq(Signal::SIGHUP), false)), vec![])]
+    #[case("with invalid signal", "eq", Yaml::String("UNKNOWN".to_string()), None, vec![(".eq", "should be signal name, but is invalid string \"UNKNOWN\"")])]
+    #[case("with unknown name", "unknown", Yaml::String("SEGV".to_string()), None, vec![("", "signal matcher \"unknown\" is not defined")])]
+    fn parse(
+        #[case] title: &str,
+        #[case] name: &str,
+        #[case] param: Yaml,
+        #[case] expected_value: Option<(SignalMatcher, bool)>,
+        #[case] expected_violation: Vec<(&str, &str)>,
+    ) {
+        let (mut v, violation) = testutil::new_validator();
+        let actual = SignalMatcher::parse(&mut v, name, &param);
+
+        assert_eq!(expected_value, actual, "{}", title);
+        assert_eq!(
+            expected_violation
+                .iter()
+                .map(|(path, msg)| violation(path, msg))
+                .collect::<Vec<_>>(),
+            v.violations,
+            "{}",
+            title
+        );
+    }
+}
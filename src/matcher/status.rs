@@ -1,13 +1,30 @@
+mod between;
 mod eq;
+mod gt;
+mod in_list;
+mod lt;
+mod success;
 
+use between::BetweenMatcher;
 use eq::EqMatcher;
+use gt::GtMatcher;
+use in_list::InMatcher;
+use lt::LtMatcher;
 use saphyr::Yaml;
+use success::SuccessMatcher;
+
+use crate::validator::Validator;
 
 use super::parse_name;
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub enum StatusMatcher {
     Eq(eq::EqMatcher),
+    In(in_list::InMatcher),
+    Gt(gt::GtMatcher),
+    Lt(lt::LtMatcher),
+    Between(between::BetweenMatcher),
+    Success(success::SuccessMatcher),
     #[cfg(test)]
     Test(super::testutil::TestMatcher),
 }
@@ -16,12 +33,17 @@ impl StatusMatcher {
     pub fn matches(&self, actual: i32) -> Result<(bool, String), String> {
         match self {
             StatusMatcher::Eq(m) => m.matches(actual),
+            StatusMatcher::In(m) => m.matches(actual),
+            StatusMatcher::Gt(m) => m.matches(actual),
+            StatusMatcher::Lt(m) => m.matches(actual),
+            StatusMatcher::Between(m) => m.matches(actual),
+            StatusMatcher::Success(m) => m.matches(actual),
             #[cfg(test)]
             StatusMatcher::Test(m) => m.matches(actual),
         }
     }
 
-    pub fn parse(v: &mut super::Validator, name: &str, param: &Yaml) -> Option<(Self, bool)> {
+    pub fn parse(v: &mut Validator, name: &str, param: &Yaml) -> Option<(Self, bool)> {
         let (name, expected_passed) = parse_name(name);
 
         #[cfg(test)]
@@ -31,6 +53,15 @@ impl StatusMatcher {
 
         match name {
             "eq" => v.in_field(name, |v| EqMatcher::parse(v, param).map(StatusMatcher::Eq)),
+            "in" => v.in_field(name, |v| InMatcher::parse(v, param).map(StatusMatcher::In)),
+            "gt" => v.in_field(name, |v| GtMatcher::parse(v, param).map(StatusMatcher::Gt)),
+            "lt" => v.in_field(name, |v| LtMatcher::parse(v, param).map(StatusMatcher::Lt)),
+            "between" => v.in_field(name, |v| {
+                BetweenMatcher::parse(v, param).map(StatusMatcher::Between)
+            }),
+            "success" => v.in_field(name, |v| {
+                SuccessMatcher::parse(v, param).map(StatusMatcher::Success)
+            }),
             _ => {
                 v.add_violation(format!("status matcher \"{}\" is not defined", name));
                 None
@@ -40,6 +71,17 @@ impl StatusMatcher {
     }
 }
 
+// status code is parsed as uint which fits in i32
+fn parse_status(v: &mut Validator, x: &Yaml) -> Option<i32> {
+    v.must_be_uint(x).and_then(|n| {
+        i32::try_from(n)
+            .inspect_err(|_| {
+                v.add_violation(format!("cannot treat {} as i32", n));
+            })
+            .ok()
+    })
+}
+
 #[cfg(test)]
 pub mod testutil {
     use crate::matcher::testutil::TestMatcher;
@@ -67,6 +109,12 @@ mod tests {
     #[rstest]
     #[case("with eq", "eq", Yaml::Integer(1), Some((StatusMatcher::Eq(eq::EqMatcher { expected: 1 }), true)), vec![])]
     #[case("with not.eq", "not.eq", Yaml::Integer(1), Some((StatusMatcher::Eq(eq::EqMatcher { expected: 1 }), false)), vec![])]
+    #[case("with in", "in", Yaml::Array(vec![Yaml::Integer(0), Yaml::Integer(3)]), Some((StatusMatcher::In(in_list::InMatcher { expected: vec![0, 3] }), true)), vec![])]
+    #[case("with gt", "gt", Yaml::Integer(1), Some((StatusMatcher::Gt(gt::GtMatcher { expected: 1 }), true)), vec![])]
+    #[case("with lt", "lt", Yaml::Integer(1), Some((StatusMatcher::Lt(lt::LtMatcher { expected: 1 }), true)), vec![])]
+    #[case("with between", "between", Yaml::Array(vec![Yaml::Integer(64), Yaml::Integer(78)]), Some((StatusMatcher::Between(between::BetweenMatcher { min: 64, max: 78 }), true)), vec![])]
+    #[case("with not.success", "not.success", Yaml::Boolean(true), Some((StatusMatcher::Success(success::SuccessMatcher { expected: true }), false)), vec![])]
+    #[case("with invalid param", "gt", Yaml::Boolean(true), None, vec![(".gt", "should be uint, but is bool")])]
     #[case("with unknown name", "unknown", Yaml::Boolean(true), None, vec![("", "status matcher \"unknown\" is not defined")])]
     fn parse(
         #[case] title: &str,
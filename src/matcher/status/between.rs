@@ -0,0 +1,118 @@
+use saphyr::Yaml;
+
+use crate::validator::Validator;
+
+use super::parse_status;
+
+// both bounds are inclusive
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct BetweenMatcher {
+    pub(super) min: i32,
+    pub(super) max: i32,
+}
+
+impl BetweenMatcher {
+    pub fn matches(&self, actual: i32) -> Result<(bool, String), String> {
+        let matched = self.min <= actual && actual <= self.max;
+
+        Ok((
+            matched,
+            if matched {
+                format!(
+                    "should not be between {} and {}, but got {}",
+                    self.min, self.max, actual
+                )
+            } else {
+                format!(
+                    "should be between {} and {}, but got {}",
+                    self.min, self.max, actual
+                )
+            },
+        ))
+    }
+
+    pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
+        let xs = v.must_be_seq(x)?;
+        if xs.len() != 2 {
+            v.add_violation(format!(
+                "should have 2 elements (min and max), but has {}",
+                xs.len()
+            ));
+            return None;
+        }
+
+        let min = v.in_index(0, |v| parse_status(v, &xs[0]));
+        let max = v.in_index(1, |v| parse_status(v, &xs[1]));
+        let (min, max) = (min?, max?);
+
+        if min > max {
+            v.add_violation(format!(
+                "min should be less than or equal to max, but {} > {}",
+                min, max
+            ));
+            return None;
+        }
+
+        Some(Self { min, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(64, true, "should not be between 64 and 78, but got 64")]
+    #[case(78, true, "should not be between 64 and 78, but got 78")]
+    #[case(63, false, "should be between 64 and 78, but got 63")]
+    #[case(79, false, "should be between 64 and 78, but got 79")]
+    fn matches(#[case] given: i32, #[case] expected_matched: bool, #[case] expected_message: &str) {
+        let m = BetweenMatcher { min: 64, max: 78 };
+        assert_eq!(
+            m.matches(given),
+            Ok((expected_matched, expected_message.to_string()))
+        );
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::testutil::new_validator;
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        fn success_case() {
+            let (mut v, _) = new_validator();
+            let x = Yaml::Array(vec![Yaml::Integer(64), Yaml::Integer(78)]);
+            let actual = BetweenMatcher::parse(&mut v, &x).unwrap();
+
+            assert_eq!(BetweenMatcher { min: 64, max: 78 }, actual);
+        }
+
+        #[rstest]
+        #[case("with not seq", Yaml::Integer(0), vec![("", "should be seq, but is uint")])]
+        #[case("with 1 element", Yaml::Array(vec![Yaml::Integer(0)]), vec![("", "should have 2 elements (min and max), but has 1")])]
+        #[case("with not int element", Yaml::Array(vec![Yaml::Integer(0), Yaml::String("hello".to_string())]), vec![("[1]", "should be uint, but is string")])]
+        #[case("with min greater than max", Yaml::Array(vec![Yaml::Integer(78), Yaml::Integer(64)]), vec![("", "min should be less than or equal to max, but 78 > 64")])]
+        fn failure_cases(
+            #[case] title: &str,
+            #[case] given: Yaml,
+            #[case] expected_violations: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = new_validator();
+            let actual = BetweenMatcher::parse(&mut v, &given);
+
+            assert!(actual.is_none(), "{}", title);
+            assert_eq!(
+                expected_violations
+                    .iter()
+                    .map(|(path, message)| violation(path, message))
+                    .collect::<Vec<_>>(),
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+}
@@ -2,6 +2,8 @@ use saphyr::Yaml;
 
 use crate::validator::Validator;
 
+use super::parse_status;
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct EqMatcher {
     pub(super) expected: i32,
@@ -22,14 +24,7 @@ impl EqMatcher {
     }
 
     pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
-        v.must_be_uint(x).and_then(|expected| {
-            i32::try_from(expected)
-                .inspect_err(|_| {
-                    v.add_violation(format!("cannot treat {} as i32", expected));
-                })
-                .ok()
-                .map(|expected| Self { expected })
-        })
+        parse_status(v, x).map(|expected| Self { expected })
     }
 }
 
@@ -0,0 +1,103 @@
+use saphyr::Yaml;
+
+use crate::validator::Validator;
+
+use super::parse_status;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct InMatcher {
+    pub(super) expected: Vec<i32>,
+}
+
+impl InMatcher {
+    pub fn matches(&self, actual: i32) -> Result<(bool, String), String> {
+        let matched = self.expected.contains(&actual);
+
+        Ok((
+            matched,
+            if matched {
+                format!("should not be in {:?}, but got {}", self.expected, actual)
+            } else {
+                format!("should be in {:?}, but got {}", self.expected, actual)
+            },
+        ))
+    }
+
+    pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
+        v.must_be_seq(x).and_then(|xs| {
+            let expected: Vec<Option<i32>> = xs
+                .iter()
+                .enumerate()
+                .map(|(i, x)| v.in_index(i, |v| parse_status(v, x)))
+                .collect();
+
+            expected
+                .into_iter()
+                .collect::<Option<Vec<_>>>()
+                .map(|expected| Self { expected })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(3, true, "should not be in [0, 3], but got 3")]
+    #[case(1, false, "should be in [0, 3], but got 1")]
+    fn matches(#[case] given: i32, #[case] expected_matched: bool, #[case] expected_message: &str) {
+        let m = InMatcher {
+            expected: vec![0, 3],
+        };
+        assert_eq!(
+            m.matches(given),
+            Ok((expected_matched, expected_message.to_string()))
+        );
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::testutil::new_validator;
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        fn success_case() {
+            let (mut v, _) = new_validator();
+            let x = Yaml::Array(vec![Yaml::Integer(0), Yaml::Integer(3)]);
+            let actual = InMatcher::parse(&mut v, &x).unwrap();
+
+            assert_eq!(
+                InMatcher {
+                    expected: vec![0, 3]
+                },
+                actual
+            );
+        }
+
+        #[rstest]
+        #[case("with not seq", Yaml::Integer(0), vec![("", "should be seq, but is uint")])]
+        #[case("with not int element", Yaml::Array(vec![Yaml::Integer(0), Yaml::String("hello".to_string())]), vec![("[1]", "should be uint, but is string")])]
+        fn failure_cases(
+            #[case] title: &str,
+            #[case] given: Yaml,
+            #[case] expected_violations: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = new_validator();
+            let actual = InMatcher::parse(&mut v, &given);
+
+            assert!(actual.is_none(), "{}", title);
+            assert_eq!(
+                expected_violations
+                    .iter()
+                    .map(|(path, message)| violation(path, message))
+                    .collect::<Vec<_>>(),
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use saphyr::Yaml;
+
+use crate::validator::Validator;
+
+use super::parse_status;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct LtMatcher {
+    pub(super) expected: i32,
+}
+
+impl LtMatcher {
+    pub fn matches(&self, actual: i32) -> Result<(bool, String), String> {
+        let matched = actual < self.expected;
+
+        Ok((
+            matched,
+            if matched {
+                format!(
+                    "should not be less than {}, but got {}",
+                    self.expected, actual
+                )
+            } else {
+                format!("should be less than {}, but got {}", self.expected, actual)
+            },
+        ))
+    }
+
+    pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
+        parse_status(v, x).map(|expected| Self { expected })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, true, "should not be less than 1, but got 0")]
+    #[case(1, false, "should be less than 1, but got 1")]
+    #[case(2, false, "should be less than 1, but got 2")]
+    fn matches(#[case] given: i32, #[case] expected_matched: bool, #[case] expected_message: &str) {
+        let m = LtMatcher { expected: 1 };
+        assert_eq!(
+            m.matches(given),
+            Ok((expected_matched, expected_message.to_string()))
+        );
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::testutil::new_validator;
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        fn success_case() {
+            let (mut v, _) = new_validator();
+            let x = Yaml::Integer(1);
+            let actual = LtMatcher::parse(&mut v, &x).unwrap();
+
+            assert_eq!(LtMatcher { expected: 1 }, actual);
+        }
+
+        #[rstest]
+        fn failure_case() {
+            let (mut v, violation) = new_validator();
+            let x = Yaml::String("hello".to_string());
+            let actual = LtMatcher::parse(&mut v, &x);
+
+            assert!(actual.is_none());
+            assert_eq!(
+                vec![violation("", "should be uint, but is string")],
+                v.violations
+            );
+        }
+    }
+}
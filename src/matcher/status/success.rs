@@ -0,0 +1,82 @@
+use saphyr::Yaml;
+
+use crate::validator::Validator;
+
+// expected is true for success (zero), false for failure (non-zero)
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct SuccessMatcher {
+    pub(super) expected: bool,
+}
+
+impl SuccessMatcher {
+    pub fn matches(&self, actual: i32) -> Result<(bool, String), String> {
+        let matched = (actual == 0) == self.expected;
+        let expected = if self.expected { "success" } else { "failure" };
+
+        Ok((
+            matched,
+            if matched {
+                format!("should not be {}, but got {}", expected, actual)
+            } else {
+                format!("should be {}, but got {}", expected, actual)
+            },
+        ))
+    }
+
+    pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
+        v.must_be_bool(x).map(|expected| Self { expected })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(true, 0, true, "should not be success, but got 0")]
+    #[case(true, 1, false, "should be success, but got 1")]
+    #[case(false, 1, true, "should not be failure, but got 1")]
+    #[case(false, 0, false, "should be failure, but got 0")]
+    fn matches(
+        #[case] expected: bool,
+        #[case] given: i32,
+        #[case] expected_matched: bool,
+        #[case] expected_message: &str,
+    ) {
+        let m = SuccessMatcher { expected };
+        assert_eq!(
+            m.matches(given),
+            Ok((expected_matched, expected_message.to_string()))
+        );
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::testutil::new_validator;
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        fn success_case() {
+            let (mut v, _) = new_validator();
+            let x = Yaml::Boolean(false);
+            let actual = SuccessMatcher::parse(&mut v, &x).unwrap();
+
+            assert_eq!(SuccessMatcher { expected: false }, actual);
+        }
+
+        #[rstest]
+        fn failure_case() {
+            let (mut v, violation) = new_validator();
+            let x = Yaml::Integer(0);
+            let actual = SuccessMatcher::parse(&mut v, &x);
+
+            assert!(actual.is_none());
+            assert_eq!(
+                vec![violation("", "should be bool, but is uint")],
+                v.violations
+            );
+        }
+    }
+}
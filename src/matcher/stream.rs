@@ -2,13 +2,20 @@ mod contain;
 mod eq;
 mod eq_json;
 mod include_json;
+mod json_options;
+mod json_path;
 mod match_regex;
+mod match_snapshot;
+mod structured;
 use contain::ContainMatcher;
 use eq::EqMatcher;
 use eq_json::EqJsonMatcher;
 use include_json::IncludeJsonMatcher;
+use json_path::JsonPathMatcher;
 use match_regex::MatchRegexMatcher;
+use match_snapshot::MatchSnapshotMatcher;
 use saphyr::Yaml;
+use structured::{Format, StructuredMatcher};
 
 use crate::validator::Validator;
 
@@ -20,7 +27,10 @@ pub enum StreamMatcher {
     Contain(contain::ContainMatcher),
     EqJson(eq_json::EqJsonMatcher),
     IncludeJson(include_json::IncludeJsonMatcher),
+    JsonPath(json_path::JsonPathMatcher),
     MatchRegex(match_regex::MatchRegexMatcher),
+    MatchSnapshot(match_snapshot::MatchSnapshotMatcher),
+    Structured(structured::StructuredMatcher),
     #[cfg(test)]
     Test(super::testutil::TestMatcher),
 }
@@ -32,12 +42,23 @@ impl StreamMatcher {
             StreamMatcher::Contain(m) => m.matches(actual),
             StreamMatcher::EqJson(m) => m.matches(actual),
             StreamMatcher::IncludeJson(m) => m.matches(actual),
+            StreamMatcher::JsonPath(m) => m.matches(actual),
             StreamMatcher::MatchRegex(m) => m.matches(actual),
+            StreamMatcher::MatchSnapshot(m) => m.matches(actual),
+            StreamMatcher::Structured(m) => m.matches(actual),
             #[cfg(test)]
             StreamMatcher::Test(m) => m.matches(actual),
         }
     }
 
+    // rewrites the golden file with the actual stream, if the matcher has it
+    pub fn update_snapshot(&self, actual: &[u8]) -> Result<(), String> {
+        match self {
+            StreamMatcher::MatchSnapshot(m) => m.update(actual),
+            _ => Ok(()),
+        }
+    }
+
     pub fn parse(v: &mut Validator, name: &str, param: &Yaml) -> Option<(Self, bool)> {
         let (name, expected_passed) = parse_name(name);
 
@@ -57,9 +78,31 @@ impl StreamMatcher {
             "include_json" => v.in_field(name, |v| {
                 IncludeJsonMatcher::parse(v, param).map(StreamMatcher::IncludeJson)
             }),
+            "json_path" => v.in_field(name, |v| {
+                JsonPathMatcher::parse(v, param).map(StreamMatcher::JsonPath)
+            }),
             "match_regex" => v.in_field(name, |v| {
                 MatchRegexMatcher::parse(v, param).map(StreamMatcher::MatchRegex)
             }),
+            "match_snapshot" => v.in_field(name, |v| {
+                MatchSnapshotMatcher::parse(v, param).map(StreamMatcher::MatchSnapshot)
+            }),
+            "eq_yaml" => v.in_field(name, |v| {
+                StructuredMatcher::parse(v, param, Format::Yaml, false)
+                    .map(StreamMatcher::Structured)
+            }),
+            "include_yaml" => v.in_field(name, |v| {
+                StructuredMatcher::parse(v, param, Format::Yaml, true)
+                    .map(StreamMatcher::Structured)
+            }),
+            "eq_toml" => v.in_field(name, |v| {
+                StructuredMatcher::parse(v, param, Format::Toml, false)
+                    .map(StreamMatcher::Structured)
+            }),
+            "include_toml" => v.in_field(name, |v| {
+                StructuredMatcher::parse(v, param, Format::Toml, true)
+                    .map(StreamMatcher::Structured)
+            }),
             _ => {
                 v.add_violation(format!("stream matcher \"{}\" is not defined", name));
                 None
@@ -107,6 +150,7 @@ mod tests {
             Some((StreamMatcher::EqJson(EqJsonMatcher {
                 expected: serde_json::Value::Object(m),
                 original: r#"{"message": "hello"}"#.into(),
+                options: json_options::JsonOptions::default(),
             }), true))
         },
         vec![])]
@@ -119,6 +163,7 @@ mod tests {
             Some((StreamMatcher::IncludeJson(IncludeJsonMatcher {
                 expected: serde_json::Value::Object(m),
                 original: r#"{"message": "hello"}"#.into(),
+                options: json_options::JsonOptions::default(),
             }), true))
         },
         vec![])]
@@ -129,6 +174,40 @@ mod tests {
             expected: Regex::new("hel*o").unwrap(),
         }), true)),
         vec![])]
+    #[case("with json_path",
+        "json_path",
+        {
+            let mut assertions = saphyr::Hash::new();
+            assertions.insert(Yaml::String("length".to_string()), Yaml::Integer(2));
+            let mut m = saphyr::Hash::new();
+            m.insert(Yaml::String("$.items".to_string()), Yaml::Hash(assertions));
+            Yaml::Hash(m)
+        },
+        Some((StreamMatcher::JsonPath(JsonPathMatcher {
+            queries: vec![json_path::JsonPathQuery {
+                original: "$.items".to_string(),
+                path: serde_json_path::JsonPath::parse("$.items").unwrap(),
+                assertions: vec![(json_path::JsonValueAssertion::Length(2), true)],
+            }],
+        }), true)),
+        vec![])]
+    #[case("with match_snapshot",
+        "match_snapshot",
+        Yaml::String("golden.txt".to_string()),
+        Some((StreamMatcher::MatchSnapshot(MatchSnapshotMatcher {
+            path: "golden.txt".into(),
+        }), true)),
+        vec![])]
+    #[case("with include_toml",
+        "include_toml",
+        Yaml::String("message = \"hello\"".to_string()),
+        Some((StreamMatcher::Structured(StructuredMatcher {
+            format: Format::Toml,
+            inclusive: true,
+            expected: serde_json::json!({"message": "hello"}),
+            original: "message = \"hello\"".into(),
+        }), true)),
+        vec![])]
     #[case("with unknown name", "unknown", Yaml::Boolean(true), None, vec![("", "stream matcher \"unknown\" is not defined")])]
     fn parse(
         #[case] title: &str,
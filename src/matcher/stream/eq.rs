@@ -19,20 +19,10 @@ impl EqMatcher {
                 ),
             ))
         } else {
-            let diff_message = TextDiff::from_lines(&self.expected, &actual.to_vec())
-                .iter_all_changes()
-                .map(|change| {
-                    let tag = match change.tag() {
-                        similar::ChangeTag::Delete => "-",
-                        similar::ChangeTag::Insert => "+",
-                        similar::ChangeTag::Equal => " ",
-                    };
-                    format!("{}{}", tag, change)
-                })
-                .collect::<Vec<_>>()
-                .join("");
-
-            Ok((false, format!("not equals:\n\n{}", diff_message)))
+            Ok((
+                false,
+                format!("not equals:\n\n{}", diff(&self.expected, actual)),
+            ))
         }
     }
 
@@ -43,6 +33,22 @@ impl EqMatcher {
     }
 }
 
+// lines tagged with "-" for expected and "+" for actual
+pub(super) fn diff(expected: &[u8], actual: &[u8]) -> String {
+    TextDiff::from_lines(expected, actual)
+        .iter_all_changes()
+        .map(|change| {
+            let tag = match change.tag() {
+                similar::ChangeTag::Delete => "-",
+                similar::ChangeTag::Insert => "+",
+                similar::ChangeTag::Equal => " ",
+            };
+            format!("{}{}", tag, change)
+        })
+        .collect::<Vec<_>>()
+        .join("")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,12 +1,17 @@
-use assert_json_diff::{assert_json_matches_no_panic, Config};
+use assert_json_diff::CompareMode;
 use saphyr::Yaml;
 
-use crate::validator::Validator;
+use crate::{expr::to_json_value, validator::Validator};
+
+use super::json_options::{self, parse_placeholders, JsonOptions, Patterns};
+
+const EXPECTED_KEY: &str = "$expected";
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct EqJsonMatcher {
     pub(super) expected: serde_json::Value,
     pub(super) original: String,
+    pub(super) options: JsonOptions,
 }
 
 impl EqJsonMatcher {
@@ -29,11 +34,10 @@ impl EqJsonMatcher {
 
         let actual_json = parsed.unwrap();
 
-        match assert_json_matches_no_panic(
-            &actual_json,
-            &self.expected,
-            Config::new(assert_json_diff::CompareMode::Strict),
-        ) {
+        match self
+            .options
+            .compare(&actual_json, &self.expected, CompareMode::Strict)
+        {
             Ok(_) => Ok((
                 true,
                 format!("should not be {} as JSON, but got it", self.original),
@@ -43,17 +47,99 @@ impl EqJsonMatcher {
     }
 
     pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
-        v.must_be_string(x)
-            .and_then(|original| match serde_json::from_str(&original) {
-                Ok(expected) => Some(Self { expected, original }),
-                _ => {
-                    v.add_violation(format!(
-                        "should be valid JSON string, but got \"{}\"",
-                        original
-                    ));
-                    None
-                }
-            })
+        parse_expected_json(v, x).map(|(expected, original, options)| Self {
+            expected,
+            original,
+            options,
+        })
+    }
+}
+
+// expected value is given directly, or given as map with reserved `$expected`
+// key and options, so that any JSON value can be expected without ambiguity
+pub(super) fn parse_expected_json(
+    v: &mut Validator,
+    x: &Yaml,
+) -> Option<(serde_json::Value, String, JsonOptions)> {
+    let with_options = x
+        .as_hash()
+        .is_some_and(|h| h.contains_key(&Yaml::String(EXPECTED_KEY.to_string())));
+    if !with_options {
+        return parse_json_value(v, x).map(|(expected, original, patterns)| {
+            (
+                expected,
+                original,
+                JsonOptions {
+                    patterns,
+                    ..JsonOptions::default()
+                },
+            )
+        });
+    }
+
+    let m = v.must_be_map(x)?;
+    let undefined: Vec<&str> = m
+        .keys()
+        .copied()
+        .filter(|key| *key != EXPECTED_KEY && !json_options::KEYS.contains(key))
+        .collect();
+    undefined
+        .iter()
+        .for_each(|key| v.add_violation(format!("option \"{}\" is not defined", key)));
+    let options = JsonOptions::parse(v, &m);
+    v.must_have(&m, EXPECTED_KEY, parse_json_value)
+        .flatten()
+        .filter(|_| undefined.is_empty())
+        .map(|(expected, original, patterns)| {
+            (
+                expected,
+                original,
+                JsonOptions {
+                    patterns,
+                    ..options
+                },
+            )
+        })
+}
+
+// JSON string or native YAML value, whose values may be placeholders
+fn parse_json_value(v: &mut Validator, x: &Yaml) -> Option<(serde_json::Value, String, Patterns)> {
+    let (expected, original) = parse_expected_value(v, x, "JSON", |s| {
+        serde_json::from_str(s).map_err(|err| err.to_string())
+    })?;
+
+    parse_placeholders(v, &expected).map(|patterns| (expected, original, patterns))
+}
+
+// string in the format or native YAML value. original is the string shown in messages.
+pub(super) fn parse_expected_value<F: Fn(&str) -> Result<serde_json::Value, String>>(
+    v: &mut Validator,
+    x: &Yaml,
+    format_name: &str,
+    parse: F,
+) -> Option<(serde_json::Value, String)> {
+    if let Some(original) = v.may_be_string(x) {
+        return match parse(&original) {
+            Ok(expected) => Some((expected, original)),
+            _ => {
+                v.add_violation(format!(
+                    "should be valid {} string, but got \"{}\"",
+                    format_name, original
+                ));
+                None
+            }
+        };
+    }
+
+    match to_json_value(x) {
+        Ok(expected) => {
+            let original = expected.to_string();
+            Some((expected, original))
+        }
+        Err(err) => {
+            v.add_violation(format!("should be {} value, but {}", format_name, err));
+            None
+        }
     }
 }
 
@@ -102,6 +188,7 @@ mod tests {
         let m = EqJsonMatcher {
             original: original.into(),
             expected: serde_json::from_str(original).unwrap(),
+            options: JsonOptions::default(),
         };
         assert_eq!(
             Ok((expected_matched, expected_message.to_string())),
@@ -109,12 +196,33 @@ mod tests {
         );
     }
 
+    #[rstest]
+    fn matches_with_options() {
+        let m = EqJsonMatcher {
+            original: r#"{"id": {"$any": "string"}, "nums": [1, 2]}"#.into(),
+            expected: serde_json::json!({"id": {"$any": "string"}, "nums": [1, 2]}),
+            options: JsonOptions {
+                unordered_arrays: true,
+                ..JsonOptions::default()
+            },
+        };
+        assert_eq!(
+            Ok((
+                true,
+                r#"should not be {"id": {"$any": "string"}, "nums": [1, 2]} as JSON, but got it"#
+                    .to_string()
+            )),
+            m.matches(br#"{"id": "0b7e", "nums": [2, 1]}"#),
+        );
+    }
+
     #[rstest]
     fn matches_with_not_utf8() {
         let original = r#"{"message": "hello", "nums": [1, 2]}"#;
         let m = EqJsonMatcher {
             original: original.into(),
             expected: serde_json::from_str(original).unwrap(),
+            options: JsonOptions::default(),
         };
         assert_eq!(
             Err("should be valid utf8 string, but got \"{\"message\": �}\"".to_string()),
@@ -127,30 +235,106 @@ mod tests {
         use crate::validator::testutil::new_validator;
         use pretty_assertions::assert_eq;
 
+        fn message_map(key: Yaml) -> Yaml {
+            let mut m = saphyr::Hash::new();
+            m.insert(key, Yaml::String("hello".to_string()));
+            Yaml::Hash(m)
+        }
+
         #[rstest]
-        fn success_case() {
+        #[case(
+            "with JSON string",
+            Yaml::String(r#"{"message": "hello"}"#.to_string()),
+            serde_json::json!({"message": "hello"}),
+            r#"{"message": "hello"}"#
+        )]
+        #[case(
+            "with map",
+            message_map(Yaml::String("message".to_string())),
+            serde_json::json!({"message": "hello"}),
+            r#"{"message":"hello"}"#
+        )]
+        #[case(
+            "with seq",
+            Yaml::Array(vec![Yaml::Integer(1), Yaml::Boolean(true)]),
+            serde_json::json!([1, true]),
+            "[1,true]"
+        )]
+        #[case("with bool", Yaml::Boolean(true), serde_json::json!(true), "true")]
+        #[case(
+            "with map having keys of options",
+            {
+                let mut m = saphyr::Hash::new();
+                m.insert(Yaml::String("expected".to_string()), Yaml::Integer(1));
+                m.insert(Yaml::String("unordered_arrays".to_string()), Yaml::Boolean(true));
+                Yaml::Hash(m)
+            },
+            serde_json::json!({"expected": 1, "unordered_arrays": true}),
+            r#"{"expected":1,"unordered_arrays":true}"#
+        )]
+        fn success_cases(
+            #[case] title: &str,
+            #[case] given: Yaml,
+            #[case] expected_value: serde_json::Value,
+            #[case] expected_original: &str,
+        ) {
             let (mut v, _) = new_validator();
-            let original = r#"{"message": "hello"}"#;
-            let x = Yaml::String(original.to_string());
-            let actual = EqJsonMatcher::parse(&mut v, &x).unwrap();
+            let actual = EqJsonMatcher::parse(&mut v, &given);
 
-            let mut m = serde_json::Map::new();
+            let expected = EqJsonMatcher {
+                original: expected_original.into(),
+                expected: expected_value,
+                options: JsonOptions::default(),
+            };
+            assert_eq!(Some(expected), actual, "{}", title);
+        }
+
+        #[rstest]
+        fn success_case_with_options() {
+            let (mut v, _) = new_validator();
+            let mut m = saphyr::Hash::new();
             m.insert(
-                "message".to_string(),
-                serde_json::Value::String("hello".to_string()),
+                Yaml::String("$expected".to_string()),
+                message_map(Yaml::String("message".to_string())),
             );
+            m.insert(
+                Yaml::String("unordered_arrays".to_string()),
+                Yaml::Boolean(true),
+            );
+            let actual = EqJsonMatcher::parse(&mut v, &Yaml::Hash(m));
+
             let expected = EqJsonMatcher {
-                original: original.into(),
-                expected: serde_json::Value::Object(m),
+                original: r#"{"message":"hello"}"#.into(),
+                expected: serde_json::json!({"message": "hello"}),
+                options: JsonOptions {
+                    unordered_arrays: true,
+                    ..JsonOptions::default()
+                },
             };
-            assert_eq!(expected, actual);
+            assert_eq!(Some(expected), actual);
+            assert!(v.violations.is_empty());
         }
 
         #[rstest]
         #[case(
-            "with not string",
-            Yaml::Boolean(true),
-            "should be string, but is bool"
+            "with not string keyed map",
+            message_map(Yaml::Integer(1)),
+            "should be JSON value, but key at index 0 is not string"
+        )]
+        #[case(
+            "with invalid placeholder",
+            Yaml::String(r#"{"id": {"$any": "uuid"}}"#.to_string()),
+            r#"$any should be one of string, number, integer, boolean, null, array, object, but got "uuid""#
+        )]
+        #[case(
+            "with undefined option",
+            {
+                let mut m = saphyr::Hash::new();
+                m.insert(Yaml::String("$expected".to_string()), Yaml::Integer(1));
+                m.insert(Yaml::String("unordered".to_string()), Yaml::Boolean(true));
+                Yaml::Hash(m)
+            },
+            "option \"unordered\" is not defined"
         )]
         #[case(
             "with not valid JSON string",
@@ -1,12 +1,15 @@
-use assert_json_diff::{assert_json_matches_no_panic, Config};
+use assert_json_diff::CompareMode;
 use saphyr::Yaml;
 
 use crate::validator::Validator;
 
+use super::{eq_json::parse_expected_json, json_options::JsonOptions};
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct IncludeJsonMatcher {
     pub(super) expected: serde_json::Value,
     pub(super) original: String,
+    pub(super) options: JsonOptions,
 }
 
 impl IncludeJsonMatcher {
@@ -29,11 +32,10 @@ impl IncludeJsonMatcher {
 
         let actual_json = parsed.unwrap();
 
-        match assert_json_matches_no_panic(
-            &actual_json,
-            &self.expected,
-            Config::new(assert_json_diff::CompareMode::Inclusive),
-        ) {
+        match self
+            .options
+            .compare(&actual_json, &self.expected, CompareMode::Inclusive)
+        {
             Ok(_) => Ok((
                 true,
                 format!("should not include {} as JSON, but got it", self.original),
@@ -43,17 +45,11 @@ impl IncludeJsonMatcher {
     }
 
     pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
-        v.must_be_string(x)
-            .and_then(|original| match serde_json::from_str(&original) {
-                Ok(expected) => Some(Self { expected, original }),
-                _ => {
-                    v.add_violation(format!(
-                        "should be valid JSON string, but got \"{}\"",
-                        original
-                    ));
-                    None
-                }
-            })
+        parse_expected_json(v, x).map(|(expected, original, options)| Self {
+            expected,
+            original,
+            options,
+        })
     }
 }
 
@@ -97,6 +93,7 @@ mod tests {
         let m = IncludeJsonMatcher {
             original: original.into(),
             expected: serde_json::from_str(original).unwrap(),
+            options: JsonOptions::default(),
         };
         assert_eq!(
             Ok((expected_matched, expected_message.to_string())),
@@ -110,6 +107,7 @@ mod tests {
         let m = IncludeJsonMatcher {
             original: original.into(),
             expected: serde_json::from_str(original).unwrap(),
+            options: JsonOptions::default(),
         };
         assert_eq!(
             Err("should be valid utf8 string, but got \"{\"message\": �}\"".to_string()),
@@ -122,30 +120,54 @@ mod tests {
         use crate::validator::testutil::new_validator;
         use pretty_assertions::assert_eq;
 
+        fn message_map(key: Yaml) -> Yaml {
+            let mut m = saphyr::Hash::new();
+            m.insert(key, Yaml::String("hello".to_string()));
+            Yaml::Hash(m)
+        }
+
         #[rstest]
-        fn success_case() {
+        #[case(
+            "with JSON string",
+            Yaml::String(r#"{"message": "hello"}"#.to_string()),
+            serde_json::json!({"message": "hello"}),
+            r#"{"message": "hello"}"#
+        )]
+        #[case(
+            "with map",
+            message_map(Yaml::String("message".to_string())),
+            serde_json::json!({"message": "hello"}),
+            r#"{"message":"hello"}"#
+        )]
+        #[case(
+            "with seq",
+            Yaml::Array(vec![Yaml::Integer(1), Yaml::Boolean(true)]),
+            serde_json::json!([1, true]),
+            "[1,true]"
+        )]
+        #[case("with bool", Yaml::Boolean(true), serde_json::json!(true), "true")]
+        fn success_cases(
+            #[case] title: &str,
+            #[case] given: Yaml,
+            #[case] expected_value: serde_json::Value,
+            #[case] expected_original: &str,
+        ) {
             let (mut v, _) = new_validator();
-            let original = r#"{"message": "hello"}"#;
-            let x = Yaml::String(original.to_string());
-            let actual = IncludeJsonMatcher::parse(&mut v, &x).unwrap();
-
-            let mut m = serde_json::Map::new();
-            m.insert(
-                "message".to_string(),
-                serde_json::Value::String("hello".to_string()),
-            );
+            let actual = IncludeJsonMatcher::parse(&mut v, &given);
+
             let expected = IncludeJsonMatcher {
-                original: original.into(),
-                expected: serde_json::Value::Object(m),
+                original: expected_original.into(),
+                expected: expected_value,
+                options: JsonOptions::default(),
             };
-            assert_eq!(expected, actual);
+            assert_eq!(Some(expected), actual, "{}", title);
         }
 
         #[rstest]
         #[case(
-            "with not string",
-            Yaml::Boolean(true),
-            "should be string, but is bool"
+            "with not string keyed map",
+            message_map(Yaml::Integer(1)),
+            "should be JSON value, but key at index 0 is not string"
         )]
         #[case(
             "with not valid JSON string",
@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+
+use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
+use regex::Regex;
+use saphyr::Yaml;
+use serde_json::Value;
+use serde_json_path::{JsonPath, PathElement};
+
+use crate::{ast::Map, validator::Validator};
+
+use super::json_path::parse_path;
+
+pub const KEYS: [&str; 3] = ["ignore", "unordered_arrays", "float_tolerance"];
+
+const ANY_TYPES: [&str; 7] = [
+    "string", "number", "integer", "boolean", "null", "array", "object",
+];
+
+// how JSON values are compared by eq_json and include_json
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct JsonOptions {
+    pub(super) ignore: Vec<JsonPath>,
+    pub(super) unordered_arrays: bool,
+    pub(super) float_tolerance: Option<f64>,
+    pub(super) patterns: Patterns,
+}
+
+// regexes of `$match` placeholders in the expected value, compiled when parsed
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Patterns(HashMap<String, Regex>);
+
+#[cfg(test)]
+impl PartialEq for Patterns {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.keys().all(|key| other.0.contains_key(key))
+    }
+}
+
+impl JsonOptions {
+    // invalid options are reported as violations, and left as default
+    pub fn parse(v: &mut Validator, m: &Map) -> Self {
+        let ignore = v
+            .may_have(m, "ignore", |v, x| {
+                v.must_be_seq(x).map(|paths| {
+                    paths
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, path)| {
+                            v.in_index(i, |v| {
+                                v.must_be_string(path).and_then(|path| parse_path(v, &path))
+                            })
+                        })
+                        .collect()
+                })
+            })
+            .flatten()
+            .unwrap_or_default();
+        let unordered_arrays = v.may_have_bool(m, "unordered_arrays").unwrap_or(false);
+        let float_tolerance = v
+            .may_have(m, "float_tolerance", |v, x| {
+                let tolerance = match x {
+                    Yaml::Integer(n) => Some(*n as f64),
+                    Yaml::Real(_) => x.as_f64(),
+                    _ => None,
+                }
+                .filter(|tolerance| *tolerance >= 0.0);
+                if tolerance.is_none() {
+                    v.add_violation("should be non-negative number");
+                }
+                tolerance
+            })
+            .flatten();
+
+        Self {
+            ignore,
+            unordered_arrays,
+            float_tolerance,
+            patterns: Patterns::default(),
+        }
+    }
+
+    // compares after ignored paths are removed, and expected values which are
+    // accepted by placeholders or tolerance are replaced with the actual ones
+    pub fn compare(
+        &self,
+        actual: &Value,
+        expected: &Value,
+        mode: CompareMode,
+    ) -> Result<(), String> {
+        let mut actual = actual.clone();
+        let mut expected = expected.clone();
+        self.ignore.iter().for_each(|path| {
+            remove_paths(&mut actual, path);
+            remove_paths(&mut expected, path);
+        });
+
+        self.compare_values(actual, expected, mode)
+    }
+
+    fn compare_values(
+        &self,
+        mut actual: Value,
+        mut expected: Value,
+        mode: CompareMode,
+    ) -> Result<(), String> {
+        self.reconcile(&mut actual, &mut expected, mode);
+        assert_json_matches_no_panic(&actual, &expected, Config::new(mode))
+    }
+
+    fn reconcile(&self, actual: &mut Value, expected: &mut Value, mode: CompareMode) {
+        if let Some(placeholder) = Placeholder::of(expected) {
+            if placeholder.accepts(actual, &self.patterns) {
+                *expected = actual.clone();
+            }
+            return;
+        }
+
+        match (actual, expected) {
+            (Value::Number(a), Value::Number(e)) => {
+                if let (Some(tolerance), Some(af), Some(ef)) =
+                    (self.float_tolerance, a.as_f64(), e.as_f64())
+                {
+                    if (af - ef).abs() <= tolerance {
+                        *e = a.clone();
+                    }
+                }
+            }
+            (Value::Object(a), Value::Object(e)) => {
+                e.iter_mut().for_each(|(key, ev)| {
+                    if let Some(av) = a.get_mut(key) {
+                        self.reconcile(av, ev, mode);
+                    }
+                });
+            }
+            (Value::Array(a), Value::Array(e)) => {
+                if self.unordered_arrays {
+                    *a = self.reorder(std::mem::take(a), e, mode);
+                }
+                a.iter_mut()
+                    .zip(e.iter_mut())
+                    .for_each(|(av, ev)| self.reconcile(av, ev, mode));
+            }
+            _ => {}
+        }
+    }
+
+    // actual elements are placed at the index of expected elements matched by them.
+    // unmatched ones fill the rest in their original order.
+    fn reorder(&self, actual: Vec<Value>, expected: &[Value], mode: CompareMode) -> Vec<Value> {
+        let matches: Vec<Vec<bool>> = expected
+            .iter()
+            .map(|ev| {
+                actual
+                    .iter()
+                    .map(|av| self.compare_values(av.clone(), ev.clone(), mode).is_ok())
+                    .collect()
+            })
+            .collect();
+
+        // maximum matching, since the first matched element may be needed by later ones
+        let mut matched_by = vec![None; actual.len()];
+        (0..expected.len()).for_each(|e| {
+            assign(&matches, e, &mut vec![false; actual.len()], &mut matched_by);
+        });
+        let used: Vec<bool> = matched_by.iter().map(Option::is_some).collect();
+        let mut assigned = vec![None; expected.len()];
+        matched_by.iter().enumerate().for_each(|(a, e)| {
+            if let Some(e) = e {
+                assigned[*e] = Some(a);
+            }
+        });
+
+        let mut rest = (0..actual.len())
+            .filter(|i| !used[*i])
+            .collect::<Vec<_>>()
+            .into_iter();
+        let mut order: Vec<usize> = assigned
+            .into_iter()
+            .filter_map(|i| i.or_else(|| rest.next()))
+            .collect();
+        order.extend(rest);
+
+        let mut actual: Vec<Option<Value>> = actual.into_iter().map(Some).collect();
+        order.into_iter().filter_map(|i| actual[i].take()).collect()
+    }
+}
+
+// finds an actual element for the expected one by augmenting path,
+// where elements already matched are moved to other ones if possible
+fn assign(
+    matches: &[Vec<bool>],
+    e: usize,
+    visited: &mut [bool],
+    matched_by: &mut [Option<usize>],
+) -> bool {
+    (0..visited.len()).any(|a| {
+        if !matches[e][a] || visited[a] {
+            return false;
+        }
+        visited[a] = true;
+        let reassigned = match matched_by[a] {
+            Some(other) => assign(matches, other, visited, matched_by),
+            None => true,
+        };
+        if reassigned {
+            matched_by[a] = Some(e);
+        }
+        reassigned
+    })
+}
+
+// expected values written as `{$any: type}` or `{$match: regex}`
+enum Placeholder<'a> {
+    Any(&'a str),
+    Match(&'a str),
+}
+
+impl<'a> Placeholder<'a> {
+    fn of(value: &'a Value) -> Option<Self> {
+        let object = value.as_object().filter(|object| object.len() == 1)?;
+        match object.iter().next()? {
+            (key, Value::String(param)) if key == "$any" => Some(Placeholder::Any(param)),
+            (key, Value::String(param)) if key == "$match" => Some(Placeholder::Match(param)),
+            _ => None,
+        }
+    }
+
+    fn accepts(&self, actual: &Value, patterns: &Patterns) -> bool {
+        match self {
+            Placeholder::Any("string") => actual.is_string(),
+            Placeholder::Any("number") => actual.is_number(),
+            Placeholder::Any("integer") => actual.is_i64() || actual.is_u64(),
+            Placeholder::Any("boolean") => actual.is_boolean(),
+            Placeholder::Any("null") => actual.is_null(),
+            Placeholder::Any("array") => actual.is_array(),
+            Placeholder::Any("object") => actual.is_object(),
+            Placeholder::Any(_) => false,
+            Placeholder::Match(pattern) => actual
+                .as_str()
+                .zip(patterns.0.get(*pattern))
+                .is_some_and(|(s, re)| re.is_match(s)),
+        }
+    }
+}
+
+// placeholders in the expected value should have valid params.
+// returns regexes of them when all of them are valid.
+pub fn parse_placeholders(v: &mut Validator, expected: &Value) -> Option<Patterns> {
+    let mut patterns = Patterns::default();
+    collect_placeholders(v, expected, &mut patterns).then_some(patterns)
+}
+
+fn collect_placeholders(v: &mut Validator, expected: &Value, patterns: &mut Patterns) -> bool {
+    let children: Vec<&Value> = match expected {
+        Value::Object(object) => {
+            if let Some(valid) = validate_placeholder(v, object, patterns) {
+                return valid;
+            }
+            object.values().collect()
+        }
+        Value::Array(array) => array.iter().collect(),
+        _ => return true,
+    };
+
+    // all children are validated to report every violation
+    let results: Vec<bool> = children
+        .into_iter()
+        .map(|child| collect_placeholders(v, child, patterns))
+        .collect();
+    results.into_iter().all(|valid| valid)
+}
+
+// returns None when the object is not placeholder
+fn validate_placeholder(
+    v: &mut Validator,
+    object: &serde_json::Map<String, Value>,
+    patterns: &mut Patterns,
+) -> Option<bool> {
+    if object.len() != 1 {
+        return None;
+    }
+
+    let (key, param) = object.iter().next()?;
+    let message = match (key.as_str(), param) {
+        ("$any", Value::String(t)) if ANY_TYPES.contains(&t.as_str()) => return Some(true),
+        ("$any", _) => format!(
+            "$any should be one of {}, but got {}",
+            ANY_TYPES.join(", "),
+            param
+        ),
+        ("$match", Value::String(pattern)) => match Regex::new(pattern) {
+            Ok(re) => {
+                patterns.0.insert(pattern.clone(), re);
+                return Some(true);
+            }
+            Err(err) => format!("$match should be valid regex, but {}", err),
+        },
+        ("$match", _) => format!("$match should be string, but got {}", param),
+        _ => return None,
+    };
+    v.add_violation(message);
+    Some(false)
+}
+
+// removed in descending order of locations, so that indices of remaining elements
+// are kept even when the path selects them in other order, such as `$.a[2,0]`
+fn remove_paths(value: &mut Value, path: &JsonPath) {
+    let mut locations: Vec<Vec<Key>> = path
+        .query_located(value)
+        .locations()
+        .map(|location| location.iter().map(Key::from).collect())
+        .collect();
+    locations.sort();
+    locations.dedup();
+
+    locations.into_iter().rev().for_each(|mut elements| {
+        // root cannot be removed
+        let Some(last) = elements.pop() else {
+            return;
+        };
+        let pointer: String = elements
+            .iter()
+            .map(|key| format!("/{}", key.to_pointer_token()))
+            .collect();
+        match (value.pointer_mut(&pointer), last) {
+            (Some(Value::Object(object)), Key::Name(name)) => {
+                object.remove(&name);
+            }
+            (Some(Value::Array(array)), Key::Index(i)) if i < array.len() => {
+                array.remove(i);
+            }
+            _ => {}
+        }
+    });
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Name(String),
+    Index(usize),
+}
+
+impl Key {
+    fn from(element: &PathElement) -> Self {
+        match element {
+            PathElement::Name(name) => Key::Name(name.to_string()),
+            PathElement::Index(i) => Key::Index(*i),
+        }
+    }
+
+    fn to_pointer_token(&self) -> String {
+        match self {
+            Key::Name(name) => name.replace('~', "~0").replace('/', "~1"),
+            Key::Index(i) => i.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::indexmap;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::json;
+
+    fn options(
+        ignore: Vec<&str>,
+        unordered_arrays: bool,
+        float_tolerance: Option<f64>,
+    ) -> JsonOptions {
+        JsonOptions {
+            ignore: ignore
+                .into_iter()
+                .map(|path| JsonPath::parse(path).unwrap())
+                .collect(),
+            unordered_arrays,
+            float_tolerance,
+            patterns: Patterns::default(),
+        }
+    }
+
+    #[rstest]
+    #[case("with ignored field", options(vec!["$.id"], false, None), json!({"id": 1, "name": "a"}), json!({"id": 2, "name": "a"}), CompareMode::Strict, Ok(()))]
+    #[case("with ignored field only in actual", options(vec!["$.items[*].id"], false, None), json!({"items": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]}), json!({"items": [{"name": "a"}, {"name": "b"}]}), CompareMode::Strict, Ok(()))]
+    #[case("with ignored element", options(vec!["$[0]"], false, None), json!([1, 2, 3]), json!([0, 2, 3]), CompareMode::Strict, Ok(()))]
+    #[case("with ignored elements in descending order", options(vec!["$[2,0]"], false, None), json!([1, 2, 3]), json!([0, 2, 4]), CompareMode::Strict, Ok(()))]
+    #[case("with ignored element selected twice", options(vec!["$[0,0]"], false, None), json!([1, 2]), json!([0, 2]), CompareMode::Strict, Ok(()))]
+    #[case("with unordered arrays", options(vec![], true, None), json!({"tags": ["b", "a", "c"]}), json!({"tags": ["a", "b", "c"]}), CompareMode::Strict, Ok(()))]
+    #[case("with unordered arrays of objects", options(vec![], true, None), json!([{"id": 2, "v": "x"}, {"id": 1, "v": "y"}]), json!([{"id": 1}, {"id": 2}]), CompareMode::Inclusive, Ok(()))]
+    #[case("with unordered arrays and missing element", options(vec![], true, None), json!(["b", "x"]), json!(["a", "b"]), CompareMode::Strict, Err("json atoms at path \"[0]\" are not equal:\n    lhs:\n        \"x\"\n    rhs:\n        \"a\"".to_string()))]
+    #[case("with ordered arrays", options(vec![], false, None), json!(["b", "a"]), json!(["a"]), CompareMode::Inclusive, Err("json atoms at path \"[0]\" are not equal:\n    expected:\n        \"a\"\n    actual:\n        \"b\"".to_string()))]
+    #[case("with float in tolerance", options(vec![], false, Some(0.01)), json!({"ratio": 0.333}), json!({"ratio": 0.33}), CompareMode::Strict, Ok(()))]
+    #[case("with integer and float in tolerance", options(vec![], false, Some(0.0)), json!(1), json!(1.0), CompareMode::Strict, Ok(()))]
+    #[case("with float out of tolerance", options(vec![], false, Some(0.001)), json!(0.333), json!(0.33), CompareMode::Strict, Err("json atoms at path \"(root)\" are not equal:\n    lhs:\n        0.333\n    rhs:\n        0.33".to_string()))]
+    #[case("with ignored field in unordered arrays", options(vec!["$[*].id"], true, None), json!([{"id": 1, "v": "b"}, {"id": 2, "v": "a"}]), json!([{"id": 3, "v": "a"}, {"v": "b"}]), CompareMode::Strict, Ok(()))]
+    #[case("with any string", options(vec![], false, None), json!({"id": "abc", "n": 1}), json!({"id": {"$any": "string"}, "n": 1}), CompareMode::Strict, Ok(()))]
+    #[case("with any integer for float", options(vec![], false, None), json!({"n": 1.5}), json!({"n": {"$any": "integer"}}), CompareMode::Strict, Err("json atoms at path \".n\" are not equal:\n    lhs:\n        1.5\n    rhs:\n        {\n          \"$any\": \"integer\"\n        }".to_string()))]
+    #[case("with matched regex", options(vec![], false, None), json!(["2024-01-02"]), json!([{"$match": "^\\d{4}-\\d{2}-\\d{2}$"}]), CompareMode::Inclusive, Ok(()))]
+    #[case("with placeholders needing other pairs in unordered arrays", options(vec![], true, None), json!(["abc", "xyz"]), json!([{"$any": "string"}, {"$match": "^a"}]), CompareMode::Strict, Ok(()))]
+    #[case("with objects needing other pairs in unordered arrays", options(vec![], true, None), json!([{"id": 1, "v": "a"}, {"id": 1, "v": "b"}]), json!([{"id": 1}, {"id": 1, "v": "a"}]), CompareMode::Inclusive, Ok(()))]
+    #[case("with placeholders in unordered arrays", options(vec![], true, None), json!([1, "a"]), json!([{"$any": "string"}, {"$any": "number"}]), CompareMode::Strict, Ok(()))]
+    fn compare(
+        #[case] title: &str,
+        #[case] options: JsonOptions,
+        #[case] actual: Value,
+        #[case] expected: Value,
+        #[case] mode: CompareMode,
+        #[case] expected_result: Result<(), String>,
+    ) {
+        let (mut v, _) = crate::validator::testutil::new_validator();
+        let options = JsonOptions {
+            patterns: super::parse_placeholders(&mut v, &expected).unwrap(),
+            ..options
+        };
+        assert_eq!(
+            expected_result,
+            options.compare(&actual, &expected, mode),
+            "{}",
+            title
+        );
+    }
+
+    #[rstest]
+    #[case("with valid placeholders", json!({"id": {"$any": "string"}, "items": [{"$match": "^a"}, {"$match": "b$"}]}), vec!["^a", "b$"], vec![])]
+    #[case("with unknown type", json!({"id": {"$any": "uuid"}}), vec![], vec!["$any should be one of string, number, integer, boolean, null, array, object, but got \"uuid\""])]
+    #[case("with not string regex", json!([{"$match": 1}]), vec![], vec!["$match should be string, but got 1"])]
+    #[case("with multiple errors", json!([{"$any": 1}, {"a": {"$match": 1}}]), vec![], vec!["$any should be one of string, number, integer, boolean, null, array, object, but got 1", "$match should be string, but got 1"])]
+    #[case("with invalid regex", json!({"$match": "("}), vec![], vec!["$match should be valid regex, but "])]
+    fn parse_placeholders(
+        #[case] title: &str,
+        #[case] given: Value,
+        #[case] expected_patterns: Vec<&str>,
+        #[case] expected_messages: Vec<&str>,
+    ) {
+        let (mut v, _) = crate::validator::testutil::new_validator();
+        let patterns = super::parse_placeholders(&mut v, &given);
+
+        assert_eq!(
+            expected_messages.is_empty().then(|| Patterns(
+                expected_patterns
+                    .into_iter()
+                    .map(|pattern| (pattern.to_string(), Regex::new(pattern).unwrap()))
+                    .collect()
+            )),
+            patterns,
+            "{}",
+            title
+        );
+        assert_eq!(expected_messages.len(), v.violations.len(), "{}", title);
+        // messages from regex are only checked for their prefix
+        expected_messages
+            .iter()
+            .zip(v.violations.iter())
+            .for_each(|(expected, actual)| {
+                assert!(
+                    actual.message.starts_with(expected),
+                    "{}: {}",
+                    title,
+                    actual.message
+                );
+            });
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::testutil::new_validator;
+        use once_cell::sync::Lazy;
+        use pretty_assertions::assert_eq;
+
+        static IGNORE: Lazy<Yaml> = Lazy::new(|| {
+            Yaml::Array(vec![
+                Yaml::String("$.id".to_string()),
+                Yaml::String(".items[].id".to_string()),
+            ])
+        });
+        static TRUE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(true));
+        static TOLERANCE: Lazy<Yaml> = Lazy::new(|| Yaml::Real("1e-6".to_string()));
+        static NEGATIVE: Lazy<Yaml> = Lazy::new(|| Yaml::Integer(-1));
+        static INVALID_PATHS: Lazy<Yaml> =
+            Lazy::new(|| Yaml::Array(vec![Yaml::String("$.items[".to_string())]));
+        static NOT_SEQ: Lazy<Yaml> = Lazy::new(|| Yaml::String("$.id".to_string()));
+
+        #[rstest]
+        #[case("without options", indexmap! {}, options(vec![], false, None), vec![])]
+        #[case("with all options",
+            indexmap! { "ignore" => &*IGNORE, "unordered_arrays" => &*TRUE, "float_tolerance" => &*TOLERANCE },
+            options(vec!["$.id", "$.items[*].id"], true, Some(1e-6)),
+            vec![])]
+        #[case("with not seq ignore", indexmap! { "ignore" => &*NOT_SEQ }, options(vec![], false, None), vec![(".ignore", "should be seq, but is string")])]
+        #[case("with invalid path", indexmap! { "ignore" => &*INVALID_PATHS }, options(vec![], false, None), vec![(".ignore[0]", "should be valid JSON path, but ")])]
+        #[case("with negative tolerance", indexmap! { "float_tolerance" => &*NEGATIVE }, options(vec![], false, None), vec![(".float_tolerance", "should be non-negative number")])]
+        fn parse(
+            #[case] title: &str,
+            #[case] given: Map,
+            #[case] expected: JsonOptions,
+            #[case] expected_violations: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = new_validator();
+            let actual = JsonOptions::parse(&mut v, &given);
+
+            assert_eq!(expected, actual, "{}", title);
+            assert_eq!(expected_violations.len(), v.violations.len(), "{}", title);
+            // messages from external parsers are only checked for their prefix
+            expected_violations
+                .iter()
+                .zip(v.violations.iter())
+                .for_each(|((path, msg), actual)| {
+                    assert_eq!(violation(path, "").path, actual.path, "{}", title);
+                    assert!(
+                        actual.message.starts_with(msg),
+                        "{}: {}",
+                        title,
+                        actual.message
+                    );
+                });
+        }
+    }
+}
@@ -0,0 +1,457 @@
+use regex::Regex;
+use saphyr::Yaml;
+use serde_json_path::JsonPath;
+
+use crate::{ast::Ast, expr::to_json_value, matcher::parse_name, validator::Validator};
+
+// asserts on values selected from JSON by path expressions.
+// every selected value should satisfy all assertions of the path.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct JsonPathMatcher {
+    pub(super) queries: Vec<JsonPathQuery>,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct JsonPathQuery {
+    pub(super) original: String,
+    pub(super) path: JsonPath,
+    pub(super) assertions: Vec<(JsonValueAssertion, bool)>,
+}
+
+#[cfg_attr(test, derive(Debug))]
+pub enum JsonValueAssertion {
+    Eq(serde_json::Value),
+    MatchRegex(Regex),
+    Length(u64),
+    Gt(f64),
+}
+
+#[cfg(test)]
+impl PartialEq for JsonValueAssertion {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (JsonValueAssertion::Eq(a), JsonValueAssertion::Eq(b)) => a == b,
+            (JsonValueAssertion::MatchRegex(a), JsonValueAssertion::MatchRegex(b)) => {
+                a.as_str() == b.as_str()
+            }
+            (JsonValueAssertion::Length(a), JsonValueAssertion::Length(b)) => a == b,
+            (JsonValueAssertion::Gt(a), JsonValueAssertion::Gt(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl JsonValueAssertion {
+    fn matches(&self, actual: &serde_json::Value) -> Result<(bool, String), String> {
+        match self {
+            JsonValueAssertion::Eq(expected) => Ok((
+                json_eq(actual, expected),
+                if json_eq(actual, expected) {
+                    format!("should not be {}, but got it", expected)
+                } else {
+                    format!("should be {}, but got {}", expected, actual)
+                },
+            )),
+            JsonValueAssertion::MatchRegex(expected) => {
+                let s = actual
+                    .as_str()
+                    .ok_or_else(|| format!("should be string, but got {}", actual))?;
+                let matched = expected.is_match(s);
+                Ok((
+                    matched,
+                    if matched {
+                        format!("should not match /{}/, but got {}", expected, actual)
+                    } else {
+                        format!("should match /{}/, but got {}", expected, actual)
+                    },
+                ))
+            }
+            JsonValueAssertion::Length(expected) => {
+                let len = match actual {
+                    serde_json::Value::Array(a) => a.len(),
+                    serde_json::Value::Object(o) => o.len(),
+                    serde_json::Value::String(s) => s.chars().count(),
+                    _ => {
+                        return Err(format!(
+                            "should be array, object or string, but got {}",
+                            actual
+                        ))
+                    }
+                } as u64;
+                Ok((
+                    len == *expected,
+                    if len == *expected {
+                        format!("should not have length {}, but got it", expected)
+                    } else {
+                        format!("should have length {}, but got {}", expected, len)
+                    },
+                ))
+            }
+            JsonValueAssertion::Gt(expected) => {
+                let n = actual
+                    .as_f64()
+                    .ok_or_else(|| format!("should be number, but got {}", actual))?;
+                Ok((
+                    n > *expected,
+                    if n > *expected {
+                        format!(
+                            "should not be greater than {}, but got {}",
+                            expected, actual
+                        )
+                    } else {
+                        format!("should be greater than {}, but got {}", expected, actual)
+                    },
+                ))
+            }
+        }
+    }
+
+    fn parse(v: &mut Validator, name: &str, x: &Yaml) -> Option<Self> {
+        match name {
+            "eq" => v.in_field(name, |v| match to_json_value(x) {
+                Ok(expected) => Some(JsonValueAssertion::Eq(expected)),
+                Err(err) => {
+                    v.add_violation(format!("should be JSON value, but {}", err));
+                    None
+                }
+            }),
+            "match_regex" => v.in_field(name, |v| {
+                v.must_be_string(x)
+                    .and_then(|pattern| match Regex::new(&pattern) {
+                        Ok(expected) => Some(JsonValueAssertion::MatchRegex(expected)),
+                        Err(err) => {
+                            v.add_violation(format!("should be valid regex, but {}", err));
+                            None
+                        }
+                    })
+            }),
+            "length" => v.in_field(name, |v| v.must_be_uint(x).map(JsonValueAssertion::Length)),
+            "gt" => v.in_field(name, |v| match x {
+                Yaml::Integer(n) => Some(JsonValueAssertion::Gt(*n as f64)),
+                Yaml::Real(_) => x.as_f64().map(JsonValueAssertion::Gt),
+                _ => {
+                    v.add_violation(format!("should be number, but is {}", x.type_name()));
+                    None
+                }
+            }),
+            _ => {
+                v.add_violation(format!("json_path assertion \"{}\" is not defined", name));
+                None
+            }
+        }
+    }
+}
+
+impl JsonPathQuery {
+    // messages of assertions not satisfied by selected values
+    fn failures(&self, actual: &serde_json::Value) -> Vec<String> {
+        let nodes = self.path.query_located(actual).all();
+        if nodes.is_empty() {
+            return vec![format!(
+                "{} should select some value, but selected nothing",
+                self.original
+            )];
+        }
+
+        self.assertions
+            .iter()
+            .flat_map(|(assertion, expected_passed)| {
+                nodes.iter().filter_map(move |node| {
+                    let location = node.location().to_string();
+                    match assertion.matches(node.node()) {
+                        Ok((passed, _)) if passed == *expected_passed => None,
+                        Ok((_, message)) | Err(message) => {
+                            Some(format!("{} {}", location, message))
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+impl JsonPathMatcher {
+    pub fn matches(&self, actual: &[u8]) -> Result<(bool, String), String> {
+        let actual_str = String::from_utf8(actual.to_vec()).map_err(|_err| {
+            format!(
+                "should be valid utf8 string, but got \"{}\"",
+                String::from_utf8_lossy(actual)
+            )
+        })?;
+
+        let Ok(actual_json) = serde_json::from_str::<serde_json::Value>(&actual_str) else {
+            return Ok((
+                false,
+                format!("should be valid JSON string, but got \"{}\"", actual_str),
+            ));
+        };
+
+        let failures: Vec<String> = self
+            .queries
+            .iter()
+            .flat_map(|query| query.failures(&actual_json))
+            .collect();
+
+        if failures.is_empty() {
+            Ok((
+                true,
+                format!(
+                    "should not satisfy {}, but satisfied",
+                    self.queries
+                        .iter()
+                        .map(|query| query.original.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ))
+        } else {
+            Ok((false, failures.join("\n")))
+        }
+    }
+
+    pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
+        let m = v.must_be_map(x)?;
+        if m.is_empty() {
+            v.add_violation("should not be empty");
+            return None;
+        }
+
+        let queries: Vec<Option<JsonPathQuery>> = m
+            .into_iter()
+            .map(|(original, assertions)| {
+                v.in_field(original, |v| {
+                    let path = parse_path(v, original);
+                    // all assertions are parsed to report every violation
+                    let assertions = v.must_be_map(assertions).map(|assertions| {
+                        assertions
+                            .into_iter()
+                            .map(|(name, param)| {
+                                let (name, expected_passed) = parse_name(name);
+                                JsonValueAssertion::parse(v, name, param)
+                                    .map(|assertion| (assertion, expected_passed))
+                            })
+                            .collect::<Vec<_>>()
+                    });
+                    Some(JsonPathQuery {
+                        original: original.to_string(),
+                        path: path?,
+                        assertions: assertions?.into_iter().collect::<Option<_>>()?,
+                    })
+                })
+            })
+            .collect();
+
+        queries
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .map(|queries| Self { queries })
+    }
+}
+
+// numbers are compared numerically like gt, so that 2.0 equals 2
+fn json_eq(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    match (a, b) {
+        (serde_json::Value::Number(a), serde_json::Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (serde_json::Value::Array(a), serde_json::Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| json_eq(a, b))
+        }
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| json_eq(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+// bare `[]` is replaced with `[*]` and `.[` with `[`, but quoted keys are kept as is
+fn from_jq_style(original: &str) -> String {
+    let mut path = String::new();
+    let mut quote = None;
+    let mut chars = original.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quote.is_none() && c == '.' && chars.peek() == Some(&'[') {
+            continue;
+        }
+        path.push(c);
+        match (quote, c) {
+            (Some(_), '\\') => path.extend(chars.next()),
+            (Some(q), _) if q == c => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[') if chars.peek() == Some(&']') => path.push('*'),
+            _ => {}
+        }
+    }
+    path
+}
+
+// jq-style path such as `.items[].id` is read as `$.items[*].id`
+pub(super) fn parse_path(v: &mut Validator, original: &str) -> Option<JsonPath> {
+    let path = if original.starts_with('.') || original.starts_with('[') {
+        format!("${}", from_jq_style(original))
+    } else {
+        original.to_string()
+    };
+
+    match JsonPath::parse(&path) {
+        Ok(path) => Some(path),
+        Err(err) => {
+            v.add_violation(format!("should be valid JSON path, but {}", err));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn query(original: &str, assertions: Vec<(JsonValueAssertion, bool)>) -> JsonPathQuery {
+        JsonPathQuery {
+            original: original.to_string(),
+            path: JsonPath::parse(original).unwrap(),
+            assertions,
+        }
+    }
+
+    const GIVEN: &str = r#"{"items": [{"id": "item-1", "count": 2}, {"id": "item-2", "count": 5}], "message": "hello"}"#;
+
+    #[rstest]
+    #[case("with eq", query("$.message", vec![(JsonValueAssertion::Eq(serde_json::json!("hello")), true)]), GIVEN, Ok((true, "should not satisfy $.message, but satisfied".to_string())))]
+    #[case("with not satisfied eq", query("$.message", vec![(JsonValueAssertion::Eq(serde_json::json!("world")), true)]), GIVEN, Ok((false, r#"$['message'] should be "world", but got "hello""#.to_string())))]
+    #[case("with not.eq", query("$.message", vec![(JsonValueAssertion::Eq(serde_json::json!("hello")), false)]), GIVEN, Ok((false, r#"$['message'] should not be "hello", but got it"#.to_string())))]
+    #[case("with eq of integer for float", query("$.ratio", vec![(JsonValueAssertion::Eq(serde_json::json!(2)), true)]), r#"{"ratio": 2.0}"#, Ok((true, "should not satisfy $.ratio, but satisfied".to_string())))]
+    #[case("with eq of nested numbers", query("$.items[0]", vec![(JsonValueAssertion::Eq(serde_json::json!({"id": "item-1", "count": 2.0})), true)]), GIVEN, Ok((true, "should not satisfy $.items[0], but satisfied".to_string())))]
+    #[case("with match_regex for every element", query("$.items[*].id", vec![(JsonValueAssertion::MatchRegex(Regex::new("^item-").unwrap()), true)]), GIVEN, Ok((true, "should not satisfy $.items[*].id, but satisfied".to_string())))]
+    #[case("with match_regex for not string", query("$.items[*].count", vec![(JsonValueAssertion::MatchRegex(Regex::new("2").unwrap()), true)]), GIVEN, Ok((false, "$['items'][0]['count'] should be string, but got 2\n$['items'][1]['count'] should be string, but got 5".to_string())))]
+    #[case("with length", query("$.items", vec![(JsonValueAssertion::Length(2), true)]), GIVEN, Ok((true, "should not satisfy $.items, but satisfied".to_string())))]
+    #[case("with wrong length", query("$.items", vec![(JsonValueAssertion::Length(3), true)]), GIVEN, Ok((false, "$['items'] should have length 3, but got 2".to_string())))]
+    #[case("with gt failed by some element", query("$.items[*].count", vec![(JsonValueAssertion::Gt(3.0), true)]), GIVEN, Ok((false, "$['items'][0]['count'] should be greater than 3, but got 2".to_string())))]
+    #[case("with nothing selected", query("$.missing", vec![(JsonValueAssertion::Length(0), true)]), GIVEN, Ok((false, "$.missing should select some value, but selected nothing".to_string())))]
+    #[case("with not JSON", query("$.message", vec![]), "hello", Ok((false, "should be valid JSON string, but got \"hello\"".to_string())))]
+    fn matches(
+        #[case] title: &str,
+        #[case] query: JsonPathQuery,
+        #[case] given: &str,
+        #[case] expected: Result<(bool, String), String>,
+    ) {
+        let m = JsonPathMatcher {
+            queries: vec![query],
+        };
+        assert_eq!(expected, m.matches(given.as_bytes()), "{}", title);
+    }
+
+    #[rstest]
+    fn matches_with_not_utf8() {
+        let m = JsonPathMatcher {
+            queries: vec![query("$.message", vec![])],
+        };
+        assert_eq!(
+            Err("should be valid utf8 string, but got \"{\"message\": \u{FFFD}}\"".to_string()),
+            m.matches(b"{\"message\": \xFF}"),
+        );
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::testutil::new_validator;
+        use pretty_assertions::assert_eq;
+
+        fn paths(entries: Vec<(&str, Vec<(&str, Yaml)>)>) -> Yaml {
+            let mut m = saphyr::Hash::new();
+            entries.into_iter().for_each(|(path, assertions)| {
+                let mut a = saphyr::Hash::new();
+                assertions.into_iter().for_each(|(name, param)| {
+                    a.insert(Yaml::String(name.to_string()), param);
+                });
+                m.insert(Yaml::String(path.to_string()), Yaml::Hash(a));
+            });
+            Yaml::Hash(m)
+        }
+
+        fn parsed(
+            original: &str,
+            path: &str,
+            assertions: Vec<(JsonValueAssertion, bool)>,
+        ) -> JsonPathQuery {
+            JsonPathQuery {
+                original: original.to_string(),
+                path: JsonPath::parse(path).unwrap(),
+                assertions,
+            }
+        }
+
+        #[rstest]
+        #[case("with JSON path",
+            paths(vec![("$.items[*].id", vec![("match_regex", Yaml::String("^item-".to_string()))])]),
+            vec![parsed("$.items[*].id", "$.items[*].id", vec![(JsonValueAssertion::MatchRegex(Regex::new("^item-").unwrap()), true)])])]
+        #[case("with jq-style path",
+            paths(vec![(".items[].count", vec![("gt", Yaml::Integer(1)), ("not.eq", Yaml::Integer(3))])]),
+            vec![parsed(".items[].count", "$.items[*].count", vec![(JsonValueAssertion::Gt(1.0), true), (JsonValueAssertion::Eq(serde_json::json!(3)), false)])])]
+        #[case("with jq-style path having quoted key with brackets",
+            paths(vec![(r#".["a[]b"][]"#, vec![("length", Yaml::Integer(2))])]),
+            vec![parsed(r#".["a[]b"][]"#, r#"$["a[]b"][*]"#, vec![(JsonValueAssertion::Length(2), true)])])]
+        #[case("with length and float gt",
+            paths(vec![("$.items", vec![("length", Yaml::Integer(2))]), ("$.ratio", vec![("gt", Yaml::Real("0.5".to_string()))])]),
+            vec![
+                parsed("$.items", "$.items", vec![(JsonValueAssertion::Length(2), true)]),
+                parsed("$.ratio", "$.ratio", vec![(JsonValueAssertion::Gt(0.5), true)]),
+            ])]
+        fn success_cases(
+            #[case] title: &str,
+            #[case] given: Yaml,
+            #[case] expected: Vec<JsonPathQuery>,
+        ) {
+            let (mut v, _) = new_validator();
+            let actual = JsonPathMatcher::parse(&mut v, &given);
+
+            assert_eq!(
+                Some(JsonPathMatcher { queries: expected }),
+                actual,
+                "{}",
+                title
+            );
+            assert!(v.violations.is_empty(), "{}", title);
+        }
+
+        #[rstest]
+        #[case("with not map", Yaml::Boolean(true), vec![("", "should be map, but is bool")])]
+        #[case("with empty map", paths(vec![]), vec![("", "should not be empty")])]
+        #[case("with invalid path", paths(vec![("$.items[", vec![])]), vec![(".$.items[", "should be valid JSON path, but ")])]
+        #[case("with not map assertions", {
+            let mut m = saphyr::Hash::new();
+            m.insert(Yaml::String("$.items".to_string()), Yaml::Integer(1));
+            Yaml::Hash(m)
+        }, vec![(".$.items", "should be map, but is uint")])]
+        #[case("with unknown assertion", paths(vec![("$.items", vec![("unknown", Yaml::Integer(1))])]), vec![(".$.items", "json_path assertion \"unknown\" is not defined")])]
+        #[case("with invalid regex", paths(vec![("$.items", vec![("match_regex", Yaml::String("(".to_string()))])]), vec![(".$.items.match_regex", "should be valid regex, but ")])]
+        #[case("with not uint length", paths(vec![("$.items", vec![("length", Yaml::String("2".to_string()))])]), vec![(".$.items.length", "should be uint, but is string")])]
+        #[case("with not number gt", paths(vec![("$.items", vec![("gt", Yaml::String("2".to_string()))])]), vec![(".$.items.gt", "should be number, but is string")])]
+        fn failure_cases(
+            #[case] title: &str,
+            #[case] given: Yaml,
+            #[case] expected_violations: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = new_validator();
+            let actual = JsonPathMatcher::parse(&mut v, &given);
+
+            assert!(actual.is_none(), "{}", title);
+            assert_eq!(expected_violations.len(), v.violations.len(), "{}", title);
+            // messages from external parsers are only checked for their prefix
+            expected_violations
+                .iter()
+                .zip(v.violations.iter())
+                .for_each(|((path, msg), actual)| {
+                    assert_eq!(violation(path, "").path, actual.path, "{}", title);
+                    assert!(
+                        actual.message.starts_with(msg),
+                        "{}: {}",
+                        title,
+                        actual.message
+                    );
+                });
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use std::path::{Path, PathBuf};
+
+use saphyr::Yaml;
+
+use crate::validator::Validator;
+
+use super::eq::diff;
+
+// compares with the golden file, which is read when matching,
+// since it may be created or rewritten by `--update-snapshots`
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct MatchSnapshotMatcher {
+    pub(super) path: PathBuf,
+}
+
+impl MatchSnapshotMatcher {
+    pub fn matches(&self, actual: &[u8]) -> Result<(bool, String), String> {
+        let expected = match std::fs::read(&self.path) {
+            Ok(expected) => expected,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Err(format!(
+                    "snapshot {} does not exist (run with --update-snapshots to create it)",
+                    self.path.display()
+                ))
+            }
+            Err(err) => {
+                return Err(format!(
+                    "cannot read snapshot {}: {}",
+                    self.path.display(),
+                    err
+                ))
+            }
+        };
+
+        if actual == expected {
+            Ok((
+                true,
+                format!(
+                    "should not match snapshot {}, but matched it",
+                    self.path.display()
+                ),
+            ))
+        } else {
+            Ok((
+                false,
+                format!(
+                    "not equals to snapshot {}:\n\n{}",
+                    self.path.display(),
+                    diff(&expected, actual)
+                ),
+            ))
+        }
+    }
+
+    // golden file is left untouched when it already has the content
+    pub fn update(&self, actual: &[u8]) -> Result<(), String> {
+        if std::fs::read(&self.path).is_ok_and(|expected| expected == actual) {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|err| format!("cannot create directory {}: {}", dir.display(), err))?;
+        }
+        std::fs::write(&self.path, actual)
+            .map_err(|err| format!("cannot write snapshot {}: {}", self.path.display(), err))
+    }
+
+    // relative path is resolved from the directory of the test case file
+    pub fn parse(v: &mut Validator, x: &Yaml) -> Option<Self> {
+        v.must_be_string(x).map(|path| {
+            let dir = Path::new(&v.filename).parent().unwrap_or(Path::new(""));
+            Self {
+                path: dir.join(path),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("with same content", Some("hello\n"), "hello\n", Ok((true, "should not match snapshot {}, but matched it".to_string())))]
+    #[case("with different content", Some("hello\n"), "goodbye\n", Ok((false, "not equals to snapshot {}:\n\n-hello\n+goodbye\n".to_string())))]
+    #[case("without snapshot", None, "hello\n", Err("snapshot {} does not exist (run with --update-snapshots to create it)".to_string()))]
+    fn matches(
+        #[case] title: &str,
+        #[case] snapshot: Option<&str>,
+        #[case] given: &str,
+        #[case] expected: Result<(bool, String), String>,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("golden.txt");
+        if let Some(snapshot) = snapshot {
+            std::fs::write(&path, snapshot).unwrap();
+        }
+        let m = MatchSnapshotMatcher { path: path.clone() };
+
+        let with_path = |message: String| message.replace("{}", &path.display().to_string());
+        assert_eq!(
+            expected
+                .map(|(matched, message)| (matched, with_path(message)))
+                .map_err(with_path),
+            m.matches(given.as_bytes()),
+            "{}",
+            title
+        );
+    }
+
+    #[rstest]
+    #[case("without snapshot", None, "hello\n")]
+    #[case("with outdated snapshot", Some("hello\n"), "goodbye\n")]
+    #[case("with same snapshot", Some("hello\n"), "hello\n")]
+    fn update(#[case] title: &str, #[case] snapshot: Option<&str>, #[case] given: &str) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshots").join("golden.txt");
+        if let Some(snapshot) = snapshot {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, snapshot).unwrap();
+        }
+        let m = MatchSnapshotMatcher { path: path.clone() };
+
+        assert_eq!(Ok(()), m.update(given.as_bytes()), "{}", title);
+        assert_eq!(given, std::fs::read_to_string(&path).unwrap(), "{}", title);
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::{testutil::new_validator, Validator};
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        #[case(
+            "with file in current directory",
+            "test.yaml",
+            "golden.txt",
+            "golden.txt"
+        )]
+        #[case(
+            "with file in other directory",
+            "tests/test.yaml",
+            "snapshots/golden.txt",
+            "tests/snapshots/golden.txt"
+        )]
+        #[case(
+            "with absolute path",
+            "tests/test.yaml",
+            "/tmp/golden.txt",
+            "/tmp/golden.txt"
+        )]
+        #[case("with stdin", "<stdin>", "golden.txt", "golden.txt")]
+        fn success_cases(
+            #[case] title: &str,
+            #[case] filename: &str,
+            #[case] given: &str,
+            #[case] expected_path: &str,
+        ) {
+            let mut v = Validator::new(filename);
+            let actual = MatchSnapshotMatcher::parse(&mut v, &Yaml::String(given.to_string()));
+
+            assert_eq!(
+                Some(MatchSnapshotMatcher {
+                    path: PathBuf::from(expected_path),
+                }),
+                actual,
+                "{}",
+                title
+            );
+            assert!(v.violations.is_empty(), "{}", title);
+        }
+
+        #[rstest]
+        #[case(
+            "with not string",
+            Yaml::Boolean(true),
+            "should be string, but is bool"
+        )]
+        fn failure_cases(#[case] title: &str, #[case] given: Yaml, #[case] expected_message: &str) {
+            let (mut v, violation) = new_validator();
+            let actual = MatchSnapshotMatcher::parse(&mut v, &given);
+
+            assert!(actual.is_none(), "{}", title);
+            assert_eq!(
+                vec![violation("", expected_message)],
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
+use saphyr::Yaml;
+
+use crate::{expr::to_json_value, validator::Validator};
+
+use super::eq_json::parse_expected_value;
+
+// text formats which are compared structurally as JSON values,
+// so that formatting and key order are ignored
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Format {
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    fn name(&self) -> &'static str {
+        match self {
+            Format::Yaml => "YAML",
+            Format::Toml => "TOML",
+        }
+    }
+
+    fn parse_value(&self, s: &str) -> Result<serde_json::Value, String> {
+        match self {
+            // first document is compared, and empty output is null
+            Format::Yaml => Yaml::load_from_str(s)
+                .map_err(|err| err.to_string())
+                .and_then(|docs| {
+                    docs.first()
+                        .map(to_json_value)
+                        .unwrap_or(Ok(serde_json::Value::Null))
+                }),
+            Format::Toml => s
+                .parse::<toml::Table>()
+                .map(|table| toml_to_json_value(toml::Value::Table(table)))
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+// datetime is compared as string in the same format as in TOML
+fn toml_to_json_value(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => serde_json::Value::String(s),
+        toml::Value::Integer(n) => serde_json::Value::Number(n.into()),
+        toml::Value::Float(n) => serde_json::Number::from_f64(n)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        toml::Value::Boolean(b) => serde_json::Value::Bool(b),
+        toml::Value::Datetime(d) => serde_json::Value::String(d.to_string()),
+        toml::Value::Array(a) => {
+            serde_json::Value::Array(a.into_iter().map(toml_to_json_value).collect())
+        }
+        toml::Value::Table(t) => serde_json::Value::Object(
+            t.into_iter()
+                .map(|(k, v)| (k, toml_to_json_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+// eq or include matcher for YAML or TOML output
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct StructuredMatcher {
+    pub(super) format: Format,
+    pub(super) inclusive: bool,
+    pub(super) expected: serde_json::Value,
+    pub(super) original: String,
+}
+
+impl StructuredMatcher {
+    pub fn matches(&self, actual: &[u8]) -> Result<(bool, String), String> {
+        let actual_str = String::from_utf8(actual.to_vec()).map_err(|_err| {
+            format!(
+                "should be valid utf8 string, but got \"{}\"",
+                String::from_utf8_lossy(actual)
+            )
+        })?;
+
+        let Ok(actual_value) = self.format.parse_value(&actual_str) else {
+            return Ok((
+                false,
+                format!(
+                    "should be valid {} string, but got \"{}\"",
+                    self.format.name(),
+                    actual_str
+                ),
+            ));
+        };
+
+        let mode = if self.inclusive {
+            CompareMode::Inclusive
+        } else {
+            CompareMode::Strict
+        };
+        match assert_json_matches_no_panic(&actual_value, &self.expected, Config::new(mode)) {
+            Ok(_) => Ok((
+                true,
+                format!(
+                    "should not {} {} as {}, but got it",
+                    if self.inclusive { "include" } else { "be" },
+                    self.original,
+                    self.format.name()
+                ),
+            )),
+            Err(msg) => Ok((false, msg)),
+        }
+    }
+
+    // expected value is given as string in the format or native YAML value like eq_json
+    pub fn parse(v: &mut Validator, x: &Yaml, format: Format, inclusive: bool) -> Option<Self> {
+        parse_expected_value(v, x, format.name(), |s| format.parse_value(s)).map(
+            |(expected, original)| Self {
+                format,
+                inclusive,
+                expected,
+                original,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::json;
+
+    const YAML: &str = "message: hello\nnums: [1, 2]\n";
+    const TOML: &str = "message = \"hello\"\nnums = [1, 2]\n";
+
+    #[rstest]
+    #[case("eq_yaml with same value", Format::Yaml, false, YAML, "nums:\n  - 1\n  - 2\nmessage: hello\n", Ok((true, format!("should not be {} as YAML, but got it", YAML))))]
+    #[case("eq_yaml with different value", Format::Yaml, false, YAML, "{message: world, nums: [1, 2]}", Ok((false, "json atoms at path \".message\" are not equal:\n    lhs:\n        \"world\"\n    rhs:\n        \"hello\"".to_string())))]
+    #[case("eq_yaml with extra key", Format::Yaml, false, YAML, "{message: hello, nums: [1, 2], passed: true}", Ok((false, "json atom at path \".passed\" is missing from rhs".to_string())))]
+    #[case("eq_yaml with invalid YAML", Format::Yaml, false, YAML, "message: [hello", Ok((false, "should be valid YAML string, but got \"message: [hello\"".to_string())))]
+    #[case("include_yaml with extra key", Format::Yaml, true, YAML, "{message: hello, nums: [1, 2, 3], passed: true}", Ok((true, format!("should not include {} as YAML, but got it", YAML))))]
+    #[case("include_yaml with missing key", Format::Yaml, true, YAML, "{nums: [1, 2]}", Ok((false, "json atom at path \".message\" is missing from actual".to_string())))]
+    #[case("eq_toml with same value", Format::Toml, false, TOML, "nums = [1, 2]\nmessage = \"hello\"\n", Ok((true, format!("should not be {} as TOML, but got it", TOML))))]
+    #[case("eq_toml with extra table", Format::Toml, false, TOML, "message = \"hello\"\nnums = [1, 2]\n[extra]\nkey = 1\n", Ok((false, "json atom at path \".extra\" is missing from rhs".to_string())))]
+    #[case("eq_toml with invalid TOML", Format::Toml, false, TOML, "message = ", Ok((false, "should be valid TOML string, but got \"message = \"".to_string())))]
+    #[case("include_toml with extra key", Format::Toml, true, TOML, "message = \"hello\"\nnums = [1, 2]\npassed = true\n", Ok((true, format!("should not include {} as TOML, but got it", TOML))))]
+    #[case("include_toml with different value", Format::Toml, true, TOML, "message = \"world\"\nnums = [1, 2]\n", Ok((false, "json atoms at path \".message\" are not equal:\n    expected:\n        \"hello\"\n    actual:\n        \"world\"".to_string())))]
+    fn matches(
+        #[case] title: &str,
+        #[case] format: Format,
+        #[case] inclusive: bool,
+        #[case] original: &str,
+        #[case] given: &str,
+        #[case] expected: Result<(bool, String), String>,
+    ) {
+        let m = StructuredMatcher {
+            format,
+            inclusive,
+            expected: format.parse_value(original).unwrap(),
+            original: original.to_string(),
+        };
+        assert_eq!(expected, m.matches(given.as_bytes()), "{}", title);
+    }
+
+    #[rstest]
+    #[case("with empty YAML", Format::Yaml, "", Ok(json!(null)))]
+    #[case("with YAML documents", Format::Yaml, "--- 1\n--- 2\n", Ok(json!(1)))]
+    #[case("with TOML datetime", Format::Toml, "at = 1979-05-27T07:32:00Z", Ok(json!({"at": "1979-05-27T07:32:00Z"})))]
+    #[case("with TOML nested table", Format::Toml, "[a.b]\nc = 1.5", Ok(json!({"a": {"b": {"c": 1.5}}})))]
+    fn parse_value(
+        #[case] title: &str,
+        #[case] format: Format,
+        #[case] given: &str,
+        #[case] expected: Result<serde_json::Value, String>,
+    ) {
+        assert_eq!(expected, format.parse_value(given), "{}", title);
+    }
+
+    #[rstest]
+    fn matches_with_not_utf8() {
+        let m = StructuredMatcher {
+            format: Format::Yaml,
+            inclusive: false,
+            expected: json!({"message": "hello"}),
+            original: "message: hello".to_string(),
+        };
+        assert_eq!(
+            Err("should be valid utf8 string, but got \"message: �\"".to_string()),
+            m.matches(b"message: \xFF"),
+        );
+    }
+
+    mod parse {
+        use super::*;
+        use crate::validator::testutil::new_validator;
+        use pretty_assertions::assert_eq;
+
+        fn message_map(key: Yaml) -> Yaml {
+            let mut m = saphyr::Hash::new();
+            m.insert(key, Yaml::String("hello".to_string()));
+            Yaml::Hash(m)
+        }
+
+        #[rstest]
+        #[case("with YAML", Format::Yaml, Yaml::String("message: hello".to_string()), "message: hello")]
+        #[case("with TOML", Format::Toml, Yaml::String("message = \"hello\"".to_string()), "message = \"hello\"")]
+        #[case("with YAML value for YAML", Format::Yaml, message_map(Yaml::String("message".to_string())), r#"{"message":"hello"}"#)]
+        #[case("with YAML value for TOML", Format::Toml, message_map(Yaml::String("message".to_string())), r#"{"message":"hello"}"#)]
+        fn success_cases(
+            #[case] title: &str,
+            #[case] format: Format,
+            #[case] given: Yaml,
+            #[case] original: &str,
+        ) {
+            let (mut v, _) = new_validator();
+            let actual = StructuredMatcher::parse(&mut v, &given, format, true);
+
+            let expected = StructuredMatcher {
+                format,
+                inclusive: true,
+                expected: json!({"message": "hello"}),
+                original: original.to_string(),
+            };
+            assert_eq!(Some(expected), actual, "{}", title);
+        }
+
+        #[rstest]
+        #[case(
+            "with not string keyed map",
+            Format::Yaml,
+            message_map(Yaml::Integer(1)),
+            "should be YAML value, but key at index 0 is not string"
+        )]
+        #[case(
+            "with not valid YAML string",
+            Format::Yaml,
+            Yaml::String("message: [hello".to_string()),
+            "should be valid YAML string, but got \"message: [hello\""
+        )]
+        #[case(
+            "with not valid TOML string",
+            Format::Toml,
+            Yaml::String("message: hello".to_string()),
+            "should be valid TOML string, but got \"message: hello\""
+        )]
+        fn failure_cases(
+            #[case] title: &str,
+            #[case] format: Format,
+            #[case] given: Yaml,
+            #[case] expected_message: &str,
+        ) {
+            let (mut v, violation) = new_validator();
+            let actual = StructuredMatcher::parse(&mut v, &given, format, false);
+
+            assert!(actual.is_none(), "{}", title);
+            assert_eq!(
+                vec![violation("", expected_message)],
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+}
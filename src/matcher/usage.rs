@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use duration_str::HumanFormat;
+use saphyr::Yaml;
+
+use crate::{exec::Usage, validator::Validator};
+
+use super::parse_name;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Metric {
+    MaxRss,
+    UserTime,
+    SysTime,
+    WallTime,
+}
+
+impl Metric {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "max_rss" => Some(Metric::MaxRss),
+            "user_time" => Some(Metric::UserTime),
+            "sys_time" => Some(Metric::SysTime),
+            "wall_time" => Some(Metric::WallTime),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Metric::MaxRss => "max_rss",
+            Metric::UserTime => "user_time",
+            Metric::SysTime => "sys_time",
+            Metric::WallTime => "wall_time",
+        }
+    }
+
+    // bytes for max_rss, microseconds for others
+    fn value_of(&self, usage: &Usage) -> u64 {
+        match self {
+            Metric::MaxRss => usage.max_rss,
+            Metric::UserTime => usage.user_time.as_micros() as u64,
+            Metric::SysTime => usage.sys_time.as_micros() as u64,
+            Metric::WallTime => usage.wall_time.as_micros() as u64,
+        }
+    }
+
+    fn parse_value(&self, v: &mut Validator, x: &Yaml) -> Option<u64> {
+        match self {
+            Metric::MaxRss => v.must_be_size(x),
+            _ => v.must_be_duration(x).map(|d| d.as_micros() as u64),
+        }
+    }
+
+    fn format(&self, value: u64) -> String {
+        match self {
+            Metric::MaxRss => format!("{} bytes", value),
+            _ => Duration::from_micros(value).human_format(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "lt" => Some(Comparison::Lt),
+            "le" => Some(Comparison::Le),
+            "gt" => Some(Comparison::Gt),
+            "ge" => Some(Comparison::Ge),
+            _ => None,
+        }
+    }
+
+    fn compare(&self, actual: u64, expected: u64) -> bool {
+        match self {
+            Comparison::Lt => actual < expected,
+            Comparison::Le => actual <= expected,
+            Comparison::Gt => actual > expected,
+            Comparison::Ge => actual >= expected,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Comparison::Lt => "less than",
+            Comparison::Le => "less than or equal to",
+            Comparison::Gt => "greater than",
+            Comparison::Ge => "greater than or equal to",
+        }
+    }
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct UsageMatcher {
+    metric: Metric,
+    comparison: Comparison,
+    expected: u64,
+}
+
+impl UsageMatcher {
+    pub fn matches(&self, actual: &Usage) -> Result<(bool, String), String> {
+        let actual = self.metric.value_of(actual);
+        let matched = self.comparison.compare(actual, self.expected);
+
+        Ok((
+            matched,
+            format!(
+                "{} should {}be {} {}, but got {}",
+                self.metric.name(),
+                if matched { "not " } else { "" },
+                self.comparison.description(),
+                self.metric.format(self.expected),
+                self.metric.format(actual)
+            ),
+        ))
+    }
+
+    pub fn parse(
+        v: &mut Validator,
+        metric: &str,
+        name: &str,
+        param: &Yaml,
+    ) -> Option<(Self, bool)> {
+        let metric = match Metric::parse(metric) {
+            Some(metric) => metric,
+            None => {
+                v.add_violation(format!("usage \"{}\" is not defined", metric));
+                return None;
+            }
+        };
+
+        let (name, expected_passed) = parse_name(name);
+        let comparison = match Comparison::parse(name) {
+            Some(comparison) => comparison,
+            None => {
+                v.add_violation(format!("usage matcher \"{}\" is not defined", name));
+                return None;
+            }
+        };
+
+        v.in_field(name, |v| metric.parse_value(v, param))
+            .map(|expected| {
+                (
+                    Self {
+                        metric,
+                        comparison,
+                        expected,
+                    },
+                    expected_passed,
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::testutil;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    const USAGE: Usage = Usage {
+        max_rss: 2048,
+        user_time: Duration::from_millis(200),
+        sys_time: Duration::from_millis(100),
+        wall_time: Duration::from_secs(1),
+    };
+
+    #[rstest]
+    #[case(
+        Metric::MaxRss,
+        Comparison::Lt,
+        4096,
+        true,
+        "max_rss should not be less than 4096 bytes, but got 2048 bytes"
+    )]
+    #[case(
+        Metric::MaxRss,
+        Comparison::Lt,
+        2048,
+        false,
+        "max_rss should be less than 2048 bytes, but got 2048 bytes"
+    )]
+    #[case(
+        Metric::MaxRss,
+        Comparison::Le,
+        2048,
+        true,
+        "max_rss should not be less than or equal to 2048 bytes, but got 2048 bytes"
+    )]
+    #[case(
+        Metric::MaxRss,
+        Comparison::Gt,
+        2048,
+        false,
+        "max_rss should be greater than 2048 bytes, but got 2048 bytes"
+    )]
+    #[case(
+        Metric::MaxRss,
+        Comparison::Ge,
+        2048,
+        true,
+        "max_rss should not be greater than or equal to 2048 bytes, but got 2048 bytes"
+    )]
+    #[case(
+        Metric::UserTime,
+        Comparison::Lt,
+        100_000,
+        false,
+        "user_time should be less than 100ms, but got 200ms"
+    )]
+    #[case(
+        Metric::SysTime,
+        Comparison::Lt,
+        200_000,
+        true,
+        "sys_time should not be less than 200ms, but got 100ms"
+    )]
+    #[case(
+        Metric::WallTime,
+        Comparison::Gt,
+        2_000_000,
+        false,
+        "wall_time should be greater than 2s, but got 1s"
+    )]
+    fn matches(
+        #[case] metric: Metric,
+        #[case] comparison: Comparison,
+        #[case] expected: u64,
+        #[case] expected_matched: bool,
+        #[case] expected_message: &str,
+    ) {
+        let m = UsageMatcher {
+            metric,
+            comparison,
+            expected,
+        };
+
+        assert_eq!(
+            Ok((expected_matched, expected_message.to_string())),
+            m.matches(&USAGE)
+        );
+    }
+
+    #[rstest]
+    #[case("with max_rss and lt", "max_rss", "lt", Yaml::String("1MiB".to_string()), Some((UsageMatcher { metric: Metric::MaxRss, comparison: Comparison::Lt, expected: 1024 * 1024 }, true)), vec![])]
+    #[case("with wall_time and not.gt", "wall_time", "not.gt", Yaml::String("2s".to_string()), Some((UsageMatcher { metric: Metric::WallTime, comparison: Comparison::Gt, expected: 2_000_000 }, false)), vec![])]
+    #[case("with unknown metric", "unknown", "lt", Yaml::Integer(1), None, vec![("", "usage \"unknown\" is not defined")])]
+    #[case("with unknown name", "max_rss", "eq", Yaml::Integer(1), None, vec![("", "usage matcher \"eq\" is not defined")])]
+    #[case("with invalid size", "max_rss", "lt", Yaml::Boolean(true), None, vec![(".lt", "should be size, but is bool")])]
+    #[case("with invalid duration", "user_time", "lt", Yaml::Boolean(true), None, vec![(".lt", "should be duration, but is bool")])]
+    fn parse(
+        #[case] title: &str,
+        #[case] metric: &str,
+        #[case] name: &str,
+        #[case] param: Yaml,
+        #[case] expected_value: Option<(UsageMatcher, bool)>,
+        #[case] expected_violation: Vec<(&str, &str)>,
+    ) {
+        let (mut v, violation) = testutil::new_validator();
+        let actual = UsageMatcher::parse(&mut v, metric, name, &param);
+
+        assert_eq!(expected_value, actual, "{}", title);
+        assert_eq!(
+            expected_violation
+                .iter()
+                .map(|(path, msg)| violation(path, msg))
+                .collect::<Vec<_>>(),
+            v.violations,
+            "{}",
+            title
+        );
+    }
+}
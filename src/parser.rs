@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{os::fd::RawFd, time::Duration};
 
 use indexmap::{indexmap, IndexMap};
 use once_cell::sync::Lazy;
@@ -7,11 +7,20 @@ use regex::Regex;
 use saphyr::Yaml;
 
 use crate::{
-    ast::Map,
+    ast::{Ast, Map},
     expr::Expr,
+    test_case::{
+        action::Action,
+        extra_fd::parse_fd_map,
+        order::{Order, Stream},
+        pipe::PipeSource,
+        stdin::StdinFormat,
+        Limits, WindowSize,
+    },
     test_case_expr::{
         BackgroundConfigExpr, ProcessExpr, ProcessMatchersExpr, ProcessModeExpr, ProcessesExpr,
         ProcessesMatchersExpr, TestCaseExpr, TestCaseExprFile, WaitConditionExpr,
+        DEFAULT_PROCESS_NAME,
     },
     validator::{Validator, Violation},
 };
@@ -86,38 +95,63 @@ pub fn parse(filename: &str, mut reader: impl std::io::Read) -> Result<TestCaseE
                                         if processes.is_empty() {
                                             v.add_violation("should not be empty");
                                         }
-                                        ProcessesExpr::Multi(
-                                            processes
-                                                .iter()
-                                                .filter_map(|(name, process)| {
-                                                    v.in_field(name, |v| {
-                                                        v.must_be_map(process).map(|process| {
-                                                            (
-                                                                name.to_string(),
-                                                                parse_process(v, &process),
-                                                            )
-                                                        })
+                                        let names = processes.keys().copied().collect::<Vec<_>>();
+                                        let processes: IndexMap<String, ProcessExpr> = processes
+                                            .iter()
+                                            .enumerate()
+                                            .filter_map(|(i, (name, process))| {
+                                                v.in_field(name, |v| {
+                                                    v.must_be_map(process).map(|process| {
+                                                        (
+                                                            name.to_string(),
+                                                            parse_process(v, &process, &names[..i]),
+                                                        )
                                                     })
                                                 })
-                                                .collect(),
-                                        )
+                                            })
+                                            .collect();
+
+                                        // each output stream can be connected to only one process
+                                        let mut readers: Vec<(&PipeSource, &str)> = vec![];
+                                        for (name, process) in &processes {
+                                            let Some(from) = &process.stdin_from else {
+                                                continue;
+                                            };
+                                            match readers.iter().find(|(source, _)| *source == from) {
+                                                Some((_, reader)) => v.in_field(name, |v| {
+                                                    v.in_field("stdin", |v| {
+                                                        v.add_violation(format!(
+                                                            "should not read {}:{} which is already read by {}",
+                                                            from.process,
+                                                            from.stream.name(),
+                                                            reader
+                                                        ))
+                                                    })
+                                                }),
+                                                None => readers.push((from, name.as_str())),
+                                            }
+                                        }
+
+                                        ProcessesExpr::Multi(processes)
                                     })
                                     .unwrap_or_else(|| ProcessesExpr::Multi(indexmap! {}))
                             })
-                            .unwrap_or_else(|| ProcessesExpr::Single(parse_process(v, &test)));
+                            .unwrap_or_else(|| ProcessesExpr::Single(parse_process(v, &test, &[])));
+
+                        let parallel = v.may_have_bool(&test, "parallel").unwrap_or(false);
 
-                        let (processes_matchers, files_matchers): (ProcessesMatchersExpr,  IndexMap<String, IndexMap<String, Expr>>) = v
+                        let (processes_matchers, files_matchers, order): (ProcessesMatchersExpr,  IndexMap<String, IndexMap<String, Expr>>, Vec<Order>) = v
                             .may_have_map(&test, "expect", |v, expect| {
-                                let processes_matchers = v.may_have_map(expect, "processes", |v, processes| {
+                                let processes_matchers = v.may_have_map(expect, "processes", |v, expectations| {
                                     ProcessesMatchersExpr::Multi(
-                                        processes
+                                        expectations
                                             .iter()
                                             .filter_map(|(name, process)| {
                                                 v.in_field(name, |v| {
                                                     v.must_be_map(process).map(|process| {
                                                         (
                                                             name.to_string(),
-                                                            parse_expectations(v, &process),
+                                                            parse_expectations(v, &process, &processes.captured_fds(name), &processes.piped_streams(name)),
                                                         )
                                                     })
                                                 })
@@ -126,7 +160,7 @@ pub fn parse(filename: &str, mut reader: impl std::io::Read) -> Result<TestCaseE
                                     )
                                 })
                                 .unwrap_or_else(|| {
-                                    ProcessesMatchersExpr::Single(parse_expectations(v, expect))
+                                    ProcessesMatchersExpr::Single(parse_expectations(v, expect, &processes.captured_fds(DEFAULT_PROCESS_NAME), &[]))
                                 });
 
                                 let files_matchers = v.may_have_map(expect, "files", |v, files| {
@@ -145,9 +179,14 @@ pub fn parse(filename: &str, mut reader: impl std::io::Read) -> Result<TestCaseE
                                         .collect()
                                 }).unwrap_or_default();
 
-                                (processes_matchers, files_matchers)
+                                let process_names = processes.names();
+                                let order = v.may_have_seq(expect, "order", |v, orders| {
+                                    v.map_seq(orders, |v, order| Order::parse(v, order, &process_names))
+                                }).flatten().unwrap_or_default();
+
+                                (processes_matchers, files_matchers, order)
                             })
-                            .unwrap_or((ProcessesMatchersExpr::Multi(indexmap! {}), indexmap! {}));
+                            .unwrap_or((ProcessesMatchersExpr::Multi(indexmap! {}), indexmap! {}, vec![]));
 
                         if let (ProcessesExpr::Multi(_), ProcessesMatchersExpr::Single(_)) =
                             (&processes, &processes_matchers)
@@ -167,6 +206,8 @@ pub fn parse(filename: &str, mut reader: impl std::io::Read) -> Result<TestCaseE
                             processes,
                             processes_matchers,
                             files_matchers,
+                            order,
+                            parallel,
                         }
                     })
                 })
@@ -197,7 +238,8 @@ pub fn parse(filename: &str, mut reader: impl std::io::Read) -> Result<TestCaseE
     }
 }
 
-fn parse_process(v: &mut Validator, m: &Map) -> ProcessExpr {
+// `process_names` are names of processes started before, which can be connected to stdin
+fn parse_process(v: &mut Validator, m: &Map, process_names: &[&str]) -> ProcessExpr {
     let command_and_args = v
         .must_have_seq(m, "command", |v, command| {
             if command.is_empty() {
@@ -215,9 +257,88 @@ fn parse_process(v: &mut Validator, m: &Map) -> ProcessExpr {
         .map(|(command, args)| (command.clone(), args.to_vec()))
         .unwrap_or_else(|| (Expr::Literal(Yaml::String("true".to_string())), vec![]));
 
-    let stdin = v
-        .may_have(m, "stdin", parse_expr)
-        .unwrap_or(Expr::Literal(Yaml::String("".to_string())));
+    // stdin given as map with .from is connected to output of the other process,
+    // stdin given as map with format (e.g. .base64) is decoded into bytes,
+    // and stdin given as seq is fed in chunks
+    let stdin_map = m.get("stdin").and_then(|stdin| stdin.as_hash());
+    let is_piped =
+        stdin_map.is_some_and(|stdin| stdin.contains_key(&Yaml::String("from".to_string())));
+    let keyed_format = stdin_map.and_then(|stdin| {
+        StdinFormat::KEYED
+            .into_iter()
+            .find(|format| stdin.contains_key(&Yaml::String(format.name().to_string())))
+    });
+    let is_chunked = m.get("stdin").is_some_and(|stdin| stdin.as_vec().is_some());
+    let (stdin, stdin_format, stdin_chunks, stdin_from) = if is_piped {
+        let stdin_from = v
+            .may_have_map(m, "stdin", |v, stdin| {
+                PipeSource::parse(v, stdin, process_names)
+            })
+            .flatten();
+        (
+            Expr::Literal(Yaml::String("".to_string())),
+            StdinFormat::Text,
+            None,
+            stdin_from,
+        )
+    } else if let Some(format) = keyed_format {
+        let stdin = v
+            .may_have_map(m, "stdin", |v, stdin| {
+                if stdin.len() != 1 {
+                    v.add_violation("should have only one of .file, .base64 and .hex");
+                }
+                v.may_have(stdin, format.name(), parse_expr)
+            })
+            .flatten()
+            .unwrap_or(Expr::Literal(Yaml::String("".to_string())));
+        (stdin, format, None, None)
+    } else if is_chunked {
+        let stdin_chunks = v
+            .may_have_seq(m, "stdin", |v, chunks| {
+                v.map_seq(chunks, |v, chunk| {
+                    v.must_be_map(chunk).map(|chunk| parse_expected(v, &chunk))
+                })
+            })
+            .flatten();
+        if let Some(chunks) = &stdin_chunks {
+            let last = chunks.len().saturating_sub(1);
+            for (i, chunk) in chunks.iter().enumerate() {
+                if chunk.contains_key("close") && i != last {
+                    v.in_field("stdin", |v| {
+                        v.in_index(i, |v| {
+                            v.add_violation("should be last chunk to close stdin")
+                        })
+                    });
+                }
+            }
+        }
+        (
+            Expr::Literal(Yaml::String("".to_string())),
+            StdinFormat::Text,
+            stdin_chunks,
+            None,
+        )
+    } else {
+        let stdin = v
+            .may_have(m, "stdin", parse_expr)
+            .unwrap_or(Expr::Literal(Yaml::String("".to_string())));
+        (stdin, StdinFormat::Text, None, None)
+    };
+    let fds = v
+        .may_have(m, "fds", |v, fds| {
+            parse_fd_map(v, fds).map(|fds| {
+                fds.into_iter()
+                    .filter_map(|(fd, params)| {
+                        v.in_field(fd.to_string(), |v| {
+                            v.must_be_map(params)
+                                .map(|params| (fd, parse_expected(v, &params)))
+                        })
+                    })
+                    .collect()
+            })
+        })
+        .flatten()
+        .unwrap_or_default();
     let env: Vec<(String, Expr)> = v
         .may_have_map(m, "env", |v, env| {
             env.into_iter()
@@ -233,6 +354,38 @@ fn parse_process(v: &mut Validator, m: &Map) -> ProcessExpr {
                 .collect::<Vec<_>>()
         })
         .unwrap_or_default();
+    let dir = v.may_have(m, "dir", parse_expr);
+    let tty = v.may_have(m, "tty", parse_tty).flatten();
+    let dialogue = v
+        .may_have_seq(m, "dialogue", |v, steps| {
+            v.map_seq(steps, |v, step| {
+                v.must_be_map(step).map(|step| parse_expected(v, &step))
+            })
+        })
+        .flatten()
+        .unwrap_or_default();
+    if stdin_from.is_some() && !dialogue.is_empty() {
+        v.in_field("dialogue", |v| {
+            v.add_violation("should not be given when stdin is connected to other process")
+        });
+    }
+    if stdin_from.is_some() && tty.is_some() {
+        v.in_field("tty", |v| {
+            v.add_violation("should not be enabled when stdin is connected to other process")
+        });
+    }
+    let limits = v
+        .may_have_map(m, "limits", parse_limits)
+        .unwrap_or_default();
+    let actions = v
+        .may_have_seq(m, "actions", |v, actions| {
+            v.map_seq(actions, |v, action| {
+                v.must_be_map(action)
+                    .and_then(|action| Action::parse(v, &action))
+            })
+        })
+        .flatten()
+        .unwrap_or_default();
     let timeout = v
         .may_have_duration(m, "timeout")
         .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT));
@@ -252,22 +405,85 @@ fn parse_process(v: &mut Validator, m: &Map) -> ProcessExpr {
                     .collect();
                 WaitConditionExpr { name, params }
             });
-            ProcessModeExpr::Background(BackgroundConfigExpr { wait_condition })
+            let default = BackgroundConfigExpr::default();
+            let stop_signal = v
+                .may_have_signal(background, "stop_signal")
+                .unwrap_or(default.stop_signal);
+            let stop_timeout = v.may_have_duration(background, "stop_timeout");
+            let stop_order = v.may_have_uint(background, "stop_order");
+            ProcessModeExpr::Background(BackgroundConfigExpr {
+                wait_condition,
+                stop_signal,
+                stop_timeout,
+                stop_order,
+            })
         })
         .unwrap_or(ProcessModeExpr::Foreground);
     let tee_stdout = v.may_have_bool(m, "tee_stdout").unwrap_or(false);
     let tee_stderr = v.may_have_bool(m, "tee_stderr").unwrap_or(false);
+    let check_leftover = v.may_have_bool(m, "check_leftover").unwrap_or(false);
 
     ProcessExpr {
         command,
         args,
         stdin,
+        stdin_format,
+        stdin_chunks,
+        stdin_from,
+        fds,
         env,
+        dir,
+        tty,
+        dialogue,
+        limits,
+        actions,
         timeout,
         mode,
         tee_stdout,
         tee_stderr,
+        check_leftover,
+    }
+}
+
+fn parse_limits(v: &mut Validator, m: &Map) -> Limits {
+    Limits {
+        cpu: v.may_have_duration(m, "cpu"),
+        memory: v.may_have_size(m, "memory"),
+        nofile: v.may_have_uint(m, "nofile"),
+        fsize: v.may_have_size(m, "fsize"),
+        core: v.may_have_size(m, "core"),
+    }
+}
+
+fn parse_tty(v: &mut Validator, x: &Yaml) -> Option<WindowSize> {
+    if let Some(enabled) = x.as_bool() {
+        return enabled.then(WindowSize::default);
+    }
+
+    if x.as_hash().is_none() {
+        v.add_violation(format!("should be bool or map, but is {}", x.type_name()));
+        return None;
     }
+
+    v.must_be_map(x).map(|m| {
+        let default = WindowSize::default();
+        let dimension = |v: &mut Validator, field: &str, default: u16| {
+            v.may_have_uint(&m, field)
+                .and_then(|n| {
+                    u16::try_from(n).ok().or_else(|| {
+                        v.in_field(field, |v| {
+                            v.add_violation(format!("should be at most {}", u16::MAX))
+                        });
+                        None
+                    })
+                })
+                .unwrap_or(default)
+        };
+        WindowSize {
+            rows: dimension(v, "rows", default.rows),
+            cols: dimension(v, "cols", default.cols),
+        }
+    })
 }
 
 static ENV_VAR_EXPR_RE: Lazy<Regex> =
@@ -310,6 +526,7 @@ fn parse_expr(v: &mut Validator, x: &Yaml) -> Expr {
                 })
             }),
             "tmp_port" => Some(Expr::TmpPort),
+            "work_dir" => Some(Expr::WorkDir),
             "var" => v.in_field("$var", |v| {
                 v.must_be_string(value).and_then(|s| {
                     if VAR_EXPR_RE.is_match(&s) {
@@ -325,20 +542,80 @@ fn parse_expr(v: &mut Validator, x: &Yaml) -> Expr {
         .unwrap_or_else(|| Expr::Literal(x.clone()))
 }
 
-fn parse_expectations(v: &mut Validator, m: &Map) -> ProcessMatchersExpr {
+// `captured_fds` are fds whose output can be matched,
+// and `piped_streams` are streams read by other processes, which cannot be matched
+fn parse_expectations(
+    v: &mut Validator,
+    m: &Map,
+    captured_fds: &[RawFd],
+    piped_streams: &[Stream],
+) -> ProcessMatchersExpr {
+    for stream in piped_streams {
+        if m.contains_key(stream.name()) {
+            v.in_field(stream.name(), |v| {
+                v.add_violation("should not be given when it is connected to other process")
+            });
+        }
+    }
+
     let status_matcher_exprs = v
         .may_have_map(m, "status", parse_expected)
         .unwrap_or_default();
+    let signal_matcher_exprs = v
+        .may_have_map(m, "signal", parse_expected)
+        .unwrap_or_default();
     let stdout_matcher_exprs = v
         .may_have_map(m, "stdout", parse_expected)
         .unwrap_or_default();
     let stderr_matcher_exprs = v
         .may_have_map(m, "stderr", parse_expected)
         .unwrap_or_default();
+    let fd_matcher_exprs = v
+        .may_have(m, "fds", |v, fds| {
+            parse_fd_map(v, fds).map(|fds| {
+                fds.into_iter()
+                    .filter_map(|(fd, expectations)| {
+                        v.in_field(fd.to_string(), |v| {
+                            if !captured_fds.contains(&fd) {
+                                v.add_violation("should be fd captured by the process");
+                                return None;
+                            }
+                            v.must_be_map(expectations)
+                                .map(|expectations| (fd, parse_expected(v, &expectations)))
+                        })
+                    })
+                    .collect()
+            })
+        })
+        .flatten()
+        .unwrap_or_default();
+    let output_matcher_exprs = v
+        .may_have_map(m, "output", parse_expected)
+        .unwrap_or_default();
+    let usage_matcher_exprs = v
+        .may_have_map(m, "usage", |v, usage| {
+            usage
+                .iter()
+                .filter_map(|(metric, expectations)| {
+                    v.in_field(metric, |v| {
+                        v.must_be_map(expectations).map(|expectations| {
+                            (metric.to_string(), parse_expected(v, &expectations))
+                        })
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let timed_out = v.may_have_bool(m, "timed_out").unwrap_or(false);
     ProcessMatchersExpr {
         status_matcher_exprs,
+        signal_matcher_exprs,
         stdout_matcher_exprs,
         stderr_matcher_exprs,
+        fd_matcher_exprs,
+        output_matcher_exprs,
+        usage_matcher_exprs,
+        timed_out,
     }
 }
 
@@ -361,6 +638,10 @@ mod tests {
                 testutil::{env_var_expr, literal_expr, var_expr},
                 Expr,
             },
+            test_case::{
+                action::Trigger,
+                order::{Event, Stream},
+            },
             test_case_expr::{
                 testutil::{
                     ProcessExprTemplate, ProcessMatchersExprTemplate, ProcessesExprTemplate,
@@ -372,6 +653,7 @@ mod tests {
 
         use super::*;
         use indexmap::indexmap;
+        use nix::sys::signal::Signal;
         use pretty_assertions::assert_eq;
         use rstest::rstest;
         use saphyr::Yaml;
@@ -414,7 +696,7 @@ tests:
             NAME-John
             Doe
         - $env: SUFFIX-", vec![TestCaseExprTemplate {
-            processes: ProcessesExprTemplate::Single(
+            processes: ProcessesExprTemplate::Single(Box::new(
                 ProcessExprTemplate {
                     command: Expr::Literal(Yaml::String("echo".to_string())),
                     args: vec![
@@ -424,7 +706,7 @@ tests:
                     ],
                     ..Default::default()
                 }
-            ),
+            )),
             ..Default::default()
         }])]
         #[case("with command contains timeout", "
@@ -433,12 +715,12 @@ tests:
         - echo
         - hello
       timeout: 5s", vec![TestCaseExprTemplate {
-            processes: ProcessesExprTemplate::Single(
+            processes: ProcessesExprTemplate::Single(Box::new(
                 ProcessExprTemplate {
                     timeout: 5,
                     ..Default::default()
                 }
-            ),
+            )),
             ..Default::default()
         }])]
         #[case("with command cotains tee_stdout & tee_stderr", "
@@ -448,13 +730,27 @@ tests:
         - hello
       tee_stdout: true
       tee_stderr: true", vec![TestCaseExprTemplate {
-            processes: ProcessesExprTemplate::Single(
+            processes: ProcessesExprTemplate::Single(Box::new(
             ProcessExprTemplate {
                     tee_stdout: true,
                     tee_stderr: true,
                     ..Default::default()
                 }
-            ),
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains check_leftover", "
+tests:
+    - command:
+        - echo
+        - hello
+      check_leftover: true", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    check_leftover: true,
+                    ..Default::default()
+                }
+            )),
             ..Default::default()
         }])]
         #[case("with command contains simple stdin", "
@@ -462,14 +758,72 @@ tests:
     - command:
         - cat
       stdin: hello", vec![TestCaseExprTemplate {
-            processes: ProcessesExprTemplate::Single(
+            processes: ProcessesExprTemplate::Single(Box::new(
                 ProcessExprTemplate {
                     command: Expr::Literal(Yaml::String("cat".to_string())),
                     args: vec![],
                     stdin: literal_expr(Yaml::String("hello".to_string())),
                     ..Default::default()
                 }
-            ),
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains stdin chunks", "
+tests:
+    - command:
+        - cat
+      stdin:
+        - data: hello
+        - delay: 200ms
+        - data: {$env: MESSAGE}
+        - close: true", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    command: Expr::Literal(Yaml::String("cat".to_string())),
+                    args: vec![],
+                    stdin_chunks: Some(vec![
+                        indexmap! { "data" => literal_expr(Yaml::String("hello".to_string())) },
+                        indexmap! { "delay" => literal_expr(Yaml::String("200ms".to_string())) },
+                        indexmap! { "data" => env_var_expr("MESSAGE") },
+                        indexmap! { "close" => literal_expr(Yaml::Boolean(true)) },
+                    ]),
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains base64 stdin", "
+tests:
+    - command:
+        - cat
+      stdin:
+        base64: AP8K", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    command: Expr::Literal(Yaml::String("cat".to_string())),
+                    args: vec![],
+                    stdin: literal_expr(Yaml::String("AP8K".to_string())),
+                    stdin_format: StdinFormat::Base64,
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains file stdin", "
+tests:
+    - command:
+        - cat
+      stdin:
+        file: input.bin", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    command: Expr::Literal(Yaml::String("cat".to_string())),
+                    args: vec![],
+                    stdin: literal_expr(Yaml::String("input.bin".to_string())),
+                    stdin_format: StdinFormat::File,
+                    ..Default::default()
+                }
+            )),
             ..Default::default()
         }])]
         #[case("with command contains yaml stdin", "
@@ -479,14 +833,14 @@ tests:
       stdin:
         $yaml:
           message: hello", vec![TestCaseExprTemplate {
-            processes: ProcessesExprTemplate::Single(
+            processes: ProcessesExprTemplate::Single(Box::new(
                 ProcessExprTemplate {
                     command: Expr::Literal(Yaml::String("cat".to_string())),
                     args: vec![],
                     stdin: Expr::Yaml(Box::new(literal_expr(Yaml::Hash(mapping(vec![("message", Yaml::String("hello".to_string()))]))))),
                     ..Default::default()
                 }
-            ),
+            )),
             ..Default::default()
         }])]
         #[case("with command contains json stdin", "
@@ -496,14 +850,14 @@ tests:
       stdin:
         $json:
           message: hello", vec![TestCaseExprTemplate {
-            processes: ProcessesExprTemplate::Single(
+            processes: ProcessesExprTemplate::Single(Box::new(
                 ProcessExprTemplate {
                     command: Expr::Literal(Yaml::String("cat".to_string())),
                     args: vec![],
                     stdin: Expr::Json(Box::new(literal_expr(Yaml::Hash(mapping(vec![("message", Yaml::String("hello".to_string()))]))))),
                     ..Default::default()
                 }
-            ),
+            )),
             ..Default::default()
         }])]
         #[case("with command contains env", "
@@ -515,12 +869,12 @@ tests:
         MESSAGE1: hello
         MESSAGE2:
           $env: FOO", vec![TestCaseExprTemplate {
-            processes: ProcessesExprTemplate::Single(
+            processes: ProcessesExprTemplate::Single(Box::new(
                 ProcessExprTemplate {
                     env: vec![("MESSAGE1", literal_expr(Yaml::String("hello".to_string()))), ("MESSAGE2", env_var_expr("FOO"))],
                     ..Default::default()
                 }
-            ),
+            )),
             ..Default::default()
         }])]
         #[case(
@@ -534,7 +888,7 @@ tests:
             contents:
                 $yaml:
                     answer: 42", vec![TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(
+                processes: ProcessesExprTemplate::Single(Box::new(
                     ProcessExprTemplate {
                         command: Expr::Literal(Yaml::String("cat".to_string())),
                         args: vec![
@@ -545,7 +899,7 @@ tests:
                         ],
                         ..Default::default()
                     }
-                ),
+                )),
                 ..Default::default()
         }])]
         #[case(
@@ -555,15 +909,138 @@ tests:
     - command:
         - cat
         - $tmp_port: {}", vec![TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(
+                processes: ProcessesExprTemplate::Single(Box::new(
                     ProcessExprTemplate {
                         command: Expr::Literal(Yaml::String("cat".to_string())),
                         args: vec![Expr::TmpPort],
                         ..Default::default()
                     }
-                ),
+                )),
                 ..Default::default()
         }])]
+        #[case("with command contains dir", "
+tests:
+    - command:
+        - echo
+        - hello
+      dir:
+        $work_dir: {}", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    dir: Some(Expr::WorkDir),
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains tty", "
+tests:
+    - command:
+        - echo
+        - hello
+      tty: true", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    tty: Some(WindowSize { rows: 24, cols: 80 }),
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains tty window size", "
+tests:
+    - command:
+        - echo
+        - hello
+      tty:
+        cols: 120", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    tty: Some(WindowSize { rows: 24, cols: 120 }),
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains dialogue", "
+tests:
+    - command:
+        - echo
+        - hello
+      dialogue:
+        - expect: 'Continue\\?'
+          send: \"y\\n\"
+          timeout: 1s
+        - send: {$env: PASSWORD}", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    dialogue: vec![
+                        indexmap! {
+                            "expect" => literal_expr(Yaml::String("Continue\\?".to_string())),
+                            "send" => literal_expr(Yaml::String("y\n".to_string())),
+                            "timeout" => literal_expr(Yaml::String("1s".to_string())),
+                        },
+                        indexmap! {
+                            "send" => env_var_expr("PASSWORD"),
+                        },
+                    ],
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains limits", "
+tests:
+    - command:
+        - echo
+        - hello
+      limits:
+        cpu: 2s
+        memory: 256MiB
+        nofile: 64
+        fsize: 1MiB
+        core: 0", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    limits: Limits {
+                        cpu: Some(Duration::from_secs(2)),
+                        memory: Some(256 * 1024 * 1024),
+                        nofile: Some(64),
+                        fsize: Some(1024 * 1024),
+                        core: Some(0),
+                    },
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with command contains actions", "
+tests:
+    - command:
+        - echo
+        - hello
+      actions:
+        - after: 500ms
+          signal: HUP
+        - on_stdout: ready
+          signal: USR1", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    actions: vec![
+                        Action {
+                            trigger: Trigger::After(Duration::from_millis(500)),
+                            signal: Signal::SIGHUP,
+                        },
+                        Action {
+                            trigger: Trigger::OnStdout(Regex::new("ready").unwrap()),
+                            signal: Signal::SIGUSR1,
+                        },
+                    ],
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
         #[case(
             "with command contains var & let",
             "
@@ -579,13 +1056,13 @@ tests:
                     "message1" => literal_expr(Yaml::String("hello".to_string())),
                     "message2" => var_expr("message1"),
                 },
-                processes: ProcessesExprTemplate::Single(
+                processes: ProcessesExprTemplate::Single(Box::new(
                     ProcessExprTemplate {
                         command: Expr::Literal(Yaml::String("echo".to_string())),
                         args: vec![Expr::Var("message".to_string())],
                         ..Default::default()
                     }
-                ),
+                )),
                 ..Default::default()
         }])]
         #[case("with multiple processes", "
@@ -618,6 +1095,69 @@ tests:
             }),
             ..Default::default()
         }])]
+        #[case("with stdin from other process", "
+tests:
+    - processes:
+        producer:
+            command:
+                - echo
+                - hello
+        consumer:
+            command:
+                - cat
+            stdin:
+                from: producer
+                stream: stdout
+    ", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Multi(indexmap! {
+                "producer" => ProcessExprTemplate {
+                    command: literal_expr(Yaml::String("echo".to_string())),
+                    args: vec![
+                        literal_expr(Yaml::String("hello".to_string())),
+                    ],
+                    ..Default::default()
+                },
+                "consumer" => ProcessExprTemplate {
+                    command: literal_expr(Yaml::String("cat".to_string())),
+                    args: vec![],
+                    stdin_from: Some(PipeSource { process: "producer".to_string(), stream: Stream::Stdout }),
+                    ..Default::default()
+                },
+            }),
+            ..Default::default()
+        }])]
+        #[case("with parallel", "
+tests:
+    - processes:
+        process1:
+            command:
+                - echo
+                - hello
+        process2:
+            command:
+                - echo
+                - world
+      parallel: true
+    ", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Multi(indexmap! {
+                "process1" => ProcessExprTemplate {
+                    command: literal_expr(Yaml::String("echo".to_string())),
+                    args: vec![
+                        literal_expr(Yaml::String("hello".to_string())),
+                    ],
+                    ..Default::default()
+                },
+                "process2" => ProcessExprTemplate {
+                    command: literal_expr(Yaml::String("echo".to_string())),
+                    args: vec![
+                        literal_expr(Yaml::String("world".to_string())),
+                    ],
+                    ..Default::default()
+                },
+            }),
+            parallel: true,
+            ..Default::default()
+        }])]
         #[case("with multiple background processes", "
 tests:
     - processes:
@@ -637,7 +1177,7 @@ tests:
                     args: vec![
                         literal_expr(Yaml::String("hello".to_string())),
                     ],
-                    mode: ProcessModeExpr::Background(BackgroundConfigExpr { wait_condition: None }),
+                    mode: ProcessModeExpr::Background(BackgroundConfigExpr::default()),
                     ..Default::default()
                 },
                 "process2" => ProcessExprTemplate {
@@ -677,7 +1217,50 @@ tests:
                             wait_condition: Some(WaitConditionExpr {
                                 name: "success_stub".to_string(),
                                 params: indexmap! { "answer".to_string() => literal_expr(Yaml::Integer(42)) }
-                            })
+                            }),
+                            ..Default::default()
+                        }
+                    ),
+                    ..Default::default()
+                },
+                "process2" => ProcessExprTemplate {
+                    command: literal_expr(Yaml::String("echo".to_string())),
+                    args: vec![
+                        literal_expr(Yaml::String("world".to_string())),
+                    ],
+                    ..Default::default()
+                },
+            }),
+            ..Default::default()
+        }])]
+        #[case("with background process with stop config", "
+tests:
+    - processes:
+        process1:
+            command:
+                - echo
+                - hello
+            background:
+                stop_signal: INT
+                stop_timeout: 2s
+                stop_order: 1
+        process2:
+            command:
+                - echo
+                - world
+    ", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Multi(indexmap! {
+                "process1" => ProcessExprTemplate {
+                    command: literal_expr(Yaml::String("echo".to_string())),
+                    args: vec![
+                        literal_expr(Yaml::String("hello".to_string())),
+                    ],
+                    mode: ProcessModeExpr::Background(
+                        BackgroundConfigExpr {
+                            wait_condition: None,
+                            stop_signal: Signal::SIGINT,
+                            stop_timeout: Some(Duration::from_secs(2)),
+                            stop_order: Some(1),
                         }
                     ),
                     ..Default::default()
@@ -748,12 +1331,12 @@ tests:
       expect:
         status:
           success: true", vec![TestCaseExprTemplate {
-            processes_matchers: ProcessesMatchersExprTemplate::Single(
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
                 ProcessMatchersExprTemplate {
                     status_matcher_exprs: indexmap!{ "success" => literal_expr(Yaml::Boolean(true)) },
                     ..Default::default()
                 }
-            ),
+            )),
             ..Default::default()
         }])]
         #[case("with stdout matcher", "
@@ -764,12 +1347,43 @@ tests:
       expect:
         stdout:
           be_empty: true", vec![TestCaseExprTemplate {
-            processes_matchers: ProcessesMatchersExprTemplate::Single(
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
                 ProcessMatchersExprTemplate {
                     stdout_matcher_exprs: indexmap!{ "be_empty" => literal_expr(Yaml::Boolean(true)) },
                     ..Default::default()
                 }
-            ),
+            )),
+            ..Default::default()
+        }])]
+        #[case("with fds and fd matcher", "
+tests:
+    - command:
+        - echo
+        - hello
+      fds:
+        3:
+          input: hello
+        4:
+          capture: true
+      expect:
+        fds:
+          4:
+            be_empty: true", vec![TestCaseExprTemplate {
+            processes: ProcessesExprTemplate::Single(Box::new(
+                ProcessExprTemplate {
+                    fds: indexmap!{
+                        3 => indexmap!{ "input" => literal_expr(Yaml::String("hello".to_string())) },
+                        4 => indexmap!{ "capture" => literal_expr(Yaml::Boolean(true)) },
+                    },
+                    ..Default::default()
+                }
+            )),
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
+                ProcessMatchersExprTemplate {
+                    fd_matcher_exprs: indexmap!{ 4 => indexmap!{ "be_empty" => literal_expr(Yaml::Boolean(true)) } },
+                    ..Default::default()
+                }
+            )),
             ..Default::default()
         }])]
         #[case("with stderr matcher", "
@@ -780,12 +1394,83 @@ tests:
       expect:
         stderr:
           be_empty: true", vec![TestCaseExprTemplate {
-            processes_matchers: ProcessesMatchersExprTemplate::Single(
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
                 ProcessMatchersExprTemplate {
                     stderr_matcher_exprs: indexmap!{ "be_empty" => literal_expr(Yaml::Boolean(true)) },
                     ..Default::default()
                 }
-            ),
+            )),
+            ..Default::default()
+        }])]
+        #[case("with output matcher", "
+tests:
+    - command:
+        - echo
+        - hello
+      expect:
+        output:
+          contain: hello", vec![TestCaseExprTemplate {
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
+                ProcessMatchersExprTemplate {
+                    output_matcher_exprs: indexmap!{ "contain" => literal_expr(Yaml::String("hello".to_string())) },
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with signal matcher and timed_out", "
+tests:
+    - command:
+        - echo
+        - hello
+      expect:
+        signal:
+          not.eq: SEGV
+        timed_out: true", vec![TestCaseExprTemplate {
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
+                ProcessMatchersExprTemplate {
+                    signal_matcher_exprs: indexmap!{ "not.eq" => literal_expr(Yaml::String("SEGV".to_string())) },
+                    timed_out: true,
+                    ..Default::default()
+                }
+            )),
+            ..Default::default()
+        }])]
+        #[case("with order", "
+tests:
+    - command:
+        - echo
+        - hello
+      expect:
+        order:
+          - - process: main
+              stdout: hello
+            - process: main
+              stderr: world", vec![TestCaseExprTemplate {
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::default()),
+            order: vec![Order {
+                events: vec![
+                    Event { process: "main".to_string(), stream: Stream::Stdout, pattern: Regex::new("hello").unwrap() },
+                    Event { process: "main".to_string(), stream: Stream::Stderr, pattern: Regex::new("world").unwrap() },
+                ],
+            }],
+            ..Default::default()
+        }])]
+        #[case("with usage matcher", "
+tests:
+    - command:
+        - echo
+        - hello
+      expect:
+        usage:
+          wall_time:
+            lt: 2s", vec![TestCaseExprTemplate {
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
+                ProcessMatchersExprTemplate {
+                    usage_matcher_exprs: indexmap!{ "wall_time" => indexmap!{ "lt" => literal_expr(Yaml::String("2s".to_string())) } },
+                    ..Default::default()
+                }
+            )),
             ..Default::default()
         }])]
         #[case("with files matcher", "
@@ -797,11 +1482,11 @@ tests:
         files:
           hello.txt:
             be_empty: true", vec![TestCaseExprTemplate {
-            processes_matchers: ProcessesMatchersExprTemplate::Single(
+            processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(
                 ProcessMatchersExprTemplate {
                     ..Default::default()
                 }
-            ),
+            )),
             files_matchers: indexmap!{ "hello.txt" => indexmap!{ "be_empty" => literal_expr(Yaml::Boolean(true))} },
             ..Default::default()
         }])]
@@ -838,6 +1523,14 @@ tests:
         #[case("when some process is not map", "tests: [{processes: {proc1: true}}]", vec![("$.tests[0].processes.proc1", "should be map, but is bool")])]
         #[case("when some process's command is empty", "tests: [{processes: {proc1: {command: []}}}]", vec![("$.tests[0].processes.proc1.command", "should not be empty")])]
         #[case("when backgroud is not map", "tests: [{processes: {proc1: {command: [true], background: true}}}]", vec![("$.tests[0].processes.proc1.background", "should be map, but is bool")])]
+        #[case("when parallel is not bool", "tests: [{processes: {proc1: {command: [echo]}}, parallel: 42}]", vec![("$.tests[0].parallel", "should be bool, but is uint")])]
+        #[case("when stdin is from following process", "tests: [{processes: {consumer: {command: [cat], stdin: {from: producer}}, producer: {command: [echo]}}}]", vec![("$.tests[0].processes.consumer.stdin.from", "should be name of preceding process, but got \"producer\"")])]
+        #[case("when stdin is from single process", "tests: [{command: [cat], stdin: {from: main}}]", vec![("$.tests[0].stdin.from", "should be name of preceding process, but got \"main\"")])]
+        #[case("when stream is already read by other process", "tests: [{processes: {producer: {command: [echo]}, consumer1: {command: [cat], stdin: {from: producer}}, consumer2: {command: [cat], stdin: {from: producer, stream: stdout}}}}]", vec![("$.tests[0].processes.consumer2.stdin", "should not read producer:stdout which is already read by consumer1")])]
+        #[case("when dialogue is given with stdin from other process", "tests: [{processes: {producer: {command: [echo]}, consumer: {command: [cat], stdin: {from: producer}, dialogue: [{send: hello}]}}}]", vec![("$.tests[0].processes.consumer.dialogue", "should not be given when stdin is connected to other process")])]
+        #[case("when tty is enabled with stdin from other process", "tests: [{processes: {producer: {command: [echo]}, consumer: {command: [cat], stdin: {from: producer}, tty: true}}}]", vec![("$.tests[0].processes.consumer.tty", "should not be enabled when stdin is connected to other process")])]
+        #[case("when stdout connected to other process is expected", "tests: [{processes: {producer: {command: [echo]}, consumer: {command: [cat], stdin: {from: producer}}}, expect: {processes: {producer: {stdout: {eq: hello}, stderr: {eq: ''}}}}}]", vec![("$.tests[0].expect.processes.producer.stdout", "should not be given when it is connected to other process")])]
+        #[case("when stderr connected to other process is expected", "tests: [{processes: {producer: {command: [echo]}, consumer: {command: [cat], stdin: {from: producer, stream: stderr}}}, expect: {processes: {producer: {stdout: {eq: hello}, stderr: {eq: ''}}}}}]", vec![("$.tests[0].expect.processes.producer.stderr", "should not be given when it is connected to other process")])]
         #[case("when test expect is not map", "tests: [{command: [echo], expect: 42}]", vec![("$.tests[0].expect", "should be map, but is uint")])]
         #[case("when test multi expect is not map", "tests: [{command: [echo], expect: {processes: 42}}]", vec![("$.tests[0].expect.processes", "should be map, but is uint")])]
         #[case("when multiple process givenm but expect is single", "tests: [{processes: {process1: {command: [echo]}}, expect: {stdin: {eq: 0}}}]", vec![("$.tests[0].expect", "expect should be multiple mode when multiple processes are given")])]
@@ -845,12 +1538,38 @@ tests:
         #[case("when test env contains not string key", "tests: [{command: [echo], env: {true: hello}}]", vec![("$.tests[0].env", "should be string keyed map, but contains Boolean(true)")])]
         #[case("when test env contains empty name", "tests: [{command: [echo], env: {'': hello}}]", vec![("$.tests[0].env", "should have valid env var name (^[a-zA-Z_][a-zA-Z0-9_]*$)")])]
         #[case("when test env contains empty name", "tests: [{command: [echo], env: {'1MESSAGE': hello}}]", vec![("$.tests[0].env", "should have valid env var name (^[a-zA-Z_][a-zA-Z0-9_]*$)")])]
+        #[case("when tty is not bool or map", "tests: [{command: [echo], tty: 42}]", vec![("$.tests[0].tty", "should be bool or map, but is uint")])]
+        #[case("when tty rows is not uint", "tests: [{command: [echo], tty: {rows: true}}]", vec![("$.tests[0].tty.rows", "should be uint, but is bool")])]
+        #[case("when tty cols is too large", "tests: [{command: [echo], tty: {cols: 65536}}]", vec![("$.tests[0].tty.cols", "should be at most 65535")])]
+        #[case("when stdin has multiple formats", "tests: [{command: [cat], stdin: {base64: AA==, hex: '00'}}]", vec![("$.tests[0].stdin", "should have only one of .file, .base64 and .hex")])]
+        #[case("when stdin chunk is not map", "tests: [{command: [cat], stdin: [42]}]", vec![("$.tests[0].stdin[0]", "should be map, but is uint")])]
+        #[case("when stdin is closed before last chunk", "tests: [{command: [cat], stdin: [{close: true}, {data: hello}]}]", vec![("$.tests[0].stdin[0]", "should be last chunk to close stdin")])]
+        #[case("when dialogue is not seq", "tests: [{command: [echo], dialogue: 42}]", vec![("$.tests[0].dialogue", "should be seq, but is uint")])]
+        #[case("when dialogue step is not map", "tests: [{command: [echo], dialogue: [42]}]", vec![("$.tests[0].dialogue[0]", "should be map, but is uint")])]
+        #[case("when actions is not seq", "tests: [{command: [echo], actions: 42}]", vec![("$.tests[0].actions", "should be seq, but is uint")])]
+        #[case("when action is not map", "tests: [{command: [echo], actions: [42]}]", vec![("$.tests[0].actions[0]", "should be map, but is uint")])]
+        #[case("when action has no trigger", "tests: [{command: [echo], actions: [{signal: HUP}]}]", vec![("$.tests[0].actions[0]", "should have .after or .on_stdout")])]
+        #[case("when limits is not map", "tests: [{command: [echo], limits: 42}]", vec![("$.tests[0].limits", "should be map, but is uint")])]
+        #[case("when limits cpu is not duration", "tests: [{command: [echo], limits: {cpu: true}}]", vec![("$.tests[0].limits.cpu", "should be duration, but is bool")])]
+        #[case("when limits memory is not size", "tests: [{command: [echo], limits: {memory: 1XB}}]", vec![("$.tests[0].limits.memory", "should be size, but is invalid string \"1XB\"")])]
+        #[case("when limits nofile is not uint", "tests: [{command: [echo], limits: {nofile: -1}}]", vec![("$.tests[0].limits.nofile", "should be uint, but is int")])]
+        #[case("when background stop_signal is invalid", "tests: [{command: [echo], background: {stop_signal: UNKNOWN}}]", vec![("$.tests[0].background.stop_signal", "should be signal name, but is invalid string \"UNKNOWN\"")])]
+        #[case("when background stop_timeout is not duration", "tests: [{command: [echo], background: {stop_timeout: true}}]", vec![("$.tests[0].background.stop_timeout", "should be duration, but is bool")])]
+        #[case("when background stop_order is not uint", "tests: [{command: [echo], background: {stop_order: -1}}]", vec![("$.tests[0].background.stop_order", "should be uint, but is int")])]
         #[case("when test status matcher is not map", "tests: [{command: [echo], expect: {status: 42}}]", vec![("$.tests[0].expect.status", "should be map, but is uint")])]
         #[case("when test status matcher contains not string key", "tests: [{command: [echo], expect: {status: {true: 42}}}]", vec![("$.tests[0].expect.status", "should be string keyed map, but contains Boolean(true)")])]
         #[case("when test stdout matcher is not map", "tests: [{command: [echo], expect: {stdout: 42}}]", vec![("$.tests[0].expect.stdout", "should be map, but is uint")])]
         #[case("when test stdout matcher contains not string key", "tests: [{command: [echo], expect: {stdout: {true: 42}}}]", vec![("$.tests[0].expect.stdout", "should be string keyed map, but contains Boolean(true)")])]
         #[case("when test stderr matcher is not map", "tests: [{command: [echo], expect: {stderr: 42}}]", vec![("$.tests[0].expect.stderr", "should be map, but is uint")])]
         #[case("when test stderr matcher contains not string key", "tests: [{command: [echo], expect: {stderr: {true: 42}}}]", vec![("$.tests[0].expect.stderr", "should be string keyed map, but contains Boolean(true)")])]
+        #[case("when test output matcher is not map", "tests: [{command: [echo], expect: {output: 42}}]", vec![("$.tests[0].expect.output", "should be map, but is uint")])]
+        #[case("when test order is not seq", "tests: [{command: [echo], expect: {order: 42}}]", vec![("$.tests[0].expect.order", "should be seq, but is uint")])]
+        #[case("when test order has unknown process", "tests: [{processes: {client: {command: [echo]}}, expect: {processes: {}, order: [[{process: client, stdout: a}, {process: server, stdout: b}]]}}]", vec![("$.tests[0].expect.order[0][1].process", "should be name of process, but got \"server\"")])]
+        #[case("when fds is not map", "tests: [{command: [echo], fds: 42}]", vec![("$.tests[0].fds", "should be map, but is uint")])]
+        #[case("when fds contains stdout", "tests: [{command: [echo], fds: {1: {capture: true}}}]", vec![("$.tests[0].fds", "should be map keyed by fd number (3 or more), but contains Integer(1)")])]
+        #[case("when fd is not map", "tests: [{command: [echo], fds: {3: 42}}]", vec![("$.tests[0].fds.3", "should be map, but is uint")])]
+        #[case("when fd matcher is not captured", "tests: [{command: [echo], fds: {3: {input: hello}}, expect: {fds: {3: {be_empty: true}}}}]", vec![("$.tests[0].expect.fds.3", "should be fd captured by the process")])]
+        #[case("when fd matcher of process is not captured", "tests: [{processes: {main: {command: [echo]}}, expect: {processes: {main: {fds: {4: {be_empty: true}}}}}}]", vec![("$.tests[0].expect.processes.main.fds.4", "should be fd captured by the process")])]
         #[case("when test files matcher is not map", "tests: [{command: [echo], expect: {files: 42}}]", vec![("$.tests[0].expect.files", "should be map, but is uint")])]
         #[case("when test file matcher is not map", "tests: [{command: [echo], expect: {files: {hello: 42}}}]", vec![("$.tests[0].expect.files.hello", "should be map, but is uint")])]
         #[case("when test file matcher contains not string key", "tests: [{command: [echo], expect: {files: {hello: {true: 42}}}}]", vec![("$.tests[0].expect.files.hello", "should be string keyed map, but contains Boolean(true)")])]
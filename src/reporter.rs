@@ -12,13 +12,9 @@ pub enum Color {
     Black,
     Red,
     Green,
-    #[allow(dead_code)]
     Yellow,
-    #[allow(dead_code)]
     Blue,
-    #[allow(dead_code)]
     Magenta,
-    #[allow(dead_code)]
     Cyan,
     #[allow(dead_code)]
     White,
@@ -180,6 +176,10 @@ impl<W: Write> Reporter<W> {
         }
     }
 
+    pub fn color_marker(&self) -> ColorMarker {
+        ColorMarker::new(self.use_color)
+    }
+
     pub fn on_run_start(&mut self) -> Result<(), String> {
         let cm = ColorMarker::new(self.use_color);
         self.formatter.on_run_start(&mut self.w, &cm)
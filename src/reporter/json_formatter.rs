@@ -10,11 +10,22 @@ struct FailureJson<'a> {
     messages: &'a Vec<String>,
 }
 
+// times are in seconds
+#[derive(serde::Serialize)]
+struct UsageJson<'a> {
+    process: &'a String,
+    max_rss: u64,
+    user_time: f64,
+    sys_time: f64,
+    wall_time: f64,
+}
+
 #[derive(serde::Serialize)]
 struct TestResultJson<'a> {
     name: &'a String,
     passed: bool,
     failures: Vec<FailureJson<'a>>,
+    usages: Vec<UsageJson<'a>>,
 }
 
 #[derive(serde::Serialize)]
@@ -81,6 +92,17 @@ impl JsonFormatter {
                             messages: v,
                         })
                         .collect(),
+                    usages: tr
+                        .usages
+                        .iter()
+                        .map(|(k, v)| UsageJson {
+                            process: k,
+                            max_rss: v.max_rss,
+                            user_time: v.user_time.as_secs_f64(),
+                            sys_time: v.sys_time.as_secs_f64(),
+                            wall_time: v.wall_time.as_secs_f64(),
+                        })
+                        .collect(),
                 })
                 .collect(),
         };
@@ -93,11 +115,14 @@ impl JsonFormatter {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use indexmap::indexmap;
     use rstest::rstest;
     use serde_json::json;
 
     use crate::{
+        exec::Usage,
         reporter::{ColorMarker, Formatter},
         test_case::{testutil::TestCaseTemplate, TestResult},
     };
@@ -138,6 +163,7 @@ mod tests {
         let test_result = TestResult {
             name: "test".to_string(),
             failures: indexmap![],
+            usages: indexmap![],
         };
 
         let r = f.on_test_case_end(&mut buf, &ColorMarker::new(false), &test_result);
@@ -155,14 +181,17 @@ mod tests {
                 TestResult {
                     name: "test1".to_string(),
                     failures: indexmap![],
+                    usages: indexmap!["main".to_string() => Usage { max_rss: 2048, user_time: Duration::from_millis(200), sys_time: Duration::from_millis(100), wall_time: Duration::from_millis(1500) }],
                 },
                 TestResult {
                     name: "test2".to_string(),
                     failures: indexmap!["status".to_string() => vec!["status1".to_string()], "stdout".to_string() => vec!["stdout1".to_string(), "stdout2".to_string()]],
+                    usages: indexmap![],
                 },
                 TestResult {
                     name: "test3".to_string(),
                     failures: indexmap!["status".to_string() => vec![]],
+                    usages: indexmap![],
                 },
             ],
         };
@@ -181,7 +210,16 @@ mod tests {
                     {
                         "name": "test1",
                         "passed": true,
-                        "failures": []
+                        "failures": [],
+                        "usages": [
+                            {
+                                "process": "main",
+                                "max_rss": 2048,
+                                "user_time": 0.2,
+                                "sys_time": 0.1,
+                                "wall_time": 1.5
+                            }
+                        ]
                     },
                     {
                         "name": "test2",
@@ -195,12 +233,14 @@ mod tests {
                                 "subject": "stdout",
                                 "messages": ["stdout1", "stdout2"]
                             }
-                        ]
+                        ],
+                        "usages": []
                     },
                     {
                         "name": "test3",
                         "passed": true,
-                        "failures": []
+                        "failures": [],
+                        "usages": []
                     },
                 ]
             }),
@@ -109,7 +109,8 @@ mod tests {
     #[case("with passed",
         TestResult {
             name: "test".to_string(),
-            failures: indexmap! {}
+            failures: indexmap! {},
+            usages: indexmap! {},
         },
         "\x1b[32m.\x1b[0m")]
     #[case("with passed",
@@ -117,7 +118,8 @@ mod tests {
             name: "test".to_string(),
             failures: indexmap! {
                 "assertion".to_string() => vec!["failure message".to_string()]
-            }
+            },
+            usages: indexmap! {},
         },
         "\x1b[31mF\x1b[0m")]
     fn on_test_case_end(
@@ -145,14 +147,17 @@ mod tests {
                 TestResult {
                     name: "test1".to_string(),
                     failures: indexmap![],
+                    usages: indexmap![],
                 },
                 TestResult {
                     name: "test2".to_string(),
                     failures: indexmap!["status".to_string() => vec!["status1".to_string()], "stdout".to_string() => vec!["stdout1".to_string(), "stdout2".to_string()]],
+                    usages: indexmap![],
                 },
                 TestResult {
                     name: "test3".to_string(),
                     failures: indexmap!["status".to_string() => vec![]],
+                    usages: indexmap![],
                 },
             ],
         };
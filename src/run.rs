@@ -40,6 +40,7 @@ pub struct Runner<ReportW: Write, ErrW: Write> {
     errw: ErrW,
     tee_stdout: bool,
     tee_stderr: bool,
+    update_snapshots: bool,
 }
 
 impl<ReportW: Write, ErrW: Write> Runner<ReportW, ErrW> {
@@ -50,6 +51,7 @@ impl<ReportW: Write, ErrW: Write> Runner<ReportW, ErrW> {
         errw: ErrW,
         tee_stdout: bool,
         tee_stderr: bool,
+        update_snapshots: bool,
     ) -> Self {
         Self {
             use_color,
@@ -58,6 +60,7 @@ impl<ReportW: Write, ErrW: Write> Runner<ReportW, ErrW> {
             errw,
             tee_stdout,
             tee_stderr,
+            update_snapshots,
         }
     }
 
@@ -145,7 +148,13 @@ impl<ReportW: Write, ErrW: Write> Runner<ReportW, ErrW> {
         let mut r = Reporter::new(&mut self.rw, self.use_color, self.formatter);
 
         drop(tmp_port_reserver);
-        let result = run_tests(test_case_files, &mut r, self.tee_stdout, self.tee_stderr);
+        let result = run_tests(
+            test_case_files,
+            &mut r,
+            self.tee_stdout,
+            self.tee_stderr,
+            self.update_snapshots,
+        );
 
         let test_result_summary = match result {
             Ok(test_result_summary) => test_result_summary,
@@ -184,12 +193,20 @@ mod tests {
     use serde_json::json;
     use tempfile::NamedTempFile;
 
+    // usages of the first test result are taken out, so that the rest is compared exactly
+    fn take_usages(report: &mut serde_json::Value) -> Option<serde_json::Value> {
+        report["test_results"][0]
+            .as_object_mut()
+            .unwrap()
+            .remove("usages")
+    }
+
     #[rstest]
     fn when_all_case_passed() {
         let formatter = Formatter::new_json();
         let mut rw: Vec<u8> = vec![];
         let mut errw: Vec<u8> = vec![];
-        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false);
+        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false, false);
 
         let mut file = NamedTempFile::new().unwrap();
         let spec = r#"{ tests: [{ command: ["true"], expect: { status: { eq: 0 } } }]}"#;
@@ -198,6 +215,8 @@ mod tests {
         let result = runner.run(vec![Input::File(file.path().to_str().unwrap().to_string())]);
 
         assert_eq!("", String::from_utf8_lossy(&errw));
+        let mut actual = serde_json::from_slice::<serde_json::Value>(rw.as_slice()).unwrap();
+        assert!(take_usages(&mut actual).is_some());
         assert_eq!(
             json!({
                 "num_test_cases": 1,
@@ -212,7 +231,7 @@ mod tests {
                     },
                 ]
             }),
-            serde_json::from_slice::<serde_json::Value>(rw.as_slice()).unwrap(),
+            actual,
         );
         assert_eq!(Ok(()), result);
     }
@@ -222,7 +241,7 @@ mod tests {
         let formatter = Formatter::new_json();
         let mut rw: Vec<u8> = vec![];
         let mut errw: Vec<u8> = vec![];
-        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false);
+        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false, false);
 
         let mut file = NamedTempFile::new().unwrap();
         let spec = r#"{ tests: [{ command: ["true"], expect: { status: { eq: 1 } } }]}"#;
@@ -231,6 +250,8 @@ mod tests {
         let result = runner.run(vec![Input::File(file.path().to_str().unwrap().to_string())]);
 
         assert_eq!("", String::from_utf8_lossy(&errw));
+        let mut actual = serde_json::from_slice::<serde_json::Value>(rw.as_slice()).unwrap();
+        assert!(take_usages(&mut actual).is_some());
         assert_eq!(
             json!({
                 "num_test_cases": 1,
@@ -250,7 +271,7 @@ mod tests {
                     },
                 ]
             }),
-            serde_json::from_slice::<serde_json::Value>(rw.as_slice()).unwrap(),
+            actual,
         );
         assert_eq!(Err(TexestError::TestFailed), result);
     }
@@ -260,7 +281,7 @@ mod tests {
         let formatter = Formatter::new_json();
         let mut rw: Vec<u8> = vec![];
         let mut errw: Vec<u8> = vec![];
-        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false);
+        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false, false);
 
         let result = runner.run(vec![Input::File("not_exist.yaml".to_string())]);
 
@@ -276,7 +297,7 @@ mod tests {
         let formatter = Formatter::new_json();
         let mut rw: Vec<u8> = vec![];
         let mut errw: Vec<u8> = vec![];
-        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false);
+        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false, false);
 
         let mut file = NamedTempFile::new().unwrap();
         let spec = r#"{ tests: [{ expect: { status: { eq: 0 } } }]}"#;
@@ -300,7 +321,7 @@ mod tests {
         let formatter = Formatter::new_json();
         let mut rw: Vec<u8> = vec![];
         let mut errw: Vec<u8> = vec![];
-        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false);
+        let runner = Runner::new(true, formatter, &mut rw, &mut errw, false, false, false);
 
         let mut file = NamedTempFile::new().unwrap();
         let spec = r#"{ tests: [{ command: [{ $env: "UNDEFINED_ENV" }],  expect: { status: { eq: 0 } } }]}"#;
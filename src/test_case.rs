@@ -1,32 +1,89 @@
+pub mod action;
+pub mod dialogue;
+pub mod extra_fd;
+pub mod order;
+pub mod pipe;
 pub mod setup_hook;
+pub mod stdin;
 pub mod teardown_hook;
 pub mod wait_condition;
 
 use std::{
-    ffi::OsString,
     fmt::Debug,
-    io::{stdout, Write},
+    future::Future,
     ops::ControlFlow,
-    os::unix::ffi::OsStrExt,
-    time::Duration,
+    os::{fd::RawFd, unix::ffi::OsStrExt},
+    path::PathBuf,
+    time::{Duration, Instant},
 };
 
-use futures::future::join_all;
+use action::Action;
+use dialogue::DialogueStep;
+use duration_str::HumanFormat;
+use extra_fd::ExtraFd;
+use futures::future::{join, join_all};
 use indexmap::{indexmap, IndexMap};
+use nix::sys::signal::Signal;
+use order::{Order, Stream};
+use pipe::PipeSource;
 use setup_hook::SetupHook;
+use stdin::StdinChunk;
 use teardown_hook::TeardownHook;
 
 use crate::{
-    exec::{execute_background_command, execute_command, BackgroundExec, Output, Status},
-    matcher::{StatusMatcher, StreamMatcher},
+    exec::{
+        capture::Tee, execute_background_command, execute_command, BackgroundExec, Output, Pipes,
+        Status, StopStage, Usage,
+    },
+    matcher::{SignalMatcher, StatusMatcher, StreamMatcher, UsageMatcher},
+    reporter::{Color, ColorMarker},
 };
 
 pub use self::wait_condition::WaitCondition;
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct BackgroundConfig {
     pub wait_condition: WaitCondition,
+    pub stop_signal: Signal,
+    // when not given, timeout of the process is used
+    pub stop_timeout: Option<Duration>,
+    pub stop_order: Option<u64>,
+}
+
+impl Default for BackgroundConfig {
+    fn default() -> Self {
+        Self {
+            wait_condition: WaitCondition::default(),
+            stop_signal: Signal::SIGTERM,
+            stop_timeout: None,
+            stop_order: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct WindowSize {
+    pub rows: u16,
+    pub cols: u16,
+}
+
+impl Default for WindowSize {
+    fn default() -> Self {
+        Self { rows: 24, cols: 80 }
+    }
+}
+
+// resource limits applied to the process (memory, fsize and core are in bytes)
+#[derive(Clone, Default)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Limits {
+    pub cpu: Option<Duration>,
+    pub memory: Option<u64>,
+    pub nofile: Option<u64>,
+    pub fsize: Option<u64>,
+    pub core: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -40,15 +97,37 @@ pub enum ProcessMode {
 pub struct Process {
     pub command: String,
     pub args: Vec<String>,
-    pub stdin: String,
+    pub stdin: Vec<u8>,
+    // when given, the file is streamed to stdin after `stdin`
+    pub stdin_file: Option<String>,
+    // when given, stdin is fed in chunks after `stdin` and kept open in background until closed by a chunk
+    pub stdin_chunks: Option<Vec<StdinChunk>>,
+    // when given, stdin is connected to output of the process instead
+    pub stdin_from: Option<PipeSource>,
+    // fds other than stdin, stdout and stderr, connected to pipes
+    pub fds: IndexMap<RawFd, ExtraFd>,
     pub env: Vec<(String, String)>,
+    pub dir: Option<String>,
+    pub tty: Option<WindowSize>,
+    pub dialogue: Vec<DialogueStep>,
+    pub limits: Limits,
+    pub actions: Vec<Action>,
     pub timeout: Duration,
     pub mode: ProcessMode,
     pub tee_stdout: bool,
     pub tee_stderr: bool,
+    pub check_leftover: bool,
     pub status_matchers: Vec<(StatusMatcher, bool)>,
+    pub signal_matchers: Vec<(SignalMatcher, bool)>,
     pub stdout_matchers: Vec<(StreamMatcher, bool)>,
     pub stderr_matchers: Vec<(StreamMatcher, bool)>,
+    // for captured fds
+    pub fd_matchers: IndexMap<RawFd, Vec<(StreamMatcher, bool)>>,
+    // for stdout and stderr merged in the order they were written
+    pub output_matchers: Vec<(StreamMatcher, bool)>,
+    pub usage_matchers: Vec<(UsageMatcher, bool)>,
+    // when true, timeout is expected instead of failure
+    pub timed_out: bool,
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -59,7 +138,11 @@ pub struct TestCase {
     #[allow(dead_code)]
     pub path: String,
     pub processes: IndexMap<String, Process>,
+    // when true, foreground processes are run concurrently
+    pub parallel: bool,
     pub files_matchers: IndexMap<String, Vec<(StreamMatcher, bool)>>,
+    pub order: Vec<Order>,
+    pub work_dir: Option<PathBuf>,
     pub setup_hooks: Vec<SetupHook>,
     pub teardown_hooks: Vec<TeardownHook>,
 }
@@ -75,6 +158,8 @@ pub struct TestCaseFile {
 pub struct TestResult {
     pub name: String,
     pub failures: IndexMap<String, Vec<String>>,
+    // resource usage of each process which is exited
+    pub usages: IndexMap<String, Usage>,
 }
 
 impl TestResult {
@@ -116,16 +201,34 @@ impl TestResultSummary {
 }
 
 impl TestCase {
-    pub fn run(&self, tee_stdout: bool, tee_stderr: bool) -> TestResult {
+    pub fn run(
+        &self,
+        tee_stdout: bool,
+        tee_stderr: bool,
+        update_snapshots: bool,
+        cm: &ColorMarker,
+    ) -> TestResult {
         let rt = if let Ok(rt) = tokio::runtime::Runtime::new() {
             rt
         } else {
             return TestResult {
                 name: self.name.clone(),
                 failures: indexmap! { "setup".to_string() => vec!["failed to create runtime".to_string()] },
+                usages: indexmap! {},
             };
         };
 
+        let mut pipes = match self.pipes() {
+            Ok(pipes) => pipes,
+            Err(err) => {
+                return TestResult {
+                    name: self.name.clone(),
+                    failures: indexmap! { "setup".to_string() => vec![err] },
+                    usages: indexmap! {},
+                };
+            }
+        };
+
         let mut setup_failures = vec![];
         let _ = self.setup_hooks.iter().try_for_each(|hook| {
             let r = hook.setup();
@@ -141,118 +244,250 @@ impl TestCase {
             return TestResult {
                 name: self.name.clone(),
                 failures: indexmap! { "setup".to_string() => setup_failures },
+                usages: indexmap! {},
             };
         }
 
         enum Execution {
             Foreground(Result<Output, String>),
-            Background(Result<BackgroundExec, String>),
+            Background(Result<BackgroundExec, String>, Option<u64>),
+            // foreground process which is not executed yet
+            Pending,
+        }
+
+        // starts pending processes together and waits for all of them
+        async fn run_pendings<F: Future<Output = Result<Output, String>>>(
+            executions: &mut [Execution],
+            pendings: &mut Vec<(usize, F)>,
+        ) {
+            let (indices, pendings): (Vec<_>, Vec<_>) = pendings.drain(..).unzip();
+            for (i, result) in indices.into_iter().zip(join_all(pendings).await) {
+                executions[i] = Execution::Foreground(result);
+            }
         }
 
+        // tee of all processes measures elapsed time from the same instant
+        let started = Instant::now();
         let exec_results = rt.block_on(async {
-            let mut executions: Vec<(&String, Execution)> = vec![];
+            let mut executions: Vec<Execution> = vec![];
+            let mut pendings = vec![];
+            // processes which read pipes, but are not started yet
+            let mut waiting_readers: Vec<&str> = vec![];
+
+            for (i, (process_name, process)) in self.processes.iter().enumerate() {
+                let tee = tee_of(
+                    process_name,
+                    i,
+                    (
+                        tee_stdout || process.tee_stdout,
+                        tee_stderr || process.tee_stderr,
+                    ),
+                    cm,
+                    started,
+                );
+                let process_pipes = pipes
+                    .shift_remove(process_name.as_str())
+                    .unwrap_or_default();
+                waiting_readers.retain(|name| *name != process_name.as_str());
 
-            for (process_name, process) in self.processes.iter() {
                 let execution = match &process.mode {
                     ProcessMode::Foreground => {
-                        let exec_result = execute_command(
-                            &process.command,
-                            &process.args,
-                            process.stdin.clone(),
-                            process.env.clone(),
-                            process.timeout,
-                        )
-                        .await;
-
-                        if let Ok(output) = &exec_result {
-                            tee_stream_of_output(
-                                process_name,
-                                output,
-                                tee_stdout || process.tee_stdout,
-                                tee_stderr || process.tee_stderr,
-                            );
-                        }
-
-                        Execution::Foreground(exec_result)
+                        pendings.push((
+                            executions.len(),
+                            execute_command(process, tee, process_pipes),
+                        ));
+                        Execution::Pending
                     }
                     ProcessMode::Background(cfg) => {
-                        let background_exec = execute_background_command(
-                            &process.command,
-                            &process.args,
-                            process.stdin.clone(),
-                            process.env.clone(),
-                            process.timeout,
-                            &cfg.wait_condition,
-                            (
-                                tee_stdout || process.tee_stdout,
-                                tee_stderr || process.tee_stderr,
-                            ),
-                        )
-                        .await;
-
-                        Execution::Background(background_exec)
+                        let start = execute_background_command(process, cfg, tee, process_pipes);
+                        // pending writer is started together, since the reader may wait for its input
+                        let background_exec = if self.reads_pending(process, &pendings) {
+                            join(start, run_pendings(&mut executions, &mut pendings))
+                                .await
+                                .0
+                        } else {
+                            start.await
+                        };
+
+                        Execution::Background(background_exec, cfg.stop_order)
                     }
                 };
 
-                executions.push((process_name, execution));
+                executions.push(execution);
+                waiting_readers.extend(self.readers_of(process_name));
+
+                // foreground processes are run one at a time unless parallel,
+                // but processes connected by pipes are run together
+                if !self.parallel && waiting_readers.is_empty() {
+                    run_pendings(&mut executions, &mut pendings).await;
+                }
             }
 
-            async fn collect_exec_result(
-                process_name: &str,
-                execution: Execution,
-            ) -> Result<Output, String> {
-                match execution {
-                    Execution::Foreground(result) => result,
-                    Execution::Background(Ok(bg)) => {
-                        let tee_stdout = bg.tee_stdout;
-                        let tee_stderr = bg.tee_stderr;
-                        let result = bg.terminate().await;
-
-                        if let Ok(output) = &result {
-                            tee_stream_of_output(process_name, output, tee_stdout, tee_stderr);
-                        }
+            // in parallel mode, pending processes are started together
+            // after all background processes are ready
+            run_pendings(&mut executions, &mut pendings).await;
 
-                        result
+            let mut results: Vec<Option<Result<Output, String>>> = vec![];
+            let mut backgrounds = vec![];
+            for execution in executions {
+                match execution {
+                    Execution::Foreground(result) => results.push(Some(result)),
+                    Execution::Background(Ok(bg), stop_order) => {
+                        backgrounds.push((results.len(), bg, stop_order));
+                        results.push(None);
                     }
-                    Execution::Background(Err(err)) => Err(err),
+                    Execution::Background(Err(err), _) => results.push(Some(Err(err))),
+                    Execution::Pending => unreachable!("pending processes should be executed"),
                 }
             }
 
-            join_all(
-                executions
-                    .into_iter()
-                    .map(|(process_name, e)| collect_exec_result(process_name, e)),
-            )
-            .await
+            // processes with stop_order are stopped first in ascending order,
+            // and the rest are stopped in reverse start order
+            backgrounds.reverse();
+            backgrounds.sort_by_key(|(_, _, stop_order)| match stop_order {
+                Some(n) => (0, *n),
+                None => (1, 0),
+            });
+
+            for (i, bg, _) in backgrounds {
+                results[i] = Some(bg.terminate().await);
+            }
+
+            results
+                .into_iter()
+                .map(|result| result.expect("all processes should be stopped"))
+                .collect::<Vec<_>>()
         });
 
         let mut failures = indexmap! {};
+        let mut usages = indexmap! {};
+        let mut outputs = indexmap! {};
         self.processes.iter().zip(exec_results).for_each(
             |((process_name, process), exec_result)| match exec_result {
                 Ok(output) => {
                     let status_messages = match output.status {
+                        Status::Exit(code) if process.timed_out => {
+                            vec![format!("should be timed out, but exited with {}", code)]
+                        }
                         Status::Exit(code) => run_status_matchers(&process.status_matchers, code),
-                        Status::Signal(signal) => vec![format!("signaled with {}", signal)],
-                        Status::Timeout => {
-                            vec![format!("timed out ({} sec)", process.timeout.as_secs())]
+                        Status::Signal(signal, core_dumped) if process.timed_out => {
+                            vec![format!(
+                                "should be timed out, but {}",
+                                describe_signal(signal, core_dumped)
+                            )]
+                        }
+                        // signaled is expected when positive signal matchers are given,
+                        // since negated ones are also satisfied by other signals
+                        Status::Signal(_, _)
+                            if process
+                                .signal_matchers
+                                .iter()
+                                .any(|(_, expected_passed)| *expected_passed) =>
+                        {
+                            vec![]
+                        }
+                        Status::Signal(signal, core_dumped) => match output.stop_stage {
+                            Some(StopStage::Signal(stop_signal))
+                                if stop_signal as i32 == signal =>
+                            {
+                                vec![format!(
+                                    "{} (stop signal {})",
+                                    describe_signal(signal, core_dumped),
+                                    stop_signal.as_str()
+                                )]
+                            }
+                            _ => vec![describe_signal(signal, core_dumped)],
+                        },
+                        Status::Timeout if process.timed_out => vec![],
+                        Status::Timeout => match (&process.mode, output.stop_stage) {
+                            (ProcessMode::Background(cfg), Some(StopStage::Kill)) => {
+                                vec![format!(
+                                    "not stopped by {} in {}, killed by SIGKILL",
+                                    cfg.stop_signal.as_str(),
+                                    cfg.stop_timeout.unwrap_or(process.timeout).human_format()
+                                )]
+                            }
+                            _ => vec![format!("timed out ({} sec)", process.timeout.as_secs())],
+                        },
+                    };
+
+                    let signal_messages = match output.status {
+                        Status::Exit(_) => run_signal_matchers(&process.signal_matchers, None),
+                        Status::Signal(signal, _) => {
+                            run_signal_matchers(&process.signal_matchers, Some(signal))
                         }
+                        Status::Timeout => vec![],
                     };
 
                     let stdout = output.stdout.as_bytes().to_vec();
-                    let stdout_messages = run_stream_matchers(&process.stdout_matchers, &stdout);
+                    let stdout_messages =
+                        run_stream_matchers(&process.stdout_matchers, &stdout, update_snapshots);
 
                     let stderr = output.stderr.as_bytes().to_vec();
-                    let stderr_messages = run_stream_matchers(&process.stderr_matchers, &stderr);
+                    let stderr_messages =
+                        run_stream_matchers(&process.stderr_matchers, &stderr, update_snapshots);
+
+                    let fd_messages: Vec<(RawFd, Vec<String>)> = process
+                        .fd_matchers
+                        .iter()
+                        .map(|(fd, matchers)| {
+                            let captured = output
+                                .fds
+                                .get(fd)
+                                .map(|captured| captured.as_bytes().to_vec())
+                                .unwrap_or_default();
+                            (
+                                *fd,
+                                run_stream_matchers(matchers, &captured, update_snapshots),
+                            )
+                        })
+                        .collect();
+
+                    let merged = output.output.as_bytes().to_vec();
+                    let output_messages =
+                        run_stream_matchers(&process.output_matchers, &merged, update_snapshots);
+
+                    let usage_messages = run_usage_matchers(&process.usage_matchers, &output.usage);
 
                     if !status_messages.is_empty() {
                         failures.insert(subject_of(process_name, "status"), status_messages);
                     }
+                    if !signal_messages.is_empty() {
+                        failures.insert(subject_of(process_name, "signal"), signal_messages);
+                    }
                     if !stdout_messages.is_empty() {
                         failures.insert(subject_of(process_name, "stdout"), stdout_messages);
                     }
                     if !stderr_messages.is_empty() {
                         failures.insert(subject_of(process_name, "stderr"), stderr_messages);
                     }
+                    for (fd, messages) in fd_messages {
+                        if !messages.is_empty() {
+                            failures
+                                .insert(subject_of(process_name, format!("fd{}", fd)), messages);
+                        }
+                    }
+                    if !output_messages.is_empty() {
+                        failures.insert(subject_of(process_name, "output"), output_messages);
+                    }
+                    if !usage_messages.is_empty() {
+                        failures.insert(subject_of(process_name, "usage"), usage_messages);
+                    }
+                    let action_messages = (output.performed_actions..process.actions.len())
+                        .map(|i| format!("actions[{}] was not performed before exit", i))
+                        .collect::<Vec<_>>();
+                    if !action_messages.is_empty() {
+                        failures.insert(subject_of(process_name, "actions"), action_messages);
+                    }
+                    if output.leftover {
+                        failures.insert(
+                            subject_of(process_name, "leftover"),
+                            vec!["descendant processes are still alive after exit".to_string()],
+                        );
+                    }
+
+                    usages.insert(process_name.clone(), output.usage);
+                    outputs.insert(process_name.as_str(), output);
                 }
                 Err(err) => {
                     failures.insert(subject_of(process_name, "exec"), vec![err]);
@@ -260,19 +495,36 @@ impl TestCase {
             },
         );
 
+        self.order.iter().enumerate().for_each(|(i, order)| {
+            let messages = order.check(|event| {
+                outputs
+                    .get(event.process.as_str())
+                    .and_then(|output| event.time_in(output))
+            });
+            if !messages.is_empty() {
+                failures.insert(subject_of("order", i.to_string()), messages);
+            }
+        });
+
         self.files_matchers.iter().for_each(|(path, matchers)| {
             let subject = subject_of("file", path);
+            let path = self
+                .work_dir
+                .as_ref()
+                .map(|work_dir| work_dir.join(path))
+                .unwrap_or_else(|| PathBuf::from(path));
 
-            match std::fs::metadata(path) {
+            match std::fs::metadata(&path) {
                 Ok(metadata) => {
                     if !metadata.is_file() {
                         failures.insert(subject, vec!["is not file".to_string()]);
                         return;
                     }
 
-                    match std::fs::read(path) {
+                    match std::fs::read(&path) {
                         Ok(content) => {
-                            let messages = run_stream_matchers(matchers, &content);
+                            let messages =
+                                run_stream_matchers(matchers, &content, update_snapshots);
                             if !messages.is_empty() {
                                 failures.insert(subject, messages);
                             }
@@ -302,7 +554,49 @@ impl TestCase {
         TestResult {
             name: self.name.clone(),
             failures,
+            usages,
+        }
+    }
+
+    // creates pipes between processes. each process takes the ends it uses.
+    fn pipes(&self) -> Result<IndexMap<&str, Pipes>, String> {
+        let mut pipes: IndexMap<&str, Pipes> = indexmap! {};
+        for (process_name, process) in &self.processes {
+            if let Some(from) = &process.stdin_from {
+                let (reader, writer) =
+                    std::io::pipe().map_err(|err| format!("cannot create pipe: {}", err))?;
+                pipes.entry(process_name).or_default().stdin = Some(reader.into());
+
+                let source = pipes.entry(&from.process).or_default();
+                match from.stream {
+                    Stream::Stdout => source.stdout = Some(writer.into()),
+                    Stream::Stderr => source.stderr = Some(writer.into()),
+                }
+            }
         }
+        Ok(pipes)
+    }
+
+    // names of processes reading output of the process
+    fn readers_of<'a>(&'a self, process_name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.processes
+            .iter()
+            .filter(move |(_, process)| {
+                process
+                    .stdin_from
+                    .as_ref()
+                    .is_some_and(|from| from.process == process_name)
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    // whether the process reads output of pending process, indexed by the order of processes
+    fn reads_pending<F>(&self, process: &Process, pendings: &[(usize, F)]) -> bool {
+        process
+            .stdin_from
+            .as_ref()
+            .and_then(|from| self.processes.get_index_of(&from.process))
+            .is_some_and(|source| pendings.iter().any(|(i, _)| *i == source))
     }
 }
 
@@ -310,6 +604,14 @@ fn subject_of<S: AsRef<str>, T: AsRef<str>>(process_name: S, subject: T) -> Stri
     format!("{}:{}", process_name.as_ref(), subject.as_ref())
 }
 
+fn describe_signal(signal: i32, core_dumped: bool) -> String {
+    if core_dumped {
+        format!("signaled with {} (core dumped)", signal)
+    } else {
+        format!("signaled with {}", signal)
+    }
+}
+
 fn run_status_matchers(matchers: &[(StatusMatcher, bool)], status: i32) -> Vec<String> {
     matchers
         .iter()
@@ -328,10 +630,40 @@ fn run_status_matchers(matchers: &[(StatusMatcher, bool)], status: i32) -> Vec<S
         .collect()
 }
 
-fn run_stream_matchers(matchers: &[(StreamMatcher, bool)], stream: &[u8]) -> Vec<String> {
+fn run_signal_matchers(matchers: &[(SignalMatcher, bool)], signal: Option<i32>) -> Vec<String> {
     matchers
         .iter()
         .filter_map(|(matcher, expected_passed)| {
+            matcher
+                .matches(signal)
+                .map(|(passed, message)| {
+                    if passed == *expected_passed {
+                        None
+                    } else {
+                        Some(message)
+                    }
+                })
+                .unwrap_or_else(Some)
+        })
+        .collect()
+}
+
+// golden files are rewritten before matching when updating snapshots,
+// except for negated matchers, which cannot be satisfied by the stream itself
+fn run_stream_matchers(
+    matchers: &[(StreamMatcher, bool)],
+    stream: &[u8],
+    update_snapshots: bool,
+) -> Vec<String> {
+    matchers
+        .iter()
+        .filter_map(|(matcher, expected_passed)| {
+            if update_snapshots && *expected_passed {
+                if let Err(err) = matcher.update_snapshot(stream) {
+                    return Some(err);
+                }
+            }
+
             matcher
                 .matches(stream)
                 .map(|(passed, message)| {
@@ -346,35 +678,64 @@ fn run_stream_matchers(matchers: &[(StreamMatcher, bool)], stream: &[u8]) -> Vec
         .collect()
 }
 
-fn tee_stream_of_output(process_name: &str, output: &Output, tee_stdout: bool, tee_stderr: bool) {
-    if tee_stdout || tee_stderr {
-        println!("== {} ==", process_name);
-    }
-    tee_stream("stdout", &output.stdout, tee_stdout);
-    tee_stream("stderr", &output.stderr, tee_stderr);
+fn run_usage_matchers(matchers: &[(UsageMatcher, bool)], usage: &Usage) -> Vec<String> {
+    matchers
+        .iter()
+        .filter_map(|(matcher, expected_passed)| {
+            matcher
+                .matches(usage)
+                .map(|(passed, message)| {
+                    if passed == *expected_passed {
+                        None
+                    } else {
+                        Some(message)
+                    }
+                })
+                .unwrap_or_else(Some)
+        })
+        .collect()
 }
 
-fn tee_stream(stream_name: &str, stream: &OsString, tee: bool) {
-    if tee {
-        let bytes = stream.as_bytes();
-        println!("=== captured {} ===", stream_name);
-        stdout().write_all(bytes).unwrap();
-        if !bytes.is_empty() {
-            println!();
-        }
-        println!("=======================");
+// processes are distinguished by colors in tee output
+fn tee_color(i: usize) -> Color {
+    match i % 5 {
+        0 => Color::Cyan,
+        1 => Color::Yellow,
+        2 => Color::Magenta,
+        3 => Color::Blue,
+        _ => Color::Green,
     }
 }
 
+fn tee_of(
+    process_name: &str,
+    i: usize,
+    tee: (bool, bool),
+    cm: &ColorMarker,
+    started: Instant,
+) -> (Option<Tee>, Option<Tee>) {
+    let tee_of_stream = |stream_name: &str| Tee {
+        label: cm.wrap(tee_color(i), format!("{}:{}", process_name, stream_name)),
+        started,
+    };
+
+    (
+        tee.0.then(|| tee_of_stream("stdout")),
+        tee.1.then(|| tee_of_stream("stderr")),
+    )
+}
+
 #[cfg(test)]
 pub mod testutil {
     use indexmap::{indexmap, IndexMap};
 
-    use crate::matcher::{StatusMatcher, StreamMatcher};
-    use std::{cell::RefCell, rc::Rc, time::Duration};
+    use crate::matcher::{SignalMatcher, StatusMatcher, StreamMatcher, UsageMatcher};
+    use std::{cell::RefCell, os::fd::RawFd, path::PathBuf, rc::Rc, time::Duration};
 
     use super::{
-        setup_hook::SetupHook, teardown_hook::TeardownHook, Process, ProcessMode, TestCase,
+        action::Action, dialogue::DialogueStep, extra_fd::ExtraFd, order::Order, pipe::PipeSource,
+        setup_hook::SetupHook, stdin::StdinChunk, teardown_hook::TeardownHook, Limits, Process,
+        ProcessMode, TestCase, TestResult, WindowSize,
     };
 
     pub const DEFAULT_NAME: &str = "test";
@@ -382,6 +743,14 @@ pub mod testutil {
     pub const DEFAULT_PATH: &str = "$.tests[0]";
     pub const DEFAULT_TIMEOUT: u64 = 10;
 
+    // usages vary on each run, so they are cleared before comparison
+    pub fn without_usages(result: TestResult) -> TestResult {
+        TestResult {
+            usages: indexmap! {},
+            ..result
+        }
+    }
+
     #[cfg_attr(test, derive(Debug, PartialEq))]
     pub enum HookType {
         Setup,
@@ -427,14 +796,28 @@ pub mod testutil {
         pub command: &'static str,
         pub args: Vec<&'static str>,
         pub stdin: &'static str,
+        pub stdin_file: Option<&'static str>,
+        pub stdin_chunks: Option<Vec<StdinChunk>>,
+        pub stdin_from: Option<PipeSource>,
+        pub fds: IndexMap<RawFd, ExtraFd>,
         pub env: Vec<(&'static str, &'static str)>,
+        pub tty: Option<WindowSize>,
+        pub dialogue: Vec<DialogueStep>,
+        pub limits: Limits,
+        pub actions: Vec<Action>,
         pub timeout: u64,
         pub mode: ProcessMode,
         pub tee_stdout: bool,
         pub tee_stderr: bool,
+        pub check_leftover: bool,
         pub status_matchers: Vec<(StatusMatcher, bool)>,
+        pub signal_matchers: Vec<(SignalMatcher, bool)>,
         pub stdout_matchers: Vec<(StreamMatcher, bool)>,
         pub stderr_matchers: Vec<(StreamMatcher, bool)>,
+        pub fd_matchers: IndexMap<RawFd, Vec<(StreamMatcher, bool)>>,
+        pub output_matchers: Vec<(StreamMatcher, bool)>,
+        pub usage_matchers: Vec<(UsageMatcher, bool)>,
+        pub timed_out: bool,
     }
 
     impl Default for ProcessTemplate {
@@ -443,14 +826,28 @@ pub mod testutil {
                 command: "echo",
                 args: vec!["hello"],
                 stdin: "",
+                stdin_file: None,
+                stdin_chunks: None,
+                stdin_from: None,
+                fds: indexmap! {},
                 env: vec![],
+                tty: None,
+                dialogue: vec![],
+                limits: Limits::default(),
+                actions: vec![],
                 timeout: DEFAULT_TIMEOUT,
                 tee_stdout: false,
                 tee_stderr: false,
+                check_leftover: false,
                 mode: ProcessMode::Foreground,
                 status_matchers: vec![],
+                signal_matchers: vec![],
                 stdout_matchers: vec![],
                 stderr_matchers: vec![],
+                fd_matchers: indexmap! {},
+                output_matchers: vec![],
+                usage_matchers: vec![],
+                timed_out: false,
             }
         }
     }
@@ -460,19 +857,34 @@ pub mod testutil {
             Process {
                 command: self.command.to_string(),
                 args: self.args.iter().map(|x| x.to_string()).collect(),
-                stdin: self.stdin.to_string(),
+                stdin: self.stdin.as_bytes().to_vec(),
+                stdin_file: self.stdin_file.map(String::from),
+                stdin_chunks: self.stdin_chunks,
+                stdin_from: self.stdin_from,
+                fds: self.fds,
                 env: self
                     .env
                     .iter()
                     .map(|(k, v)| (k.to_string(), v.to_string()))
                     .collect(),
+                dir: None,
+                tty: self.tty,
+                dialogue: self.dialogue,
+                limits: self.limits,
+                actions: self.actions,
                 timeout: Duration::from_secs(self.timeout),
                 mode: self.mode,
                 tee_stdout: self.tee_stdout,
                 tee_stderr: self.tee_stderr,
+                check_leftover: self.check_leftover,
                 status_matchers: self.status_matchers,
+                signal_matchers: self.signal_matchers,
                 stdout_matchers: self.stdout_matchers,
                 stderr_matchers: self.stderr_matchers,
+                fd_matchers: self.fd_matchers,
+                output_matchers: self.output_matchers,
+                usage_matchers: self.usage_matchers,
+                timed_out: self.timed_out,
             }
         }
     }
@@ -483,7 +895,10 @@ pub mod testutil {
         pub filename: &'static str,
         pub path: &'static str,
         pub processes: IndexMap<&'static str, ProcessTemplate>,
+        pub parallel: bool,
         pub files_matchers: FilesMatchers,
+        pub order: Vec<Order>,
+        pub work_dir: Option<PathBuf>,
         pub setup_hooks: Vec<SetupHook>,
         pub teardown_hooks: Vec<TeardownHook>,
     }
@@ -504,6 +919,9 @@ pub mod testutil {
                     .into_iter()
                     .map(|(k, v)| (k.to_string(), v))
                     .collect(),
+                order: self.order,
+                parallel: self.parallel,
+                work_dir: self.work_dir,
                 setup_hooks: self.setup_hooks,
                 teardown_hooks: self.teardown_hooks,
             }
@@ -518,6 +936,9 @@ pub mod testutil {
                 path: DEFAULT_PATH,
                 processes: indexmap! { "main" => ProcessTemplate::default() },
                 files_matchers: indexmap! {},
+                order: vec![],
+                parallel: false,
+                work_dir: None,
                 setup_hooks: vec![],
                 teardown_hooks: vec![],
             }
@@ -550,9 +971,9 @@ mod tests {
             };
             use crate::test_case::testutil::HookType::{Setup, Teardown};
             use crate::test_case::testutil::{
-                HookHistory, ProcessTemplate, TestCaseTemplate, DEFAULT_NAME,
+                without_usages, HookHistory, ProcessTemplate, TestCaseTemplate, DEFAULT_NAME,
             };
-            use crate::test_case::wait_condition::SleepCondition;
+            use crate::test_case::wait_condition::{SleepCondition, StdoutCondition};
 
             use self::tests::testutil::{DEFAULT_FILENAME, DEFAULT_PATH};
 
@@ -566,55 +987,88 @@ mod tests {
             #[rstest]
             #[case("command is exit, no matchers",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
             #[case("command is exit, status matchers are succeeded",
                 TestCaseTemplate{ processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], status_matchers: vec![(new_status_test_success(Yaml::Boolean(true)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
             #[case("command is exit, status matchers are failed",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], status_matchers: vec![(new_status_test_failure(Yaml::Integer(1)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec![TestMatcher::failure_message(0)]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec![TestMatcher::failure_message(0)]}, usages: indexmap! {} })]
             #[case("command is exit, status matchers are succeeded (not required passing)",
                 TestCaseTemplate{ processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], status_matchers: vec![(new_status_test_success(Yaml::Boolean(true)),  false)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec![TestMatcher::success_message(0)]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec![TestMatcher::success_message(0)]}, usages: indexmap! {} })]
             #[case("command is exit, status matchers are failed (not required passing)",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], status_matchers: vec![(new_status_test_failure(Yaml::Integer(1)), false)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
             #[case("command is exit, stdout matchers are succeeded",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], stdout_matchers: vec![(new_stream_test_success(Yaml::Boolean(true)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
             #[case("command is exit, stdout matchers are failed",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "echo", args: vec!["-n", "hello"], stdout_matchers: vec![(new_stream_test_failure(Yaml::Integer(1)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello".as_bytes())]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello".as_bytes())]}, usages: indexmap! {} })]
             #[case("command is exit, stdout matchers are succeeded (not required passing)",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "echo", args: vec!["-n", "hello"], stdout_matchers: vec![(new_stream_test_success(Yaml::Boolean(true)), false)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::success_message("hello".as_bytes())]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::success_message("hello".as_bytes())]}, usages: indexmap! {} })]
             #[case("command is exit, stdout matchers are failed (not required passing)",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "echo", args: vec!["-n", "hello"], stdout_matchers: vec![(new_stream_test_failure(Yaml::Integer(1)), false)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
             #[case("command is exit, stdout matchers are failed, stdin is given",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "cat", args: vec![], stdin: "hello world", stdout_matchers: vec![(new_stream_test_failure(Yaml::Integer(1)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello world".as_bytes())]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello world".as_bytes())]}, usages: indexmap! {} })]
             #[case("command is exit, stdout matchers are failed, env is given",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "printenv", args: vec!["MESSAGE"], env: vec![("MESSAGE", "hello")], stdout_matchers: vec![(new_stream_test_failure(Yaml::Integer(1)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello\n".as_bytes())]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello\n".as_bytes())]}, usages: indexmap! {} })]
             #[case("command is exit, stderr matchers are succeeded",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], stderr_matchers: vec![(new_stream_test_success(Yaml::Boolean(true)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
             #[case("command is exit, stderr matchers are failed",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "echo -n hi >&2"], stderr_matchers: vec![(new_stream_test_failure(Yaml::Integer(1)), true)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDERR_STRING) => vec![TestMatcher::failure_message("hi".as_bytes())]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDERR_STRING) => vec![TestMatcher::failure_message("hi".as_bytes())]}, usages: indexmap! {} })]
             #[case("command is exit, stderr matchers are succeeded (not required passing)",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "echo -n hi >&2"], stderr_matchers: vec![(new_stream_test_success(Yaml::Boolean(true)), false)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDERR_STRING) => vec![TestMatcher::success_message("hi".as_bytes())]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STDERR_STRING) => vec![TestMatcher::success_message("hi".as_bytes())]}, usages: indexmap! {} })]
             #[case("command is exit, stderr matchers are failed (not required passing)",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "echo -n hi >&2"], stderr_matchers: vec![(new_stream_test_failure(Yaml::Integer(1)), false)], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
+            #[case("command is exit, fd matchers are succeeded",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "echo -n hi >&3"], fds: indexmap! { 3 => ExtraFd::Capture }, fd_matchers: indexmap! { 3 => vec![(new_stream_test_success(Yaml::Boolean(true)), true)] }, ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
+            #[case("command is exit, fd matchers are failed",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "echo -n hi >&3"], fds: indexmap! { 3 => ExtraFd::Capture }, fd_matchers: indexmap! { 3 => vec![(new_stream_test_failure(Yaml::Integer(1)), true)] }, ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{"main:fd3".to_string() => vec![TestMatcher::failure_message("hi".as_bytes())]}, usages: indexmap! {} })]
+            #[case("command is exit, output matchers are succeeded",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], output_matchers: vec![(new_stream_test_success(Yaml::Boolean(true)), true)], ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
+            #[case("command is exit, output matchers are failed",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "echo -n hello; sleep 0.1; echo -n hi >&2"], output_matchers: vec![(new_stream_test_failure(Yaml::Integer(1)), true)], ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{"main:output".to_string() => vec![TestMatcher::failure_message("hellohi".as_bytes())]}, usages: indexmap! {} })]
             #[case("command is signaled",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "kill -TERM $$"], ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec!["signaled with 15".to_string()]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec!["signaled with 15".to_string()]}, usages: indexmap! {} })]
             #[case("command is timed out",
                 TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "sleep", args: vec!["1"], timeout: 0, ..Default::default() } }, ..Default::default() },
-                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec!["timed out (0 sec)".to_string()]} })]
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec!["timed out (0 sec)".to_string()]}, usages: indexmap! {} })]
+            #[case("command is signaled, signal matchers are succeeded",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "kill -TERM $$"], signal_matchers: vec![(SignalMatcher::Eq(Signal::SIGTERM), true)], ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
+            #[case("command is signaled, signal matchers are failed",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "kill -TERM $$"], signal_matchers: vec![(SignalMatcher::Eq(Signal::SIGSEGV), true)], ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{"main:signal".to_string() => vec!["should be signaled with SIGSEGV, but got SIGTERM".to_string()]}, usages: indexmap! {} })]
+            #[case("command is signaled, only negated signal matchers are succeeded",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "kill -TERM $$"], signal_matchers: vec![(SignalMatcher::Eq(Signal::SIGHUP), false)], ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec!["signaled with 15".to_string()]}, usages: indexmap! {} })]
+            #[case("command is exit, signal matchers are failed",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], signal_matchers: vec![(SignalMatcher::Eq(Signal::SIGTERM), true)], ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{"main:signal".to_string() => vec!["should be signaled with SIGTERM, but not signaled".to_string()]}, usages: indexmap! {} })]
+            #[case("command is exit, signal matchers are succeeded (not required passing)",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], signal_matchers: vec![(SignalMatcher::Eq(Signal::SIGTERM), false)], ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
+            #[case("command is timed out as expected",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "sleep", args: vec!["1"], timeout: 0, timed_out: true, ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{}, usages: indexmap! {} })]
+            #[case("command is not timed out against expectation",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], timed_out: true, ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("main:{}", *STATUS_STRING) => vec!["should be timed out, but exited with 0".to_string()]}, usages: indexmap! {} })]
             #[case("with background process",
                 TestCaseTemplate {
                     processes: indexmap! {
@@ -627,7 +1081,8 @@ mod tests {
                             "#
                             ],
                             mode: ProcessMode::Background(BackgroundConfig {
-                                wait_condition: WaitCondition::Sleep(SleepCondition { duration: Duration::from_millis(50) })
+                                wait_condition: WaitCondition::Sleep(SleepCondition { duration: Duration::from_millis(50) }),
+                                ..Default::default()
                             }),
                             status_matchers: vec![(new_status_test_failure(Yaml::Boolean(true)), true)],
                             stdout_matchers: vec![(new_stream_test_failure(Yaml::Boolean(true)), true)],
@@ -650,14 +1105,149 @@ mod tests {
                         format!("bg:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello\n".as_bytes())],
                         format!("bg:{}", *STDERR_STRING) => vec![TestMatcher::failure_message("goodbye\n".as_bytes())],
                         format!("main:{}", *STATUS_STRING) => vec![TestMatcher::failure_message(1)]
-                    }
+                    },
+                    usages: indexmap! {},
+                })]
+            #[case("command leaves descendant process",
+                TestCaseTemplate { processes: indexmap! { "main" => ProcessTemplate { command: "bash", args: vec!["-c", "sleep 5 >/dev/null 2>&1 &"], check_leftover: true, ..Default::default() } }, ..Default::default() },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{"main:leftover".to_string() => vec!["descendant processes are still alive after exit".to_string()]}, usages: indexmap! {} })]
+            #[case("with background process stopped by stop signal",
+                TestCaseTemplate {
+                    processes: indexmap! {
+                        "bg" => ProcessTemplate {
+                            command: "bash",
+                            args: vec!["-c", "exec sleep 10"],
+                            mode: ProcessMode::Background(BackgroundConfig {
+                                wait_condition: WaitCondition::Sleep(SleepCondition { duration: Duration::from_millis(50) }),
+                                stop_signal: Signal::SIGINT,
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }
+                    },
+                    ..Default::default()
+                },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("bg:{}", *STATUS_STRING) => vec!["signaled with 2 (stop signal SIGINT)".to_string()]}, usages: indexmap! {} })]
+            #[case("with background process killed after stop timeout",
+                TestCaseTemplate {
+                    processes: indexmap! {
+                        "bg" => ProcessTemplate {
+                            command: "bash",
+                            args: vec!["-c", "trap '' TERM; exec sleep 10"],
+                            mode: ProcessMode::Background(BackgroundConfig {
+                                wait_condition: WaitCondition::Sleep(SleepCondition { duration: Duration::from_millis(50) }),
+                                stop_timeout: Some(Duration::from_millis(100)),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }
+                    },
+                    ..Default::default()
+                },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("bg:{}", *STATUS_STRING) => vec![format!("not stopped by SIGTERM in {}, killed by SIGKILL", Duration::from_millis(100).human_format())]}, usages: indexmap! {} })]
+            #[case("with pipe from stdout",
+                TestCaseTemplate {
+                    processes: indexmap! {
+                        "producer" => ProcessTemplate {
+                            command: "echo",
+                            args: vec!["-n", "hello"],
+                            ..Default::default()
+                        },
+                        "consumer" => ProcessTemplate {
+                            command: "tr",
+                            args: vec!["a-z", "A-Z"],
+                            stdin_from: Some(PipeSource { process: "producer".to_string(), stream: Stream::Stdout }),
+                            stdout_matchers: vec![(new_stream_test_failure(Yaml::Boolean(true)), true)],
+                            ..Default::default()
+                        },
+                    },
+                    ..Default::default()
+                },
+                TestResult {
+                    name: DEFAULT_NAME.to_string(),
+                    failures: indexmap! {
+                        format!("consumer:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("HELLO".as_bytes())],
+                    },
+                    usages: indexmap! {},
+                })]
+            #[case("with pipe from stderr",
+                TestCaseTemplate {
+                    processes: indexmap! {
+                        "producer" => ProcessTemplate {
+                            command: "bash",
+                            args: vec!["-c", "echo -n hello; echo -n world >&2"],
+                            stdout_matchers: vec![(new_stream_test_failure(Yaml::Boolean(true)), true)],
+                            ..Default::default()
+                        },
+                        "consumer" => ProcessTemplate {
+                            command: "cat",
+                            args: vec![],
+                            stdin_from: Some(PipeSource { process: "producer".to_string(), stream: Stream::Stderr }),
+                            stdout_matchers: vec![(new_stream_test_failure(Yaml::Boolean(true)), true)],
+                            ..Default::default()
+                        },
+                    },
+                    ..Default::default()
+                },
+                TestResult {
+                    name: DEFAULT_NAME.to_string(),
+                    failures: indexmap! {
+                        format!("producer:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello".as_bytes())],
+                        format!("consumer:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("world".as_bytes())],
+                    },
+                    usages: indexmap! {},
+                })]
+            #[case("with pipe closed by reader",
+                TestCaseTemplate {
+                    processes: indexmap! {
+                        "producer" => ProcessTemplate { command: "yes", args: vec![], ..Default::default() },
+                        "consumer" => ProcessTemplate {
+                            command: "head",
+                            args: vec!["-n", "1"],
+                            stdin_from: Some(PipeSource { process: "producer".to_string(), stream: Stream::Stdout }),
+                            ..Default::default()
+                        },
+                    },
+                    ..Default::default()
+                },
+                TestResult { name: DEFAULT_NAME.to_string(), failures: indexmap!{format!("producer:{}", *STATUS_STRING) => vec!["signaled with 13".to_string()]}, usages: indexmap! {} })]
+            #[case("with pipe from foreground process to background process waiting for its input",
+                TestCaseTemplate {
+                    processes: indexmap! {
+                        "producer" => ProcessTemplate { command: "echo", args: vec!["hello"], ..Default::default() },
+                        "consumer" => ProcessTemplate {
+                            command: "bash",
+                            args: vec!["-c", "cat; exec sleep 10"],
+                            stdin_from: Some(PipeSource { process: "producer".to_string(), stream: Stream::Stdout }),
+                            stdout_matchers: vec![(new_stream_test_failure(Yaml::Boolean(true)), true)],
+                            mode: ProcessMode::Background(BackgroundConfig {
+                                wait_condition: WaitCondition::Stdout(StdoutCondition { pattern: regex::Regex::new("hello").unwrap(), timeout: Duration::from_secs(1) }),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                    },
+                    ..Default::default()
+                },
+                TestResult {
+                    name: DEFAULT_NAME.to_string(),
+                    failures: indexmap! {
+                        format!("consumer:{}", *STATUS_STRING) => vec!["signaled with 15 (stop signal SIGTERM)".to_string()],
+                        format!("consumer:{}", *STDOUT_STRING) => vec![TestMatcher::failure_message("hello\n".as_bytes())],
+                    },
+                    usages: indexmap! {},
                 })]
             fn when_exec_succeeded(
                 #[case] title: &str,
                 #[case] given: TestCaseTemplate,
                 #[case] expected: TestResult,
             ) {
-                let actual = given.build().run(false, false);
+                let actual = without_usages(given.build().run(
+                    false,
+                    false,
+                    false,
+                    &ColorMarker::new(false),
+                ));
 
                 assert_eq!(expected, actual, "{}", title);
             }
@@ -710,17 +1300,35 @@ mod tests {
                             command: "bash".to_string(),
                             args: vec!["-c".to_string(), command_with_path],
                             env: vec![],
-                            stdin: "".to_string(),
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! { path.clone() => matchers },
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 };
@@ -730,9 +1338,131 @@ mod tests {
                     failures: expected_messages
                         .map(|messages| indexmap! { format!("file:{}", path) => messages.clone() })
                         .unwrap_or_default(),
+                    usages: indexmap! {},
                 };
 
-                assert_eq!(expected, given.run(false, false), "{}", title);
+                assert_eq!(
+                    expected,
+                    without_usages(given.run(false, false, false, &ColorMarker::new(false))),
+                    "{}",
+                    title
+                );
+            }
+
+            #[rstest]
+            fn when_files_matcher_has_relative_path_and_work_dir_is_given() {
+                let work_dir = tempfile::tempdir().unwrap();
+                std::fs::write(work_dir.path().join("output.txt"), "hello").unwrap();
+
+                let given = TestCaseTemplate {
+                    files_matchers: indexmap! {
+                        "output.txt" => vec![(new_stream_test_failure(Yaml::Boolean(true)), true)],
+                    },
+                    work_dir: Some(work_dir.path().to_path_buf()),
+                    ..Default::default()
+                }
+                .build();
+
+                let expected = TestResult {
+                    name: DEFAULT_NAME.to_string(),
+                    failures: indexmap! {
+                        "file:output.txt".to_string() => vec![TestMatcher::failure_message("hello".as_bytes())],
+                    },
+                    usages: indexmap! {},
+                };
+
+                assert_eq!(
+                    expected,
+                    without_usages(given.run(false, false, false, &ColorMarker::new(false)))
+                );
+            }
+
+            #[rstest]
+            #[case("in order", ("a", "first"), ("b", "second"), None)]
+            #[case("out of order", ("b", "second"), ("a", "first"), Some("line matching \"first\" in a:stdout should be output after line matching \"second\" in b:stdout, but was "))]
+            #[case("not output", ("a", "first"), ("b", "third"), Some("line matching \"third\" in b:stdout was not output"))]
+            fn with_order(
+                #[case] title: &str,
+                #[case] before: (&str, &str),
+                #[case] after: (&str, &str),
+                #[case] expected_message_prefix: Option<&str>,
+            ) {
+                use crate::test_case::order::{Event, Stream};
+
+                let event = |(process, pattern): (&str, &str)| Event {
+                    process: process.to_string(),
+                    stream: Stream::Stdout,
+                    pattern: regex::Regex::new(pattern).unwrap(),
+                };
+                let given = TestCaseTemplate {
+                    processes: indexmap! {
+                        "a" => ProcessTemplate { command: "echo", args: vec!["first"], ..Default::default() },
+                        "b" => ProcessTemplate { command: "echo", args: vec!["second"], ..Default::default() },
+                    },
+                    order: vec![Order {
+                        events: vec![event(before), event(after)],
+                    }],
+                    ..Default::default()
+                }
+                .build();
+
+                let actual = given.run(false, false, false, &ColorMarker::new(false));
+
+                match expected_message_prefix {
+                    Some(prefix) => {
+                        let messages = &actual.failures["order:0"];
+                        assert_eq!(1, messages.len(), "{}", title);
+                        assert!(
+                            messages[0].starts_with(prefix),
+                            "{}: {}",
+                            title,
+                            messages[0]
+                        );
+                    }
+                    None => assert!(
+                        actual.failures.is_empty(),
+                        "{}: {:?}",
+                        title,
+                        actual.failures
+                    ),
+                }
+            }
+
+            #[rstest]
+            #[case("sequential", false, Duration::from_millis(1000), Duration::MAX)]
+            #[case("parallel", true, Duration::ZERO, Duration::from_millis(900))]
+            fn with_parallel(
+                #[case] title: &str,
+                #[case] parallel: bool,
+                #[case] min_elapsed: Duration,
+                #[case] max_elapsed: Duration,
+            ) {
+                let given = TestCaseTemplate {
+                    processes: indexmap! {
+                        "a" => ProcessTemplate { command: "sleep", args: vec!["0.5"], ..Default::default() },
+                        "b" => ProcessTemplate { command: "sleep", args: vec!["0.5"], ..Default::default() },
+                    },
+                    parallel,
+                    ..Default::default()
+                }
+                .build();
+
+                let started = std::time::Instant::now();
+                let actual = given.run(false, false, false, &ColorMarker::new(false));
+                let elapsed = started.elapsed();
+
+                assert!(
+                    actual.failures.is_empty(),
+                    "{}: {:?}",
+                    title,
+                    actual.failures
+                );
+                assert!(
+                    min_elapsed <= elapsed && elapsed < max_elapsed,
+                    "{}: {:?}",
+                    title,
+                    elapsed
+                );
             }
 
             #[rstest]
@@ -791,14 +1521,16 @@ mod tests {
                 }
                 .build();
 
-                let result = given.run(false, false);
+                let result =
+                    without_usages(given.run(false, false, false, &ColorMarker::new(false)));
                 assert_eq!(
                     TestResult {
                         name: DEFAULT_NAME.into(),
                         failures: expected_failures
                             .iter()
                             .map(|(subject, messages)| (subject.to_string(), messages.clone()))
-                            .collect()
+                            .collect(),
+                        usages: indexmap! {},
                     },
                     result,
                     "{}: result",
@@ -821,12 +1553,108 @@ mod tests {
                 }
                 .build();
 
-                let actual = given.run(false, false);
+                let actual = given.run(false, false, false, &ColorMarker::new(false));
 
                 assert_eq!(DEFAULT_NAME, actual.name);
                 assert_eq!(1, actual.failures.len());
                 assert_eq!(1, actual.failures.get("main:exec").unwrap().len());
             }
+
+            #[rstest]
+            #[case("usage matchers are succeeded", "lt", true, 0)]
+            #[case("usage matchers are failed", "gt", true, 1)]
+            #[case("usage matchers are failed (not required passing)", "gt", false, 0)]
+            fn with_usage_matchers(
+                #[case] title: &str,
+                #[case] name: &str,
+                #[case] expected_passed: bool,
+                #[case] expected_failures: usize,
+            ) {
+                let (mut v, _) = crate::validator::testutil::new_validator();
+                let (matcher, _) = UsageMatcher::parse(
+                    &mut v,
+                    "wall_time",
+                    name,
+                    &Yaml::String("10s".to_string()),
+                )
+                .unwrap();
+                let given = TestCaseTemplate {
+                    processes: indexmap! { "main" => ProcessTemplate { command: "true", args: vec![], usage_matchers: vec![(matcher, expected_passed)], ..Default::default() } },
+                    ..Default::default()
+                }
+                .build();
+
+                let actual = given.run(false, false, false, &ColorMarker::new(false));
+
+                assert_eq!(
+                    expected_failures,
+                    actual
+                        .failures
+                        .get("main:usage")
+                        .map(|messages| messages.len())
+                        .unwrap_or_default(),
+                    "{}: failures",
+                    title
+                );
+                assert!(
+                    actual.usages["main"].wall_time < Duration::from_secs(10),
+                    "{}: usages",
+                    title
+                );
+            }
+
+            #[rstest]
+            fn with_unperformed_actions() {
+                let action = Action {
+                    trigger: action::Trigger::OnStdout(regex::Regex::new("ready").unwrap()),
+                    signal: Signal::SIGHUP,
+                };
+                let given = TestCaseTemplate {
+                    processes: indexmap! { "main" => ProcessTemplate { command: "echo", args: vec!["hello"], actions: vec![action], ..Default::default() } },
+                    ..Default::default()
+                }
+                .build();
+
+                let actual = given.run(false, false, false, &ColorMarker::new(false));
+
+                assert_eq!(
+                    Some(&vec!["actions[0] was not performed before exit".to_string()]),
+                    actual.failures.get("main:actions")
+                );
+            }
+        }
+    }
+
+    mod tee_of {
+        use super::*;
+        use once_cell::sync::Lazy;
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+
+        fn tee(label: &str) -> Tee {
+            Tee {
+                label: label.to_string(),
+                started: *STARTED,
+            }
+        }
+
+        #[rstest]
+        #[case("with stdout", 0, (true, false), false, (Some(tee("main:stdout")), None))]
+        #[case("with stderr", 0, (false, true), false, (None, Some(tee("main:stderr"))))]
+        #[case("with color", 1, (true, true), true, (Some(tee("\x1b[33mmain:stdout\x1b[0m")), Some(tee("\x1b[33mmain:stderr\x1b[0m"))))]
+        fn tee_of(
+            #[case] title: &str,
+            #[case] i: usize,
+            #[case] enabled: (bool, bool),
+            #[case] use_color: bool,
+            #[case] expected: (Option<Tee>, Option<Tee>),
+        ) {
+            let actual =
+                super::super::tee_of("main", i, enabled, &ColorMarker::new(use_color), *STARTED);
+
+            assert_eq!(expected, actual, "{}", title);
         }
     }
 
@@ -844,7 +1672,8 @@ mod tests {
                 STATUS_STRING.clone() => vec![],
                 STDOUT_STRING.clone() => vec![],
                 STDERR_STRING.clone() => vec![],
-            }
+            },
+            usages: indexmap! {},
         }], 1)]
         #[case(vec![
             TestResult{ name: "test".to_string(),
@@ -852,9 +1681,10 @@ mod tests {
                     STATUS_STRING.clone() => vec![],
                     STDOUT_STRING.clone() => vec![],
                     STDERR_STRING.clone() => vec![],
-                }
+                },
+                usages: indexmap! {},
             },
-            TestResult{ name: "test2".to_string(), failures: indexmap!{} },
+            TestResult{ name: "test2".to_string(), failures: indexmap!{}, usages: indexmap! {} },
         ], 2)]
         fn len(#[case] results: Vec<TestResult>, #[case] expected: usize) {
             let summary = TestResultSummary { results };
@@ -869,6 +1699,7 @@ mod tests {
                 STDOUT_STRING.clone() => vec![],
                 STDERR_STRING.clone() => vec![],
             },
+            usages: indexmap! {},
         });
         static PASSED2: Lazy<TestResult> = Lazy::new(|| TestResult {
             name: "passed2".to_string(),
@@ -877,6 +1708,7 @@ mod tests {
                 STDOUT_STRING.clone() => vec![],
                 STDERR_STRING.clone() => vec![],
             },
+            usages: indexmap! {},
         });
         static FAILURE1: Lazy<TestResult> = Lazy::new(|| TestResult {
             name: "failure1".to_string(),
@@ -885,6 +1717,7 @@ mod tests {
                 STDOUT_STRING.clone() => vec![],
                 STDERR_STRING.clone() => vec![],
             },
+            usages: indexmap! {},
         });
         static FAILURE2: Lazy<TestResult> = Lazy::new(|| TestResult {
             name: "failure2".to_string(),
@@ -893,6 +1726,7 @@ mod tests {
                 STDOUT_STRING.clone() => vec!["stdout failure".to_string()],
                 STDERR_STRING.clone() => vec![],
             },
+            usages: indexmap! {},
         });
 
         #[rstest]
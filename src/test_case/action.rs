@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+use nix::sys::signal::Signal;
+use regex::Regex;
+
+use crate::{
+    ast::Map,
+    exec::capture::{find_from, Capture},
+    validator::Validator,
+};
+
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Trigger {
+    // elapsed time from the start of the process
+    After(Duration),
+    OnStdout(Regex),
+}
+
+#[cfg(test)]
+impl PartialEq for Trigger {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Trigger::After(x), Trigger::After(y)) => x == y,
+            (Trigger::OnStdout(x), Trigger::OnStdout(y)) => x.as_str() == y.as_str(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Action {
+    pub trigger: Trigger,
+    pub signal: Signal,
+}
+
+impl Action {
+    // waits until the trigger fires. matching of stdout starts from `start`,
+    // and returns the position where next matching should start.
+    pub async fn wait(
+        &self,
+        started: Instant,
+        stdout: &mut Capture,
+        start: usize,
+    ) -> Result<usize, String> {
+        match &self.trigger {
+            Trigger::After(after) => {
+                tokio::time::sleep_until((started + *after).into()).await;
+                Ok(start)
+            }
+            Trigger::OnStdout(pattern) => stdout
+                .wait_for(|buf| find_from(pattern, buf, start))
+                .await?
+                .ok_or_else(|| format!("stdout never output \"{}\"", pattern.as_str())),
+        }
+    }
+
+    pub fn parse(v: &mut Validator, params: &Map) -> Option<Self> {
+        let err_count = v.violations.len();
+
+        let trigger = match (
+            params.contains_key("after"),
+            params.contains_key("on_stdout"),
+        ) {
+            (true, false) => v.may_have_duration(params, "after").map(Trigger::After),
+            (false, true) => v
+                .may_have(params, "on_stdout", |v, x| v.must_be_string(x))
+                .flatten()
+                .and_then(|pattern| {
+                    Regex::new(&pattern)
+                        .inspect_err(|_| {
+                            v.in_field("on_stdout", |v| {
+                                v.add_violation("should be valid regular expression pattern")
+                            });
+                        })
+                        .ok()
+                })
+                .map(Trigger::OnStdout),
+            (true, true) => {
+                v.add_violation("should not have both .after and .on_stdout");
+                None
+            }
+            (false, false) => {
+                v.add_violation("should have .after or .on_stdout");
+                None
+            }
+        };
+        let signal = v.must_have_signal(params, "signal");
+
+        if err_count != v.violations.len() {
+            return None;
+        }
+
+        trigger
+            .zip(signal)
+            .map(|(trigger, signal)| Self { trigger, signal })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod action {
+        use indexmap::indexmap;
+        use once_cell::sync::Lazy;
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+        use saphyr::Yaml;
+
+        use super::*;
+
+        static VALID_DURATION: Lazy<Yaml> = Lazy::new(|| Yaml::String("500ms".to_string()));
+        static VALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("ready".to_string()));
+        static INVALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("(ready".to_string()));
+        static VALID_SIGNAL: Lazy<Yaml> = Lazy::new(|| Yaml::String("HUP".to_string()));
+        static INVALID_VALUE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(true));
+
+        #[rstest]
+        #[case("with after", indexmap! { "after" => &*VALID_DURATION, "signal" => &*VALID_SIGNAL }, Some(Action { trigger: Trigger::After(Duration::from_millis(500)), signal: Signal::SIGHUP }), vec![])]
+        #[case("with on_stdout", indexmap! { "on_stdout" => &*VALID_PATTERN, "signal" => &*VALID_SIGNAL }, Some(Action { trigger: Trigger::OnStdout(Regex::new("ready").unwrap()), signal: Signal::SIGHUP }), vec![])]
+        #[case("without trigger", indexmap! { "signal" => &*VALID_SIGNAL }, None, vec![("", "should have .after or .on_stdout")])]
+        #[case("with both triggers", indexmap! { "after" => &*VALID_DURATION, "on_stdout" => &*VALID_PATTERN, "signal" => &*VALID_SIGNAL }, None, vec![("", "should not have both .after and .on_stdout")])]
+        #[case("without signal", indexmap! { "after" => &*VALID_DURATION }, None, vec![("", "should have .signal as signal name")])]
+        #[case("with invalid after", indexmap! { "after" => &*INVALID_VALUE, "signal" => &*VALID_SIGNAL }, None, vec![(".after", "should be duration, but is bool")])]
+        #[case("with invalid pattern", indexmap! { "on_stdout" => &*INVALID_PATTERN, "signal" => &*VALID_SIGNAL }, None, vec![(".on_stdout", "should be valid regular expression pattern")])]
+        #[case("with invalid signal", indexmap! { "after" => &*VALID_DURATION, "signal" => &*INVALID_VALUE }, None, vec![(".signal", "should be signal name, but is bool")])]
+        fn parse(
+            #[case] title: &'static str,
+            #[case] params: Map,
+            #[case] expected_value: Option<Action>,
+            #[case] expected_violation: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = crate::validator::testutil::new_validator();
+
+            let actual = Action::parse(&mut v, &params);
+
+            assert_eq!(expected_value, actual, "{}", title);
+            assert_eq!(
+                expected_violation
+                    .into_iter()
+                    .map(|(path, msg)| violation(path, msg))
+                    .collect::<Vec<_>>(),
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use regex::Regex;
+
+use crate::{ast::Map, validator::Validator};
+
+use super::wait_condition::StdoutCondition;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct DialogueStep {
+    pub expect: Option<StdoutCondition>,
+    pub send: String,
+}
+
+impl DialogueStep {
+    pub fn parse(v: &mut Validator, params: &Map) -> Option<Self> {
+        let err_count = v.violations.len();
+
+        if !params.contains_key("expect") && !params.contains_key("send") {
+            v.add_violation("should have .expect or .send");
+            return None;
+        }
+
+        let pattern = v
+            .may_have(params, "expect", |v, x| v.must_be_string(x))
+            .flatten()
+            .and_then(|pattern| {
+                Regex::new(&pattern)
+                    .inspect_err(|_| {
+                        v.in_field("expect", |v| {
+                            v.add_violation("should be valid regular expression pattern")
+                        });
+                    })
+                    .ok()
+            });
+        let timeout = v
+            .may_have_duration(params, "timeout")
+            .unwrap_or(DEFAULT_TIMEOUT);
+        let send = v
+            .may_have(params, "send", |v, x| v.must_be_string(x))
+            .flatten()
+            .unwrap_or_default();
+
+        if err_count != v.violations.len() {
+            return None;
+        }
+
+        Some(Self {
+            expect: pattern.map(|pattern| StdoutCondition { pattern, timeout }),
+            send,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod dialogue_step {
+        use indexmap::indexmap;
+        use once_cell::sync::Lazy;
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+        use saphyr::Yaml;
+
+        use super::*;
+
+        static VALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("\\[y/N\\]".to_string()));
+        static INVALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("[y/N".to_string()));
+        static VALID_SEND: Lazy<Yaml> = Lazy::new(|| Yaml::String("y\n".to_string()));
+        static VALID_DURATION: Lazy<Yaml> = Lazy::new(|| Yaml::String("10s".to_string()));
+        static INVALID_DURATION: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(true));
+
+        #[rstest]
+        #[case("with expect and send", indexmap! { "expect" => &*VALID_PATTERN, "send" => &*VALID_SEND }, Some(DialogueStep { expect: Some(StdoutCondition { pattern: Regex::new("\\[y/N\\]").unwrap(), timeout: Duration::from_secs(3) }), send: "y\n".to_string() }), vec![])]
+        #[case("with full params", indexmap! { "expect" => &*VALID_PATTERN, "send" => &*VALID_SEND, "timeout" => &*VALID_DURATION }, Some(DialogueStep { expect: Some(StdoutCondition { pattern: Regex::new("\\[y/N\\]").unwrap(), timeout: Duration::from_secs(10) }), send: "y\n".to_string() }), vec![])]
+        #[case("with expect only", indexmap! { "expect" => &*VALID_PATTERN }, Some(DialogueStep { expect: Some(StdoutCondition { pattern: Regex::new("\\[y/N\\]").unwrap(), timeout: Duration::from_secs(3) }), send: "".to_string() }), vec![])]
+        #[case("with send only", indexmap! { "send" => &*VALID_SEND }, Some(DialogueStep { expect: None, send: "y\n".to_string() }), vec![])]
+        #[case("without expect and send", indexmap! {}, None, vec![("", "should have .expect or .send")])]
+        #[case("with invalid pattern", indexmap! { "expect" => &*INVALID_PATTERN, "send" => &*VALID_SEND }, None, vec![(".expect", "should be valid regular expression pattern")])]
+        #[case("with not string send", indexmap! { "send" => &*INVALID_DURATION }, None, vec![(".send", "should be string, but is bool")])]
+        #[case("with invalid timeout", indexmap! { "expect" => &*VALID_PATTERN, "timeout" => &*INVALID_DURATION }, None, vec![(".timeout", "should be duration, but is bool")])]
+        fn parse(
+            #[case] title: &'static str,
+            #[case] params: Map,
+            #[case] expected_value: Option<DialogueStep>,
+            #[case] expected_violation: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = crate::validator::testutil::new_validator();
+
+            let actual = DialogueStep::parse(&mut v, &params);
+
+            assert_eq!(expected_value, actual, "{}", title);
+            assert_eq!(
+                expected_violation
+                    .into_iter()
+                    .map(|(path, msg)| violation(path, msg))
+                    .collect::<Vec<_>>(),
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::os::fd::RawFd;
+
+use indexmap::IndexMap;
+use saphyr::Yaml;
+
+use crate::{
+    ast::{Ast, Map},
+    validator::Validator,
+};
+
+// 0, 1 and 2 are stdin, stdout and stderr
+const MIN_FD: RawFd = 3;
+
+// fd of the process other than stdin, stdout and stderr, connected to a pipe.
+// input is written to the pipe, or output to the pipe is captured.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ExtraFd {
+    Input(String),
+    Capture,
+}
+
+const KEYS: [&str; 2] = ["input", "capture"];
+
+impl ExtraFd {
+    pub fn parse(v: &mut Validator, params: &Map) -> Option<Self> {
+        let keys: Vec<&str> = KEYS
+            .into_iter()
+            .filter(|key| params.contains_key(key))
+            .collect();
+
+        match keys[..] {
+            ["input"] => v
+                .may_have(params, "input", |v, x| v.must_be_string(x))
+                .flatten()
+                .map(ExtraFd::Input),
+            ["capture"] => match v.may_have_bool(params, "capture") {
+                Some(true) => Some(ExtraFd::Capture),
+                Some(false) => {
+                    v.in_field("capture", |v| v.add_violation("should be true"));
+                    None
+                }
+                None => None,
+            },
+            [] => {
+                v.add_violation("should have .input or .capture");
+                None
+            }
+            _ => {
+                v.add_violation("should have only one of .input and .capture");
+                None
+            }
+        }
+    }
+}
+
+// map keyed by fd numbers, e.g. `{3: {input: hello}}`. keys may be quoted.
+pub fn parse_fd_map<'a>(v: &mut Validator, x: &'a Yaml) -> Option<IndexMap<RawFd, &'a Yaml>> {
+    let Some(original) = x.as_hash() else {
+        v.add_violation(format!("should be map, but is {}", x.type_name()));
+        return None;
+    };
+
+    let mut m = IndexMap::new();
+    for (key, value) in original {
+        let fd = match key {
+            Yaml::Integer(n) => RawFd::try_from(*n).ok(),
+            Yaml::String(s) => s.parse::<RawFd>().ok(),
+            _ => None,
+        };
+        match fd {
+            Some(fd) if fd >= MIN_FD => {
+                m.insert(fd, value);
+            }
+            _ => {
+                v.add_violation(format!(
+                    "should be map keyed by fd number ({} or more), but contains {:?}",
+                    MIN_FD, key
+                ));
+                return None;
+            }
+        }
+    }
+    Some(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::indexmap;
+    use once_cell::sync::Lazy;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    static VALID_INPUT: Lazy<Yaml> = Lazy::new(|| Yaml::String("hello".to_string()));
+    static TRUE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(true));
+    static FALSE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(false));
+    static INVALID_VALUE: Lazy<Yaml> = Lazy::new(|| Yaml::Integer(1));
+
+    #[rstest]
+    #[case("with input", indexmap! { "input" => &*VALID_INPUT }, Some(ExtraFd::Input("hello".to_string())), vec![])]
+    #[case("with capture", indexmap! { "capture" => &*TRUE }, Some(ExtraFd::Capture), vec![])]
+    #[case("with not string input", indexmap! { "input" => &*INVALID_VALUE }, None, vec![(".input", "should be string, but is uint")])]
+    #[case("with false capture", indexmap! { "capture" => &*FALSE }, None, vec![(".capture", "should be true")])]
+    #[case("without any", indexmap! {}, None, vec![("", "should have .input or .capture")])]
+    #[case("with both input and capture", indexmap! { "input" => &*VALID_INPUT, "capture" => &*TRUE }, None, vec![("", "should have only one of .input and .capture")])]
+    fn parse(
+        #[case] title: &str,
+        #[case] params: Map,
+        #[case] expected_value: Option<ExtraFd>,
+        #[case] expected_violations: Vec<(&str, &str)>,
+    ) {
+        let (mut v, violation) = crate::validator::testutil::new_validator();
+
+        let actual = ExtraFd::parse(&mut v, &params);
+
+        assert_eq!(expected_value, actual, "{}", title);
+        assert_eq!(
+            expected_violations
+                .into_iter()
+                .map(|(path, msg)| violation(path, msg))
+                .collect::<Vec<_>>(),
+            v.violations,
+            "{}",
+            title
+        );
+    }
+
+    fn keyed_by(keys: Vec<Yaml>) -> Yaml {
+        let mut m = saphyr::Hash::new();
+        keys.into_iter().for_each(|key| {
+            m.insert(key, VALID_INPUT.clone());
+        });
+        Yaml::Hash(m)
+    }
+
+    #[rstest]
+    #[case("with number keys", keyed_by(vec![Yaml::Integer(3), Yaml::Integer(4)]), Some(vec![3, 4]), vec![])]
+    #[case("with quoted keys", keyed_by(vec![Yaml::String("3".to_string())]), Some(vec![3]), vec![])]
+    #[case("with not map", Yaml::Integer(42), None, vec![("", "should be map, but is uint")])]
+    #[case("with stdout", keyed_by(vec![Yaml::Integer(1)]), None, vec![("", "should be map keyed by fd number (3 or more), but contains Integer(1)")])]
+    #[case("with not number key", keyed_by(vec![Yaml::String("out".to_string())]), None, vec![("", "should be map keyed by fd number (3 or more), but contains String(\"out\")")])]
+    fn parse_fd_map(
+        #[case] title: &str,
+        #[case] given: Yaml,
+        #[case] expected_fds: Option<Vec<RawFd>>,
+        #[case] expected_violations: Vec<(&str, &str)>,
+    ) {
+        let (mut v, violation) = crate::validator::testutil::new_validator();
+
+        let actual = super::parse_fd_map(&mut v, &given);
+
+        assert_eq!(
+            expected_fds,
+            actual.map(|m| m.keys().copied().collect::<Vec<_>>()),
+            "{}",
+            title
+        );
+        assert_eq!(
+            expected_violations
+                .into_iter()
+                .map(|(path, msg)| violation(path, msg))
+                .collect::<Vec<_>>(),
+            v.violations,
+            "{}",
+            title
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use std::os::unix::ffi::OsStrExt;
+use std::time::Instant;
+
+use regex::Regex;
+use saphyr::Yaml;
+
+use crate::{ast::Map, exec::Output, validator::Validator};
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+// output of a line matching the pattern in the stream of the process
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Event {
+    pub process: String,
+    pub stream: Stream,
+    pub pattern: Regex,
+}
+
+#[cfg(test)]
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.process == other.process
+            && self.stream == other.stream
+            && self.pattern.as_str() == other.pattern.as_str()
+    }
+}
+
+impl Event {
+    // returns when the first matching line arrived
+    pub fn time_in(&self, output: &Output) -> Option<Instant> {
+        match self.stream {
+            Stream::Stdout => output
+                .stdout_arrivals
+                .first_line_matching(&self.pattern, output.stdout.as_bytes()),
+            Stream::Stderr => output
+                .stderr_arrivals
+                .first_line_matching(&self.pattern, output.stderr.as_bytes()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "line matching \"{}\" in {}:{}",
+            self.pattern.as_str(),
+            self.process,
+            self.stream.name()
+        )
+    }
+
+    fn parse(v: &mut Validator, params: &Map, process_names: &[&str]) -> Option<Self> {
+        let err_count = v.violations.len();
+
+        let process = v.must_have_string(params, "process").and_then(|process| {
+            if process_names.contains(&process.as_str()) {
+                Some(process)
+            } else {
+                v.in_field("process", |v| {
+                    v.add_violation(format!(
+                        "should be name of process, but got \"{}\"",
+                        process
+                    ))
+                });
+                None
+            }
+        });
+
+        let stream = match (params.contains_key("stdout"), params.contains_key("stderr")) {
+            (true, false) => Some(Stream::Stdout),
+            (false, true) => Some(Stream::Stderr),
+            (true, true) => {
+                v.add_violation("should not have both .stdout and .stderr");
+                None
+            }
+            (false, false) => {
+                v.add_violation("should have .stdout or .stderr");
+                None
+            }
+        };
+        let pattern = stream.and_then(|stream| {
+            v.may_have(params, stream.name(), |v, x| v.must_be_string(x))
+                .flatten()
+                .and_then(|pattern| {
+                    Regex::new(&pattern)
+                        .inspect_err(|_| {
+                            v.in_field(stream.name(), |v| {
+                                v.add_violation("should be valid regular expression pattern")
+                            });
+                        })
+                        .ok()
+                })
+        });
+
+        if err_count != v.violations.len() {
+            return None;
+        }
+
+        Some(Self {
+            process: process?,
+            stream: stream?,
+            pattern: pattern?,
+        })
+    }
+}
+
+// events which should happen in the given order
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Order {
+    pub events: Vec<Event>,
+}
+
+impl Order {
+    // checks the order with `time_of`, which returns when the event happened.
+    // events which never happened are reported instead.
+    pub fn check(&self, time_of: impl Fn(&Event) -> Option<Instant>) -> Vec<String> {
+        let times: Vec<Option<Instant>> = self.events.iter().map(time_of).collect();
+
+        let mut messages: Vec<String> = self
+            .events
+            .iter()
+            .zip(&times)
+            .filter(|(_, time)| time.is_none())
+            .map(|(event, _)| format!("{} was not output", event.describe()))
+            .collect();
+
+        for (i, window) in times.windows(2).enumerate() {
+            if let [Some(before), Some(after)] = window {
+                if after < before {
+                    messages.push(format!(
+                        "{} should be output after {}, but was {:.3}s before",
+                        self.events[i + 1].describe(),
+                        self.events[i].describe(),
+                        (*before - *after).as_secs_f64()
+                    ));
+                }
+            }
+        }
+
+        messages
+    }
+
+    pub fn parse(v: &mut Validator, x: &Yaml, process_names: &[&str]) -> Option<Self> {
+        let events = v.must_be_seq(x)?;
+        if events.len() < 2 {
+            v.add_violation(format!(
+                "should have 2 or more events, but has {}",
+                events.len()
+            ));
+            return None;
+        }
+
+        v.map_seq(events, |v, event| {
+            v.must_be_map(event)
+                .and_then(|event| Event::parse(v, &event, process_names))
+        })
+        .map(|events| Self { events })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn event(process: &str, stream: Stream, pattern: &str) -> Event {
+        Event {
+            process: process.to_string(),
+            stream,
+            pattern: Regex::new(pattern).unwrap(),
+        }
+    }
+
+    mod event {
+        use indexmap::indexmap;
+        use once_cell::sync::Lazy;
+
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        static CLIENT: Lazy<Yaml> = Lazy::new(|| Yaml::String("client".to_string()));
+        static UNKNOWN: Lazy<Yaml> = Lazy::new(|| Yaml::String("unknown".to_string()));
+        static VALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("sent".to_string()));
+        static INVALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("(sent".to_string()));
+        static INVALID_VALUE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(true));
+
+        #[rstest]
+        #[case("with stdout", indexmap! { "process" => &*CLIENT, "stdout" => &*VALID_PATTERN }, Some(event("client", Stream::Stdout, "sent")), vec![])]
+        #[case("with stderr", indexmap! { "process" => &*CLIENT, "stderr" => &*VALID_PATTERN }, Some(event("client", Stream::Stderr, "sent")), vec![])]
+        #[case("without process", indexmap! { "stdout" => &*VALID_PATTERN }, None, vec![("", "should have .process as string")])]
+        #[case("with unknown process", indexmap! { "process" => &*UNKNOWN, "stdout" => &*VALID_PATTERN }, None, vec![(".process", "should be name of process, but got \"unknown\"")])]
+        #[case("without stream", indexmap! { "process" => &*CLIENT }, None, vec![("", "should have .stdout or .stderr")])]
+        #[case("with both streams", indexmap! { "process" => &*CLIENT, "stdout" => &*VALID_PATTERN, "stderr" => &*VALID_PATTERN }, None, vec![("", "should not have both .stdout and .stderr")])]
+        #[case("with invalid pattern", indexmap! { "process" => &*CLIENT, "stdout" => &*INVALID_PATTERN }, None, vec![(".stdout", "should be valid regular expression pattern")])]
+        #[case("with not string pattern", indexmap! { "process" => &*CLIENT, "stderr" => &*INVALID_VALUE }, None, vec![(".stderr", "should be string, but is bool")])]
+        fn parse(
+            #[case] title: &str,
+            #[case] params: Map,
+            #[case] expected_value: Option<Event>,
+            #[case] expected_violations: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = crate::validator::testutil::new_validator();
+
+            let actual = Event::parse(&mut v, &params, &["client", "server"]);
+
+            assert_eq!(expected_value, actual, "{}", title);
+            assert_eq!(
+                expected_violations
+                    .into_iter()
+                    .map(|(path, msg)| violation(path, msg))
+                    .collect::<Vec<_>>(),
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+
+    mod order {
+        use std::time::Duration;
+
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        #[case("in order", vec![Some(0), Some(100), Some(100)], vec![])]
+        #[case("out of order", vec![Some(100), Some(0), Some(200)], vec!["line matching \"accepted\" in server:stdout should be output after line matching \"sent\" in client:stdout, but was 0.100s before"])]
+        #[case("not output", vec![Some(0), None, Some(200)], vec!["line matching \"accepted\" in server:stdout was not output"])]
+        fn check(
+            #[case] title: &str,
+            #[case] millis: Vec<Option<u64>>,
+            #[case] expected: Vec<&str>,
+        ) {
+            let given = Order {
+                events: vec![
+                    event("client", Stream::Stdout, "sent"),
+                    event("server", Stream::Stdout, "accepted"),
+                    event("client", Stream::Stderr, "closed"),
+                ],
+            };
+            let started = Instant::now();
+            let times = millis
+                .into_iter()
+                .map(|millis| millis.map(|millis| started + Duration::from_millis(millis)))
+                .collect::<Vec<_>>();
+
+            let actual = given.check(|event| {
+                let i = given.events.iter().position(|e| e == event).unwrap();
+                times[i]
+            });
+
+            assert_eq!(expected, actual, "{}", title);
+        }
+
+        #[rstest]
+        #[case("with not seq", Yaml::Integer(0), vec![("", "should be seq, but is uint")])]
+        #[case("with 1 event", Yaml::Array(vec![Yaml::Integer(0)]), vec![("", "should have 2 or more events, but has 1")])]
+        #[case("with not map event", Yaml::Array(vec![Yaml::Integer(0), Yaml::Integer(1)]), vec![("[0]", "should be map, but is uint"), ("[1]", "should be map, but is uint")])]
+        fn parse_failure_cases(
+            #[case] title: &str,
+            #[case] given: Yaml,
+            #[case] expected_violations: Vec<(&str, &str)>,
+        ) {
+            let (mut v, violation) = crate::validator::testutil::new_validator();
+
+            let actual = Order::parse(&mut v, &given, &["client", "server"]);
+
+            assert!(actual.is_none(), "{}", title);
+            assert_eq!(
+                expected_violations
+                    .into_iter()
+                    .map(|(path, msg)| violation(path, msg))
+                    .collect::<Vec<_>>(),
+                v.violations,
+                "{}",
+                title
+            );
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{ast::Map, validator::Validator};
+
+use super::order::Stream;
+
+// output stream of the process which is connected to stdin of another process by a pipe
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct PipeSource {
+    pub process: String,
+    pub stream: Stream,
+}
+
+impl PipeSource {
+    // only processes started before can be the source, so `process_names` are names of them
+    pub fn parse(v: &mut Validator, params: &Map, process_names: &[&str]) -> Option<Self> {
+        let err_count = v.violations.len();
+
+        let process = v.must_have_string(params, "from").and_then(|process| {
+            if process_names.contains(&process.as_str()) {
+                Some(process)
+            } else {
+                v.in_field("from", |v| {
+                    v.add_violation(format!(
+                        "should be name of preceding process, but got \"{}\"",
+                        process
+                    ))
+                });
+                None
+            }
+        });
+
+        let stream = match v
+            .may_have(params, "stream", |v, x| v.must_be_string(x))
+            .flatten()
+            .as_deref()
+        {
+            None | Some("stdout") => Some(Stream::Stdout),
+            Some("stderr") => Some(Stream::Stderr),
+            Some(stream) => {
+                v.in_field("stream", |v| {
+                    v.add_violation(format!(
+                        "should be stdout or stderr, but got \"{}\"",
+                        stream
+                    ))
+                });
+                None
+            }
+        };
+
+        if err_count != v.violations.len() {
+            return None;
+        }
+
+        Some(Self {
+            process: process?,
+            stream: stream?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::indexmap;
+    use once_cell::sync::Lazy;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use saphyr::Yaml;
+
+    static PRODUCER: Lazy<Yaml> = Lazy::new(|| Yaml::String("producer".to_string()));
+    static CONSUMER: Lazy<Yaml> = Lazy::new(|| Yaml::String("consumer".to_string()));
+    static STDOUT: Lazy<Yaml> = Lazy::new(|| Yaml::String("stdout".to_string()));
+    static STDERR: Lazy<Yaml> = Lazy::new(|| Yaml::String("stderr".to_string()));
+    static OUTPUT: Lazy<Yaml> = Lazy::new(|| Yaml::String("output".to_string()));
+    static INVALID_VALUE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(true));
+
+    fn source(process: &str, stream: Stream) -> PipeSource {
+        PipeSource {
+            process: process.to_string(),
+            stream,
+        }
+    }
+
+    #[rstest]
+    #[case("without stream", indexmap! { "from" => &*PRODUCER }, Some(source("producer", Stream::Stdout)), vec![])]
+    #[case("with stdout", indexmap! { "from" => &*PRODUCER, "stream" => &*STDOUT }, Some(source("producer", Stream::Stdout)), vec![])]
+    #[case("with stderr", indexmap! { "from" => &*PRODUCER, "stream" => &*STDERR }, Some(source("producer", Stream::Stderr)), vec![])]
+    #[case("without from", indexmap! { "stream" => &*STDOUT }, None, vec![("", "should have .from as string")])]
+    #[case("with not string from", indexmap! { "from" => &*INVALID_VALUE }, None, vec![(".from", "should be string, but is bool")])]
+    #[case("with not preceding process", indexmap! { "from" => &*CONSUMER }, None, vec![(".from", "should be name of preceding process, but got \"consumer\"")])]
+    #[case("with unknown stream", indexmap! { "from" => &*PRODUCER, "stream" => &*OUTPUT }, None, vec![(".stream", "should be stdout or stderr, but got \"output\"")])]
+    #[case("with not string stream", indexmap! { "from" => &*PRODUCER, "stream" => &*INVALID_VALUE }, None, vec![(".stream", "should be string, but is bool")])]
+    fn parse(
+        #[case] title: &str,
+        #[case] params: Map,
+        #[case] expected_value: Option<PipeSource>,
+        #[case] expected_violations: Vec<(&str, &str)>,
+    ) {
+        let (mut v, violation) = crate::validator::testutil::new_validator();
+
+        let actual = PipeSource::parse(&mut v, &params, &["producer"]);
+
+        assert_eq!(expected_value, actual, "{}", title);
+        assert_eq!(
+            expected_violations
+                .into_iter()
+                .map(|(path, msg)| violation(path, msg))
+                .collect::<Vec<_>>(),
+            v.violations,
+            "{}",
+            title
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use base64::Engine;
+
+use crate::{ast::Map, validator::Validator};
+
+// how the value of stdin is turned into bytes.
+// file is read when the process starts, since it may be created by setup hooks.
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum StdinFormat {
+    Text,
+    File,
+    Base64,
+    Hex,
+}
+
+impl StdinFormat {
+    // formats given as map with the name as key
+    pub const KEYED: [StdinFormat; 3] = [StdinFormat::File, StdinFormat::Base64, StdinFormat::Hex];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StdinFormat::Text => "text",
+            StdinFormat::File => "file",
+            StdinFormat::Base64 => "base64",
+            StdinFormat::Hex => "hex",
+        }
+    }
+}
+
+// whitespaces are ignored, so that long data can be folded
+pub fn decode_base64(s: &str) -> Result<Vec<u8>, String> {
+    let s: String = s.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|err| format!("should be valid base64, but {}", err))
+}
+
+// whitespaces are ignored, so that bytes can be separated
+pub fn decode_hex(s: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("should be valid hex, but has odd number of digits".to_string());
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| {
+                    format!(
+                        "should be valid hex, but has invalid digits \"{}\"",
+                        String::from_utf8_lossy(pair)
+                    )
+                })
+        })
+        .collect()
+}
+
+// part of stdin which is fed in order, so that input can arrive in pieces
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum StdinChunk {
+    Data(String),
+    Delay(Duration),
+    Close,
+}
+
+const KEYS: [&str; 3] = ["data", "delay", "close"];
+
+impl StdinChunk {
+    pub fn parse(v: &mut Validator, params: &Map) -> Option<Self> {
+        let keys: Vec<&str> = KEYS
+            .into_iter()
+            .filter(|key| params.contains_key(key))
+            .collect();
+
+        match keys[..] {
+            ["data"] => v
+                .may_have(params, "data", |v, x| v.must_be_string(x))
+                .flatten()
+                .map(StdinChunk::Data),
+            ["delay"] => v.may_have_duration(params, "delay").map(StdinChunk::Delay),
+            ["close"] => match v.may_have_bool(params, "close") {
+                Some(true) => Some(StdinChunk::Close),
+                Some(false) => {
+                    v.in_field("close", |v| v.add_violation("should be true"));
+                    None
+                }
+                None => None,
+            },
+            [] => {
+                v.add_violation("should have .data, .delay or .close");
+                None
+            }
+            _ => {
+                v.add_violation("should have only one of .data, .delay and .close");
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::indexmap;
+    use once_cell::sync::Lazy;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use saphyr::Yaml;
+
+    static VALID_DATA: Lazy<Yaml> = Lazy::new(|| Yaml::String("hello".to_string()));
+    static VALID_DURATION: Lazy<Yaml> = Lazy::new(|| Yaml::String("200ms".to_string()));
+    static TRUE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(true));
+    static FALSE: Lazy<Yaml> = Lazy::new(|| Yaml::Boolean(false));
+    static INVALID_VALUE: Lazy<Yaml> = Lazy::new(|| Yaml::Integer(1));
+
+    #[rstest]
+    #[case("with base64", "aGVsbG8=", Ok(b"hello".to_vec()))]
+    #[case("with folded base64", "aGVs\nbG8=\n", Ok(b"hello".to_vec()))]
+    #[case("with binary base64", "AP8=", Ok(vec![0x00, 0xff]))]
+    #[case("with invalid base64", "aGVsbG8", Err("should be valid base64, but Invalid padding".to_string()))]
+    fn decode_base64(
+        #[case] title: &str,
+        #[case] given: &str,
+        #[case] expected: Result<Vec<u8>, String>,
+    ) {
+        assert_eq!(expected, super::decode_base64(given), "{}", title);
+    }
+
+    #[rstest]
+    #[case("with hex", "68656c6c6f", Ok(b"hello".to_vec()))]
+    #[case("with separated hex", "00 ff\nFF", Ok(vec![0x00, 0xff, 0xff]))]
+    #[case("with odd digits", "0ff", Err("should be valid hex, but has odd number of digits".to_string()))]
+    #[case("with invalid digits", "0g", Err("should be valid hex, but has invalid digits \"0g\"".to_string()))]
+    fn decode_hex(
+        #[case] title: &str,
+        #[case] given: &str,
+        #[case] expected: Result<Vec<u8>, String>,
+    ) {
+        assert_eq!(expected, super::decode_hex(given), "{}", title);
+    }
+
+    #[rstest]
+    #[case("with data", indexmap! { "data" => &*VALID_DATA }, Some(StdinChunk::Data("hello".to_string())), vec![])]
+    #[case("with delay", indexmap! { "delay" => &*VALID_DURATION }, Some(StdinChunk::Delay(Duration::from_millis(200))), vec![])]
+    #[case("with close", indexmap! { "close" => &*TRUE }, Some(StdinChunk::Close), vec![])]
+    #[case("with not string data", indexmap! { "data" => &*INVALID_VALUE }, None, vec![(".data", "should be string, but is uint")])]
+    #[case("with invalid delay", indexmap! { "delay" => &*TRUE }, None, vec![(".delay", "should be duration, but is bool")])]
+    #[case("with false close", indexmap! { "close" => &*FALSE }, None, vec![(".close", "should be true")])]
+    #[case("with not bool close", indexmap! { "close" => &*INVALID_VALUE }, None, vec![(".close", "should be bool, but is uint")])]
+    #[case("without any", indexmap! {}, None, vec![("", "should have .data, .delay or .close")])]
+    #[case("with both data and delay", indexmap! { "data" => &*VALID_DATA, "delay" => &*VALID_DURATION }, None, vec![("", "should have only one of .data, .delay and .close")])]
+    fn parse(
+        #[case] title: &str,
+        #[case] params: Map,
+        #[case] expected_value: Option<StdinChunk>,
+        #[case] expected_violations: Vec<(&str, &str)>,
+    ) {
+        let (mut v, violation) = crate::validator::testutil::new_validator();
+
+        let actual = StdinChunk::parse(&mut v, &params);
+
+        assert_eq!(expected_value, actual, "{}", title);
+        assert_eq!(
+            expected_violations
+                .into_iter()
+                .map(|(path, msg)| violation(path, msg))
+                .collect::<Vec<_>>(),
+            v.violations,
+            "{}",
+            title
+        );
+    }
+}
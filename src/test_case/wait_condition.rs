@@ -4,14 +4,13 @@ mod stdout;
 
 use std::time::Duration;
 
-use stdout::StdoutCondition;
-
 use crate::ast::Map;
 use crate::exec::BackgroundExec;
 use crate::validator::Validator;
 
 pub use self::http::HttpCondition;
 pub use self::sleep::SleepCondition;
+pub use self::stdout::StdoutCondition;
 
 #[derive(Clone)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -28,11 +27,7 @@ impl WaitCondition {
         match self {
             WaitCondition::Sleep(sleep_condition) => sleep_condition.wait().await,
             WaitCondition::Http(http_condition) => http_condition.wait().await,
-            WaitCondition::Stdout(stdout_condition) => {
-                let output = stdout_condition.wait(exec).await?;
-                exec.append_buffered_stdout(&output);
-                Ok(())
-            }
+            WaitCondition::Stdout(stdout_condition) => stdout_condition.wait(exec).await,
             #[cfg(test)]
             WaitCondition::SuccessStub(_) => Ok(()),
         }
@@ -1,10 +1,17 @@
 use duration_str::HumanFormat;
+use std::future::Future;
 use std::time::Duration;
 
 use regex::Regex;
-use tokio::io::{AsyncBufReadExt, BufReader};
 
-use crate::{ast::Map, exec::BackgroundExec, validator::Validator};
+use crate::{
+    ast::Map,
+    exec::{
+        capture::{find_from, Capture},
+        BackgroundExec,
+    },
+    validator::Validator,
+};
 
 #[derive(Clone)]
 #[cfg_attr(test, derive(Debug))]
@@ -22,38 +29,44 @@ impl PartialEq for StdoutCondition {
 
 impl StdoutCondition {
     // FIXME: dependency cycle (exec -> test_case -> exec)
-    pub async fn wait(&self, exec: &mut BackgroundExec) -> Result<String, String> {
-        let stdout = exec.child.stdout.as_mut().unwrap();
-        let mut reader = BufReader::new(stdout);
-
-        let result = tokio::time::timeout(self.timeout, async {
-            let mut buf = String::new();
-            let mut line = String::new();
-
-            while reader
-                .read_line(&mut line)
-                .await
-                .map_err(|err| err.to_string())?
-                > 0
-            {
-                buf.push_str(&line);
-                if self.pattern.is_match(&line) {
-                    return Ok(buf);
+    pub async fn wait(&self, exec: &mut BackgroundExec) -> Result<(), String> {
+        // matches line by line, skipping lines which are already checked
+        let mut checked = 0;
+        self.with_timeout(exec.stdout.wait_for(|buf| {
+            for line in buf[checked..].split_inclusive(|b| *b == b'\n') {
+                if self.pattern.is_match(&String::from_utf8_lossy(line)) {
+                    return Some(());
+                }
+                if line.ends_with(b"\n") {
+                    checked += line.len();
                 }
-                line.clear();
             }
+            None
+        }))
+        .await
+    }
 
-            Err(format!("stdout never output \"{}\"", self.pattern.as_str()))
-        })
-        .await;
+    // unlike wait(), matches output regardless of lines so that prompts without newline can be matched.
+    // matching starts from `start`, and returns end position of the matched part.
+    pub async fn wait_output(&self, stdout: &mut Capture, start: usize) -> Result<usize, String> {
+        self.with_timeout(stdout.wait_for(|buf| find_from(&self.pattern, buf, start)))
+            .await
+    }
 
-        result.unwrap_or_else(|_| {
-            Err(format!(
+    async fn with_timeout<T>(
+        &self,
+        found: impl Future<Output = Result<Option<T>, String>>,
+    ) -> Result<T, String> {
+        match tokio::time::timeout(self.timeout, found).await {
+            Ok(found) => {
+                found?.ok_or_else(|| format!("stdout never output \"{}\"", self.pattern.as_str()))
+            }
+            Err(_) => Err(format!(
                 "stdout did not output \"{}\" in {}",
                 self.pattern.as_str(),
                 self.timeout.human_format()
-            ))
-        })
+            )),
+        }
     }
 
     pub fn parse(v: &mut Validator, params: &Map) -> Option<Self> {
@@ -88,6 +101,7 @@ impl StdoutCondition {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exec::capture::Merged;
 
     mod stdout_condition {
         use indexmap::indexmap;
@@ -104,17 +118,17 @@ mod tests {
             "when matched, returns Ok",
             Duration::from_secs(3),
             "echo hello; echo world; echo goodbye",
-            Ok("hello\nworld\n".to_string())
+            Ok(())
         )]
         #[tokio::test]
-        #[case("when timeout, returns Err", Duration::from_millis(10), "yes", Err("stdout did not output \"wo.ld\" in 10ms".to_string()))]
+        #[case("when timeout, returns Err", Duration::from_millis(10), "sleep 1", Err("stdout did not output \"wo.ld\" in 10ms".to_string()))]
         #[tokio::test]
         #[case("when never matched, returns Err", Duration::from_secs(3), "true", Err("stdout never output \"wo.ld\"".to_string()))]
         async fn wait(
             #[case] title: &'static str,
             #[case] timeout: Duration,
             #[case] command: &'static str,
-            #[case] expected: Result<String, String>,
+            #[case] expected: Result<(), String>,
         ) {
             let given = StdoutCondition {
                 pattern: Regex::new("wo.ld").unwrap(),
@@ -129,13 +143,94 @@ mod tests {
                 .spawn()
                 .unwrap();
 
-            let mut exec = BackgroundExec::new(child, Duration::from_secs(10), false, false);
+            let mut exec = BackgroundExec::new(
+                child,
+                nix::sys::signal::Signal::SIGTERM,
+                Duration::from_secs(10),
+                false,
+                (None, None),
+            )
+            .unwrap();
 
             let actual = given.wait(&mut exec).await;
 
             assert_eq!(actual, expected, "{}", title);
         }
 
+        #[rstest]
+        #[tokio::test]
+        async fn wait_keeps_output_after_matched_line() {
+            let given = StdoutCondition {
+                pattern: Regex::new("wo.ld").unwrap(),
+                timeout: Duration::from_secs(3),
+            };
+
+            let child = tokio::process::Command::new("bash")
+                .arg("-c")
+                .arg("echo hello; echo world; echo goodbye; sleep 1")
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .process_group(0)
+                .spawn()
+                .unwrap();
+
+            let mut exec = BackgroundExec::new(
+                child,
+                nix::sys::signal::Signal::SIGTERM,
+                Duration::from_secs(10),
+                false,
+                (None, None),
+            )
+            .unwrap();
+
+            given.wait(&mut exec).await.unwrap();
+            let output = exec.terminate().await.unwrap();
+
+            assert_eq!("hello\nworld\ngoodbye\n", output.stdout);
+        }
+
+        #[rstest]
+        #[tokio::test]
+        #[case(
+            "when matched, returns end of matched part",
+            Duration::from_secs(3),
+            "echo -n 'hello world?'; sleep 1",
+            0,
+            Ok((11, "hello world?".to_string()))
+        )]
+        #[tokio::test]
+        #[case("when timeout, returns Err", Duration::from_millis(100), "echo -n hello; sleep 1", 0, Err("stdout did not output \"wo.ld\" in 100ms".to_string()))]
+        #[tokio::test]
+        #[case("when never matched, returns Err", Duration::from_secs(3), "echo -n hello", 0, Err("stdout never output \"wo.ld\"".to_string()))]
+        async fn wait_output(
+            #[case] title: &'static str,
+            #[case] timeout: Duration,
+            #[case] command: &'static str,
+            #[case] start: usize,
+            #[case] expected: Result<(usize, String), String>,
+        ) {
+            let given = StdoutCondition {
+                pattern: Regex::new("wo.ld").unwrap(),
+                timeout,
+            };
+
+            let mut child = tokio::process::Command::new("bash")
+                .arg("-c")
+                .arg(command)
+                .stdout(std::process::Stdio::piped())
+                .spawn()
+                .unwrap();
+
+            let mut stdout = Capture::start(child.stdout.take(), "stdout", None, Merged::default());
+            let actual = given
+                .wait_output(&mut stdout, start)
+                .await
+                .map(|end| (end, String::from_utf8_lossy(&stdout.snapshot()).to_string()));
+
+            assert_eq!(actual, expected, "{}", title);
+        }
+
         static VALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("wo.ld".to_string()));
         static INVALID_PATTERN: Lazy<Yaml> = Lazy::new(|| Yaml::String("(wo.ld".to_string()));
         static VALID_DURATION: Lazy<Yaml> = Lazy::new(|| Yaml::String("10s".to_string()));
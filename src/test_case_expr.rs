@@ -1,13 +1,22 @@
-use std::{net::TcpListener, time::Duration};
+use std::{net::TcpListener, os::fd::RawFd, time::Duration};
 
 use indexmap::{indexmap, IndexMap};
+use nix::sys::signal::Signal;
 use saphyr::Yaml;
 
 use crate::{
+    ast::Map,
     expr::{Context, EvalOutput, Expr},
-    matcher::{StatusMatcher, StreamMatcher},
+    matcher::{SignalMatcher, StatusMatcher, StreamMatcher, UsageMatcher},
     test_case::{
-        setup_hook::SetupHook, BackgroundConfig, Process, ProcessMode, TestCase, WaitCondition,
+        action::Action,
+        dialogue::DialogueStep,
+        extra_fd::ExtraFd,
+        order::{Order, Stream},
+        pipe::PipeSource,
+        setup_hook::SetupHook,
+        stdin::{decode_base64, decode_hex, StdinChunk, StdinFormat},
+        BackgroundConfig, Limits, Process, ProcessMode, TestCase, WaitCondition, WindowSize,
     },
     tmp_dir::TmpDirSupplier,
     validator::{Validator, Violation},
@@ -22,6 +31,20 @@ pub struct TestExprError {
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct BackgroundConfigExpr {
     pub wait_condition: Option<WaitConditionExpr>,
+    pub stop_signal: Signal,
+    pub stop_timeout: Option<Duration>,
+    pub stop_order: Option<u64>,
+}
+
+impl Default for BackgroundConfigExpr {
+    fn default() -> Self {
+        Self {
+            wait_condition: None,
+            stop_signal: Signal::SIGTERM,
+            stop_timeout: None,
+            stop_order: None,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -43,11 +66,33 @@ pub struct ProcessExpr {
     pub command: Expr,
     pub args: Vec<Expr>,
     pub stdin: Expr,
+    pub stdin_format: StdinFormat,
+    // when given, stdin is fed in chunks and kept open in background until closed by a chunk
+    pub stdin_chunks: Option<Vec<IndexMap<String, Expr>>>,
+    pub stdin_from: Option<PipeSource>,
+    pub fds: IndexMap<RawFd, IndexMap<String, Expr>>,
     pub env: Vec<(String, Expr)>,
+    pub dir: Option<Expr>,
+    pub tty: Option<WindowSize>,
+    pub dialogue: Vec<IndexMap<String, Expr>>,
+    pub limits: Limits,
+    pub actions: Vec<Action>,
     pub timeout: Duration,
     pub mode: ProcessModeExpr,
     pub tee_stdout: bool,
     pub tee_stderr: bool,
+    pub check_leftover: bool,
+}
+
+impl ProcessExpr {
+    // fds whose output can be matched
+    pub fn captured_fds(&self) -> Vec<RawFd> {
+        self.fds
+            .iter()
+            .filter(|(_, params)| params.contains_key("capture"))
+            .map(|(fd, _)| *fd)
+            .collect()
+    }
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -57,14 +102,50 @@ pub enum ProcessesExpr {
     Multi(IndexMap<String, ProcessExpr>),
 }
 
+impl ProcessesExpr {
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            ProcessesExpr::Single(_) => vec![DEFAULT_PROCESS_NAME],
+            ProcessesExpr::Multi(processes) => processes.keys().map(String::as_str).collect(),
+        }
+    }
+
+    pub fn captured_fds(&self, name: &str) -> Vec<RawFd> {
+        let process = match self {
+            ProcessesExpr::Single(process) => (name == DEFAULT_PROCESS_NAME).then_some(process),
+            ProcessesExpr::Multi(processes) => processes.get(name),
+        };
+        process.map(ProcessExpr::captured_fds).unwrap_or_default()
+    }
+
+    // output streams of the process which are read by other processes
+    pub fn piped_streams(&self, name: &str) -> Vec<Stream> {
+        match self {
+            ProcessesExpr::Single(_) => vec![],
+            ProcessesExpr::Multi(processes) => processes
+                .values()
+                .filter_map(|process| process.stdin_from.as_ref())
+                .filter(|from| from.process == name)
+                .map(|from| from.stream)
+                .collect(),
+        }
+    }
+}
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct ProcessMatchersExpr {
     pub status_matcher_exprs: IndexMap<String, Expr>,
+    pub signal_matcher_exprs: IndexMap<String, Expr>,
     pub stdout_matcher_exprs: IndexMap<String, Expr>,
     pub stderr_matcher_exprs: IndexMap<String, Expr>,
+    pub fd_matcher_exprs: IndexMap<RawFd, IndexMap<String, Expr>>,
+    pub output_matcher_exprs: IndexMap<String, Expr>,
+    pub usage_matcher_exprs: IndexMap<String, IndexMap<String, Expr>>,
+    pub timed_out: bool,
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
+#[allow(clippy::large_enum_variant)]
 pub enum ProcessesMatchersExpr {
     Single(ProcessMatchersExpr),
     Multi(IndexMap<String, ProcessMatchersExpr>),
@@ -77,8 +158,11 @@ pub struct TestCaseExpr {
     pub path: String,
     pub let_decls: IndexMap<String, Expr>,
     pub processes: ProcessesExpr,
+    // when true, foreground processes are run concurrently
+    pub parallel: bool,
     pub processes_matchers: ProcessesMatchersExpr,
     pub files_matchers: IndexMap<String, IndexMap<String, Expr>>,
+    pub order: Vec<Order>,
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -89,11 +173,16 @@ pub struct TestCaseExprFile {
 
 type ProcessMatchersTuple = (
     Vec<(StatusMatcher, bool)>,
+    Vec<(SignalMatcher, bool)>,
+    Vec<(StreamMatcher, bool)>,
     Vec<(StreamMatcher, bool)>,
+    IndexMap<RawFd, Vec<(StreamMatcher, bool)>>,
     Vec<(StreamMatcher, bool)>,
+    Vec<(UsageMatcher, bool)>,
+    bool,
 );
 
-const DEFAULT_PROCESS_NAME: &str = "main";
+pub const DEFAULT_PROCESS_NAME: &str = "main";
 
 pub fn eval_test_expr<T: TmpDirSupplier>(
     tmp_dir_supplier: &mut T,
@@ -124,8 +213,13 @@ pub fn eval_test_expr<T: TmpDirSupplier>(
             indexmap! {
                 DEFAULT_PROCESS_NAME => (
                     eval_matcher_exprs(v, &mut ctx, "status", StatusMatcher::parse, &pm.status_matcher_exprs),
+                    eval_matcher_exprs(v, &mut ctx, "signal", SignalMatcher::parse, &pm.signal_matcher_exprs),
                     eval_matcher_exprs(v, &mut ctx, "stdout", StreamMatcher::parse, &pm.stdout_matcher_exprs),
                     eval_matcher_exprs(v, &mut ctx, "stderr", StreamMatcher::parse, &pm.stderr_matcher_exprs),
+                    eval_fd_matcher_exprs(v, &mut ctx, &pm.fd_matcher_exprs),
+                    eval_matcher_exprs(v, &mut ctx, "output", StreamMatcher::parse, &pm.output_matcher_exprs),
+                    eval_usage_matcher_exprs(v, &mut ctx, &pm.usage_matcher_exprs),
+                    pm.timed_out,
                 )
             }
         }
@@ -143,6 +237,13 @@ pub fn eval_test_expr<T: TmpDirSupplier>(
                                 StatusMatcher::parse,
                                 &pm.status_matcher_exprs,
                             ),
+                            eval_matcher_exprs(
+                                v,
+                                &mut ctx,
+                                "signal",
+                                SignalMatcher::parse,
+                                &pm.signal_matcher_exprs,
+                            ),
                             eval_matcher_exprs(
                                 v,
                                 &mut ctx,
@@ -157,6 +258,16 @@ pub fn eval_test_expr<T: TmpDirSupplier>(
                                 StreamMatcher::parse,
                                 &pm.stderr_matcher_exprs,
                             ),
+                            eval_fd_matcher_exprs(v, &mut ctx, &pm.fd_matcher_exprs),
+                            eval_matcher_exprs(
+                                v,
+                                &mut ctx,
+                                "output",
+                                StreamMatcher::parse,
+                                &pm.output_matcher_exprs,
+                            ),
+                            eval_usage_matcher_exprs(v, &mut ctx, &pm.usage_matcher_exprs),
+                            pm.timed_out,
                         ),
                     )
                 })
@@ -166,10 +277,10 @@ pub fn eval_test_expr<T: TmpDirSupplier>(
 
     let processes = match &test_case_expr.processes {
         ProcessesExpr::Single(process_expr) => {
-            let (status_matchers, stdout_matchers, stderr_matchers) = processes_matchers
+            let matchers = processes_matchers
                 .shift_remove(DEFAULT_PROCESS_NAME)
                 .unwrap_or_default();
-            indexmap! { DEFAULT_PROCESS_NAME.to_string() => eval_process_expr(&mut v, &mut ctx, &mut setup_hooks, status_matchers, stdout_matchers, stderr_matchers, process_expr) }
+            indexmap! { DEFAULT_PROCESS_NAME.to_string() => eval_process_expr(&mut v, &mut ctx, &mut setup_hooks, matchers, process_expr) }
         }
         ProcessesExpr::Multi(process_exprs) => process_exprs
             .iter()
@@ -177,19 +288,10 @@ pub fn eval_test_expr<T: TmpDirSupplier>(
                 (
                     name.clone(),
                     v.in_field(name, |v| {
-                        let (status_matchers, stdout_matchers, stderr_matchers) =
-                            processes_matchers
-                                .shift_remove(name.as_str())
-                                .unwrap_or_default();
-                        eval_process_expr(
-                            v,
-                            &mut ctx,
-                            &mut setup_hooks,
-                            status_matchers,
-                            stdout_matchers,
-                            stderr_matchers,
-                            process_expr,
-                        )
+                        let matchers = processes_matchers
+                            .shift_remove(name.as_str())
+                            .unwrap_or_default();
+                        eval_process_expr(v, &mut ctx, &mut setup_hooks, matchers, process_expr)
                     }),
                 )
             })
@@ -244,6 +346,8 @@ pub fn eval_test_expr<T: TmpDirSupplier>(
     }
     .unwrap_or("".to_string());
 
+    let work_dir = ctx.work_dir().cloned();
+
     if v.violations.is_empty() {
         Ok(vec![TestCase {
             name,
@@ -251,6 +355,9 @@ pub fn eval_test_expr<T: TmpDirSupplier>(
             path: test_case_expr.path.clone(),
             processes,
             files_matchers,
+            order: test_case_expr.order.clone(),
+            parallel: test_case_expr.parallel,
+            work_dir,
             setup_hooks,
             teardown_hooks: vec![],
         }])
@@ -288,15 +395,91 @@ fn eval_matcher_exprs<
     })
 }
 
+fn eval_usage_matcher_exprs<TS: TmpDirSupplier>(
+    v: &mut Validator,
+    ctx: &mut Context<'_, '_, TS>,
+    matcher_exprs: &IndexMap<String, IndexMap<String, Expr>>,
+) -> Vec<(UsageMatcher, bool)> {
+    v.in_field("usage", |v| {
+        matcher_exprs
+            .iter()
+            .flat_map(|(metric, exprs)| {
+                eval_matcher_exprs(
+                    v,
+                    ctx,
+                    metric,
+                    |v, name, param| UsageMatcher::parse(v, metric, name, param),
+                    exprs,
+                )
+            })
+            .collect()
+    })
+}
+
+fn eval_fd_matcher_exprs<TS: TmpDirSupplier>(
+    v: &mut Validator,
+    ctx: &mut Context<'_, '_, TS>,
+    matcher_exprs: &IndexMap<RawFd, IndexMap<String, Expr>>,
+) -> IndexMap<RawFd, Vec<(StreamMatcher, bool)>> {
+    v.in_field("fds", |v| {
+        matcher_exprs
+            .iter()
+            .map(|(fd, exprs)| {
+                (
+                    *fd,
+                    eval_matcher_exprs(v, ctx, &fd.to_string(), StreamMatcher::parse, exprs),
+                )
+            })
+            .collect()
+    })
+}
+
+// evaluates params of dialogue steps, stdin chunks, extra fds and wait conditions,
+// and parses them with the evaluated values
+fn eval_params_map<T: TmpDirSupplier, R, F: FnOnce(&mut Validator, &Map) -> Option<R>>(
+    v: &mut Validator,
+    ctx: &mut Context<'_, '_, T>,
+    setup_hooks: &mut Vec<SetupHook>,
+    params: &IndexMap<String, Expr>,
+    parse: F,
+) -> Option<R> {
+    let params: Option<IndexMap<&String, Yaml>> = params
+        .iter()
+        .map(|(k, expr)| match ctx.eval_expr(expr) {
+            Ok(EvalOutput {
+                value,
+                setup_hooks: output_setup_hooks,
+            }) => {
+                setup_hooks.extend(output_setup_hooks);
+                Some((k, value))
+            }
+            Err(message) => {
+                v.in_field(k, |v| v.add_violation(format!("eval error: {}", message)));
+                None
+            }
+        })
+        .collect();
+
+    params.and_then(|params| parse(v, &params.iter().map(|(k, v)| (k.as_str(), v)).collect()))
+}
+
 fn eval_process_expr<T: TmpDirSupplier>(
     v: &mut Validator,
     ctx: &mut Context<'_, '_, T>,
     setup_hooks: &mut Vec<SetupHook>,
-    status_matchers: Vec<(StatusMatcher, bool)>,
-    stdout_matchers: Vec<(StreamMatcher, bool)>,
-    stderr_matchers: Vec<(StreamMatcher, bool)>,
+    matchers: ProcessMatchersTuple,
     process_expr: &ProcessExpr,
 ) -> Process {
+    let (
+        status_matchers,
+        signal_matchers,
+        stdout_matchers,
+        stderr_matchers,
+        fd_matchers,
+        output_matchers,
+        usage_matchers,
+        timed_out,
+    ) = matchers;
     let command = v.in_field("command[0]", |v| {
         match ctx.eval_expr(&process_expr.command) {
             Ok(EvalOutput {
@@ -336,8 +519,9 @@ fn eval_process_expr<T: TmpDirSupplier>(
             .collect()
     });
 
-    let stdin = v
-        .in_field("stdin", |v| match ctx.eval_expr(&process_expr.stdin) {
+    let stdin_format = process_expr.stdin_format;
+    let (stdin, stdin_file) = v.in_field("stdin", |v| {
+        let mut eval_stdin = |v: &mut Validator| match ctx.eval_expr(&process_expr.stdin) {
             Ok(EvalOutput {
                 value,
                 setup_hooks: output_setup_hooks,
@@ -349,8 +533,25 @@ fn eval_process_expr<T: TmpDirSupplier>(
                 v.add_violation(format!("eval error: {}", message));
                 None
             }
-        })
-        .unwrap_or("".to_string());
+        };
+        let mut eval_decoded = |v: &mut Validator, decode: fn(&str) -> Result<Vec<u8>, String>| {
+            v.in_field(stdin_format.name(), |v| {
+                eval_stdin(v)
+                    .and_then(|value| decode(&value).map_err(|err| v.add_violation(err)).ok())
+            })
+            .unwrap_or_default()
+        };
+
+        match stdin_format {
+            StdinFormat::Text => (
+                eval_stdin(v).map(String::into_bytes).unwrap_or_default(),
+                None,
+            ),
+            StdinFormat::File => (vec![], v.in_field("file", eval_stdin)),
+            StdinFormat::Base64 => (eval_decoded(v, decode_base64), None),
+            StdinFormat::Hex => (eval_decoded(v, decode_hex), None),
+        }
+    });
 
     let env: Vec<(String, String)> = v.in_field("env", |v| {
         process_expr
@@ -377,47 +578,92 @@ fn eval_process_expr<T: TmpDirSupplier>(
             .collect()
     });
 
+    let dir = process_expr.dir.as_ref().and_then(|dir| {
+        v.in_field("dir", |v| match ctx.eval_expr(dir) {
+            Ok(EvalOutput {
+                value,
+                setup_hooks: output_setup_hooks,
+            }) => {
+                setup_hooks.extend(output_setup_hooks);
+                v.must_be_string(&value)
+            }
+            Err(message) => {
+                v.add_violation(format!("eval error: {}", message));
+                None
+            }
+        })
+    });
+
+    let dialogue: Vec<DialogueStep> = v.in_field("dialogue", |v| {
+        process_expr
+            .dialogue
+            .iter()
+            .enumerate()
+            .filter_map(|(i, step)| {
+                v.in_index(i, |v| {
+                    eval_params_map(v, ctx, setup_hooks, step, DialogueStep::parse)
+                })
+            })
+            .collect()
+    });
+
+    let stdin_chunks: Option<Vec<StdinChunk>> = process_expr.stdin_chunks.as_ref().map(|chunks| {
+        v.in_field("stdin", |v| {
+            chunks
+                .iter()
+                .enumerate()
+                .filter_map(|(i, chunk)| {
+                    v.in_index(i, |v| {
+                        eval_params_map(v, ctx, setup_hooks, chunk, StdinChunk::parse)
+                    })
+                })
+                .collect()
+        })
+    });
+
+    let fds: IndexMap<RawFd, ExtraFd> = v.in_field("fds", |v| {
+        process_expr
+            .fds
+            .iter()
+            .filter_map(|(fd, params)| {
+                v.in_field(fd.to_string(), |v| {
+                    eval_params_map(v, ctx, setup_hooks, params, ExtraFd::parse)
+                        .map(|extra_fd| (*fd, extra_fd))
+                })
+            })
+            .collect()
+    });
+
     let mode = match &process_expr.mode {
         ProcessModeExpr::Foreground => ProcessMode::Foreground,
-        ProcessModeExpr::Background(BackgroundConfigExpr { wait_condition }) => {
-            v.in_field("background", |v| {
+        ProcessModeExpr::Background(BackgroundConfigExpr {
+            wait_condition,
+            stop_signal,
+            stop_timeout,
+            stop_order,
+        }) => {
+            let wait_condition = v.in_field("background", |v| {
                 v.in_field("wait_for", |v| {
                     wait_condition
                         .as_ref()
                         .and_then(|wait_condition| {
-                            let params: Option<IndexMap<&String, Yaml>> = wait_condition
-                                .params
-                                .iter()
-                                .map(|(k, expr)| match ctx.eval_expr(expr) {
-                                    Ok(EvalOutput {
-                                        value,
-                                        setup_hooks: output_setup_hooks,
-                                    }) => {
-                                        setup_hooks.extend(output_setup_hooks);
-                                        Some((k, value))
-                                    }
-                                    Err(message) => {
-                                        v.in_field(k, |v| {
-                                            v.add_violation(format!("eval error: {}", message))
-                                        });
-                                        None
-                                    }
-                                })
-                                .collect();
-
-                            params.and_then(|params| {
-                                WaitCondition::parse(
-                                    v,
-                                    &wait_condition.name,
-                                    &params.iter().map(|(k, v)| (k.as_str(), v)).collect(),
-                                )
-                                .map(|wait_condition| {
-                                    ProcessMode::Background(BackgroundConfig { wait_condition })
-                                })
-                            })
+                            eval_params_map(
+                                v,
+                                ctx,
+                                setup_hooks,
+                                &wait_condition.params,
+                                |v, params| WaitCondition::parse(v, &wait_condition.name, params),
+                            )
                         })
-                        .unwrap_or(ProcessMode::Background(BackgroundConfig::default()))
+                        .unwrap_or_default()
                 })
+            });
+
+            ProcessMode::Background(BackgroundConfig {
+                wait_condition,
+                stop_signal: *stop_signal,
+                stop_timeout: *stop_timeout,
+                stop_order: *stop_order,
             })
         }
     };
@@ -426,20 +672,35 @@ fn eval_process_expr<T: TmpDirSupplier>(
         command,
         args,
         stdin,
+        stdin_file,
+        stdin_chunks,
+        stdin_from: process_expr.stdin_from.clone(),
+        fds,
         env,
+        dir,
+        tty: process_expr.tty,
+        dialogue,
+        limits: process_expr.limits.clone(),
+        actions: process_expr.actions.clone(),
         status_matchers,
+        signal_matchers,
         stdout_matchers,
         stderr_matchers,
+        fd_matchers,
+        output_matchers,
+        usage_matchers,
+        timed_out,
         timeout: process_expr.timeout,
         mode,
         tee_stdout: process_expr.tee_stdout,
         tee_stderr: process_expr.tee_stderr,
+        check_leftover: process_expr.check_leftover,
     }
 }
 
 #[cfg(test)]
 pub mod testutil {
-    use std::time::Duration;
+    use std::{os::fd::RawFd, time::Duration};
 
     use indexmap::indexmap;
     use indexmap::IndexMap;
@@ -448,6 +709,9 @@ pub mod testutil {
     use crate::expr::Expr;
 
     use crate::expr::testutil::*;
+    use crate::test_case::{
+        action::Action, order::Order, pipe::PipeSource, stdin::StdinFormat, Limits, WindowSize,
+    };
 
     use super::ProcessExpr;
     use super::ProcessMatchersExpr;
@@ -460,11 +724,21 @@ pub mod testutil {
         pub command: Expr,
         pub args: Vec<Expr>,
         pub stdin: Expr,
+        pub stdin_format: StdinFormat,
+        pub stdin_chunks: Option<Vec<IndexMap<&'static str, Expr>>>,
+        pub stdin_from: Option<PipeSource>,
+        pub fds: IndexMap<RawFd, IndexMap<&'static str, Expr>>,
         pub env: Vec<(&'static str, Expr)>,
+        pub dir: Option<Expr>,
+        pub tty: Option<WindowSize>,
+        pub dialogue: Vec<IndexMap<&'static str, Expr>>,
+        pub limits: Limits,
+        pub actions: Vec<Action>,
         pub timeout: u64,
         pub mode: ProcessModeExpr,
         pub tee_stdout: bool,
         pub tee_stderr: bool,
+        pub check_leftover: bool,
     }
 
     impl ProcessExprTemplate {
@@ -473,15 +747,46 @@ pub mod testutil {
                 command: self.command.clone(),
                 args: self.args.clone(),
                 stdin: self.stdin.clone(),
+                stdin_format: self.stdin_format,
+                stdin_chunks: self.stdin_chunks.map(|chunks| {
+                    chunks
+                        .into_iter()
+                        .map(|chunk| chunk.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+                        .collect()
+                }),
+                stdin_from: self.stdin_from,
+                fds: self
+                    .fds
+                    .into_iter()
+                    .map(|(fd, params)| {
+                        (
+                            fd,
+                            params
+                                .into_iter()
+                                .map(|(k, v)| (k.to_string(), v))
+                                .collect(),
+                        )
+                    })
+                    .collect(),
                 env: self
                     .env
                     .iter()
                     .map(|(k, v)| (k.to_string(), v.clone()))
                     .collect(),
+                dir: self.dir,
+                tty: self.tty,
+                dialogue: self
+                    .dialogue
+                    .into_iter()
+                    .map(|step| step.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+                    .collect(),
+                limits: self.limits,
+                actions: self.actions,
                 timeout: Duration::from_secs(self.timeout),
                 mode: self.mode,
                 tee_stdout: self.tee_stdout,
                 tee_stderr: self.tee_stderr,
+                check_leftover: self.check_leftover,
             }
         }
     }
@@ -492,17 +797,27 @@ pub mod testutil {
                 command: TestCaseExprTemplate::default_command(),
                 args: TestCaseExprTemplate::default_args(),
                 stdin: literal_expr(Yaml::String("".to_string())),
+                stdin_format: StdinFormat::Text,
+                stdin_chunks: None,
+                stdin_from: None,
+                fds: indexmap! {},
                 env: vec![],
+                dir: None,
+                tty: None,
+                dialogue: vec![],
+                limits: Limits::default(),
+                actions: vec![],
                 timeout: 10,
                 mode: ProcessModeExpr::Foreground,
                 tee_stdout: false,
                 tee_stderr: false,
+                check_leftover: false,
             }
         }
     }
 
     pub enum ProcessesExprTemplate {
-        Single(ProcessExprTemplate),
+        Single(Box<ProcessExprTemplate>),
         Multi(IndexMap<&'static str, ProcessExprTemplate>),
     }
 
@@ -521,8 +836,13 @@ pub mod testutil {
 
     pub struct ProcessMatchersExprTemplate {
         pub status_matcher_exprs: IndexMap<&'static str, Expr>,
+        pub signal_matcher_exprs: IndexMap<&'static str, Expr>,
         pub stdout_matcher_exprs: IndexMap<&'static str, Expr>,
         pub stderr_matcher_exprs: IndexMap<&'static str, Expr>,
+        pub fd_matcher_exprs: IndexMap<RawFd, IndexMap<&'static str, Expr>>,
+        pub output_matcher_exprs: IndexMap<&'static str, Expr>,
+        pub usage_matcher_exprs: IndexMap<&'static str, IndexMap<&'static str, Expr>>,
+        pub timed_out: bool,
     }
 
     impl ProcessMatchersExprTemplate {
@@ -533,6 +853,11 @@ pub mod testutil {
                     .into_iter()
                     .map(|(k, v)| (k.to_string(), v))
                     .collect(),
+                signal_matcher_exprs: self
+                    .signal_matcher_exprs
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect(),
                 stdout_matcher_exprs: self
                     .stdout_matcher_exprs
                     .into_iter()
@@ -543,6 +868,32 @@ pub mod testutil {
                     .into_iter()
                     .map(|(k, v)| (k.to_string(), v))
                     .collect(),
+                fd_matcher_exprs: self
+                    .fd_matcher_exprs
+                    .into_iter()
+                    .map(|(fd, exprs)| {
+                        (
+                            fd,
+                            exprs.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+                        )
+                    })
+                    .collect(),
+                output_matcher_exprs: self
+                    .output_matcher_exprs
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect(),
+                usage_matcher_exprs: self
+                    .usage_matcher_exprs
+                    .into_iter()
+                    .map(|(metric, exprs)| {
+                        (
+                            metric.to_string(),
+                            exprs.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+                        )
+                    })
+                    .collect(),
+                timed_out: self.timed_out,
             }
         }
     }
@@ -551,14 +902,19 @@ pub mod testutil {
         fn default() -> Self {
             Self {
                 status_matcher_exprs: indexmap! {},
+                signal_matcher_exprs: indexmap! {},
                 stdout_matcher_exprs: indexmap! {},
                 stderr_matcher_exprs: indexmap! {},
+                fd_matcher_exprs: indexmap! {},
+                output_matcher_exprs: indexmap! {},
+                usage_matcher_exprs: indexmap! {},
+                timed_out: false,
             }
         }
     }
 
     pub enum ProcessesMatchersExprTemplate {
-        Single(ProcessMatchersExprTemplate),
+        Single(Box<ProcessMatchersExprTemplate>),
         Multi(IndexMap<&'static str, ProcessMatchersExprTemplate>),
     }
 
@@ -584,8 +940,10 @@ pub mod testutil {
         pub path: &'static str,
         pub let_decls: IndexMap<&'static str, Expr>,
         pub processes: ProcessesExprTemplate,
+        pub parallel: bool,
         pub processes_matchers: ProcessesMatchersExprTemplate,
         pub files_matchers: IndexMap<&'static str, IndexMap<&'static str, Expr>>,
+        pub order: Vec<Order>,
     }
 
     impl TestCaseExprTemplate {
@@ -623,6 +981,8 @@ pub mod testutil {
                         )
                     })
                     .collect(),
+                order: self.order,
+                parallel: self.parallel,
             }
         }
     }
@@ -634,9 +994,11 @@ pub mod testutil {
                 filename: TestCaseExprTemplate::DEFAULT_FILENAME,
                 path: TestCaseExprTemplate::DEFAULT_PATH,
                 let_decls: indexmap! {},
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate::default()),
+                processes: ProcessesExprTemplate::Single(Box::default()),
                 processes_matchers: ProcessesMatchersExprTemplate::Multi(indexmap! {}),
                 files_matchers: indexmap! {},
+                order: vec![],
+                parallel: false,
             }
         }
     }
@@ -652,7 +1014,7 @@ mod tests {
                 new_status_test_success, new_stream_test_success, PARSE_ERROR_VIOLATION_MESSAGE,
                 TEST_PARSE_ERROR_NAME, TEST_SUCCESS_NAME, TEST_SUCCESS_NAME_WITH_NOT,
             },
-            test_case::{setup_hook::SetupHook, BackgroundConfig, ProcessMode},
+            test_case::{order::Stream, setup_hook::SetupHook, BackgroundConfig, ProcessMode},
             test_case_expr::testutil::{
                 ProcessExprTemplate, ProcessMatchersExprTemplate, ProcessesExprTemplate,
                 ProcessesMatchersExprTemplate, TestCaseExprTemplate,
@@ -682,18 +1044,36 @@ mod tests {
                 "main".to_string() => Process {
                     command: "echo".to_string(),
                     args: vec!["hello".to_string()],
-                    stdin: "".to_string(),
+                    stdin: vec![],
+                    stdin_file: None,
+                    stdin_chunks: None,
+                    stdin_from: None,
+                    fds: indexmap! {},
                     env: vec![],
+                    dir: None,
+                    tty: None,
+                    dialogue: vec![],
+                    limits: Limits::default(),
+                    actions: vec![],
                     timeout: Duration::from_secs(10),
                     mode: ProcessMode::Foreground,
                     tee_stdout: false,
                     tee_stderr: false,
+                    check_leftover: false,
                     status_matchers: vec![],
                     stdout_matchers: vec![],
                     stderr_matchers: vec![],
+                    fd_matchers: indexmap! {},
+                    output_matchers: vec![],
+                    usage_matchers: vec![],
+                    signal_matchers: vec![],
+                    timed_out: false,
                 }
             },
             files_matchers: indexmap! {},
+            order: vec![],
+            parallel: false,
+            work_dir: None,
             setup_hooks: vec![],
             teardown_hooks: vec![],
         }])]
@@ -711,18 +1091,36 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -737,6 +1135,7 @@ mod tests {
                                 name: "success_stub".to_string(),
                                 params: indexmap! { "answer".to_string() => literal_expr(Yaml::Integer(42)) }
                             }),
+                            ..Default::default()
                         }),
                         ..Default::default()
                     },
@@ -753,33 +1152,67 @@ mod tests {
                         "process1".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Background(BackgroundConfig {
                                 wait_condition: WaitCondition::SuccessStub(indexmap! { "answer".to_string() => Yaml::Integer(42) }),
+                                ..Default::default()
                             }),
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         },
                         "process2".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 }
@@ -787,10 +1220,228 @@ mod tests {
         )]
         #[case("with stdin case",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     stdin: literal_expr(Yaml::String("hello".to_string())),
                     ..Default::default()
-                }),
+                })),
+                ..Default::default()
+            },
+            vec![
+                TestCase {
+                    name: TestCaseExprTemplate::NAME_FOR_DEFAULT_COMMAND.to_string(),
+                    filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                    path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                    processes: indexmap! {
+                        "main".to_string() => Process {
+                            command: "echo".to_string(),
+                            args: vec!["hello".to_string()],
+                            stdin: b"hello".to_vec(),
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
+                            env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            timeout: Duration::from_secs(10),
+                            mode: ProcessMode::Foreground,
+                            tee_stdout: false,
+                            tee_stderr: false,
+                            check_leftover: false,
+                            status_matchers: vec![],
+                            stdout_matchers: vec![],
+                            stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
+                        }
+                    },
+                    files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
+                    setup_hooks: vec![],
+                    teardown_hooks: vec![],
+                },
+            ]
+        )]
+        #[case("with base64 stdin case",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    stdin: literal_expr(Yaml::String("AP8K".to_string())),
+                    stdin_format: StdinFormat::Base64,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                TestCase {
+                    name: TestCaseExprTemplate::NAME_FOR_DEFAULT_COMMAND.to_string(),
+                    filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                    path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                    processes: indexmap! {
+                        "main".to_string() => Process {
+                            command: "echo".to_string(),
+                            args: vec!["hello".to_string()],
+                            stdin: vec![0x00, 0xff, 0x0a],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
+                            env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            timeout: Duration::from_secs(10),
+                            mode: ProcessMode::Foreground,
+                            tee_stdout: false,
+                            tee_stderr: false,
+                            check_leftover: false,
+                            status_matchers: vec![],
+                            stdout_matchers: vec![],
+                            stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
+                        }
+                    },
+                    files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
+                    setup_hooks: vec![],
+                    teardown_hooks: vec![],
+                },
+            ]
+        )]
+        #[case("with file stdin case",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    stdin: literal_expr(Yaml::String("input.bin".to_string())),
+                    stdin_format: StdinFormat::File,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                TestCase {
+                    name: TestCaseExprTemplate::NAME_FOR_DEFAULT_COMMAND.to_string(),
+                    filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                    path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                    processes: indexmap! {
+                        "main".to_string() => Process {
+                            command: "echo".to_string(),
+                            args: vec!["hello".to_string()],
+                            stdin: vec![],
+                            stdin_file: Some("input.bin".to_string()),
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
+                            env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            timeout: Duration::from_secs(10),
+                            mode: ProcessMode::Foreground,
+                            tee_stdout: false,
+                            tee_stderr: false,
+                            check_leftover: false,
+                            status_matchers: vec![],
+                            stdout_matchers: vec![],
+                            stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
+                        }
+                    },
+                    files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
+                    setup_hooks: vec![],
+                    teardown_hooks: vec![],
+                },
+            ]
+        )]
+        #[case("with stdin chunks case",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    stdin_chunks: Some(vec![
+                        indexmap! { "data" => literal_expr(Yaml::String("hello".to_string())) },
+                        indexmap! { "delay" => literal_expr(Yaml::String("200ms".to_string())) },
+                        indexmap! { "close" => literal_expr(Yaml::Boolean(true)) },
+                    ]),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                TestCase {
+                    name: TestCaseExprTemplate::NAME_FOR_DEFAULT_COMMAND.to_string(),
+                    filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                    path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                    processes: indexmap! {
+                        "main".to_string() => Process {
+                            command: "echo".to_string(),
+                            args: vec!["hello".to_string()],
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: Some(vec![
+                                StdinChunk::Data("hello".to_string()),
+                                StdinChunk::Delay(Duration::from_millis(200)),
+                                StdinChunk::Close,
+                            ]),
+                            stdin_from: None,
+                            fds: indexmap! {},
+                            env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            timeout: Duration::from_secs(10),
+                            mode: ProcessMode::Foreground,
+                            tee_stdout: false,
+                            tee_stderr: false,
+                            check_leftover: false,
+                            status_matchers: vec![],
+                            stdout_matchers: vec![],
+                            stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
+                        }
+                    },
+                    files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
+                    setup_hooks: vec![],
+                    teardown_hooks: vec![],
+                },
+            ]
+        )]
+        #[case("with stdin from case",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    stdin_from: Some(PipeSource { process: "producer".to_string(), stream: Stream::Stdout }),
+                    ..Default::default()
+                })),
                 ..Default::default()
             },
             vec![
@@ -802,18 +1453,36 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "hello".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: Some(PipeSource { process: "producer".to_string(), stream: Stream::Stdout }),
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -821,10 +1490,10 @@ mod tests {
         )]
         #[case("with env case",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     env: vec![("MESSAGE1", literal_expr(Yaml::String("hello".to_string()))), ("MESSAGE2", literal_expr(Yaml::String("world".to_string())))],
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -836,18 +1505,36 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![("MESSAGE1".to_string(), "hello".to_string()), ("MESSAGE2".to_string(), "world".to_string())],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -859,11 +1546,11 @@ mod tests {
                     "MESSAGE" => literal_expr(Yaml::String("hello".to_string())),
                     "MESSAGE2" => var_expr("MESSAGE"),
                 },
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     command: literal_expr(Yaml::String("echo".to_string())),
                     args: vec![var_expr("MESSAGE"), var_expr("MESSAGE2")],
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -875,18 +1562,36 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string(), "hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -894,13 +1599,13 @@ mod tests {
         )]
         #[case("with status matcher case",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     status_matcher_exprs: indexmap!{
                         TEST_SUCCESS_NAME => literal_expr(Yaml::Boolean(true)),
                         TEST_SUCCESS_NAME_WITH_NOT => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -912,21 +1617,94 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![
                                 (new_status_test_success(Yaml::Boolean(true)), true),
                                 (new_status_test_success(Yaml::Boolean(true)), false),
                             ],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
+                        }
+                    },
+                    files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
+                    setup_hooks: vec![],
+                    teardown_hooks: vec![],
+                },
+            ]
+        )]
+        #[case("with signal matcher and timed_out case",
+            TestCaseExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
+                    signal_matcher_exprs: indexmap!{
+                        "eq" => literal_expr(Yaml::String("SEGV".to_string())),
+                    },
+                    timed_out: true,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                TestCase {
+                    name: TestCaseExprTemplate::NAME_FOR_DEFAULT_COMMAND.to_string(),
+                    filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                    path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                    processes: indexmap! {
+                        "main".to_string() => Process {
+                            command: "echo".to_string(),
+                            args: vec!["hello".to_string()],
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
+                            env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            timeout: Duration::from_secs(10),
+                            mode: ProcessMode::Foreground,
+                            tee_stdout: false,
+                            tee_stderr: false,
+                            check_leftover: false,
+                            status_matchers: vec![],
+                            stdout_matchers: vec![],
+                            stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![(SignalMatcher::Eq(Signal::SIGSEGV), true)],
+                            timed_out: true,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -934,13 +1712,13 @@ mod tests {
         )]
         #[case("with stdout matcher case",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stdout_matcher_exprs: indexmap!{
                         TEST_SUCCESS_NAME => literal_expr(Yaml::Boolean(true)),
                         TEST_SUCCESS_NAME_WITH_NOT => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -952,21 +1730,105 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![
                                 (new_stream_test_success(Yaml::Boolean(true)), true),
                                 (new_stream_test_success(Yaml::Boolean(true)), false),
                             ],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
+                    setup_hooks: vec![],
+                    teardown_hooks: vec![],
+                },
+            ]
+        )]
+        #[case("with fds and fd matcher case",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    fds: indexmap! {
+                        3 => indexmap! { "input" => literal_expr(Yaml::String("hello".to_string())) },
+                        4 => indexmap! { "capture" => literal_expr(Yaml::Boolean(true)) },
+                    },
+                    ..Default::default()
+                })),
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
+                    fd_matcher_exprs: indexmap! {
+                        4 => indexmap! { TEST_SUCCESS_NAME => literal_expr(Yaml::Boolean(true)) },
+                    },
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                TestCase {
+                    name: TestCaseExprTemplate::NAME_FOR_DEFAULT_COMMAND.to_string(),
+                    filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                    path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                    processes: indexmap! {
+                        "main".to_string() => Process {
+                            command: "echo".to_string(),
+                            args: vec!["hello".to_string()],
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {
+                                3 => ExtraFd::Input("hello".to_string()),
+                                4 => ExtraFd::Capture,
+                            },
+                            env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            timeout: Duration::from_secs(10),
+                            mode: ProcessMode::Foreground,
+                            tee_stdout: false,
+                            tee_stderr: false,
+                            check_leftover: false,
+                            status_matchers: vec![],
+                            stdout_matchers: vec![],
+                            stderr_matchers: vec![],
+                            fd_matchers: indexmap! {
+                                4 => vec![(new_stream_test_success(Yaml::Boolean(true)), true)],
+                            },
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
+                        }
+                    },
+                    files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -974,13 +1836,13 @@ mod tests {
         )]
         #[case("with stderr matcher case",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stderr_matcher_exprs: indexmap! {
                         TEST_SUCCESS_NAME => literal_expr(Yaml::Boolean(true)),
                         TEST_SUCCESS_NAME_WITH_NOT => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -992,21 +1854,97 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![
                                 (new_stream_test_success(Yaml::Boolean(true)), true),
                                 (new_stream_test_success(Yaml::Boolean(true)), false),
                             ],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
+                    setup_hooks: vec![],
+                    teardown_hooks: vec![],
+                },
+            ]
+        )]
+        #[case("with output matcher case",
+            TestCaseExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
+                    output_matcher_exprs: indexmap! {
+                        TEST_SUCCESS_NAME => literal_expr(Yaml::Boolean(true)),
+                        TEST_SUCCESS_NAME_WITH_NOT => literal_expr(Yaml::Boolean(true)),
+                    },
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                TestCase {
+                    name: TestCaseExprTemplate::NAME_FOR_DEFAULT_COMMAND.to_string(),
+                    filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                    path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                    processes: indexmap! {
+                        "main".to_string() => Process {
+                            command: "echo".to_string(),
+                            args: vec!["hello".to_string()],
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
+                            env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
+                            timeout: Duration::from_secs(10),
+                            mode: ProcessMode::Foreground,
+                            tee_stdout: false,
+                            tee_stderr: false,
+                            check_leftover: false,
+                            status_matchers: vec![],
+                            stdout_matchers: vec![],
+                            stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![
+                                (new_stream_test_success(Yaml::Boolean(true)), true),
+                                (new_stream_test_success(Yaml::Boolean(true)), false),
+                            ],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
+                        }
+                    },
+                    files_matchers: indexmap! {},
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -1014,9 +1952,9 @@ mod tests {
         )]
         #[case("with file matcher case",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     ..Default::default()
-                }),
+                })),
                 files_matchers: indexmap! {
                     "/tmp/output.txt" => indexmap! {
                         TEST_SUCCESS_NAME => literal_expr(Yaml::Boolean(true)),
@@ -1034,15 +1972,30 @@ mod tests {
                         "main".to_string() => Process {
                             command: "echo".to_string(),
                             args: vec!["hello".to_string()],
-                            stdin: "".to_string(),
+                            stdin: vec![],
+                            stdin_file: None,
+                            stdin_chunks: None,
+                            stdin_from: None,
+                            fds: indexmap! {},
                             env: vec![],
+                            dir: None,
+                            tty: None,
+                            dialogue: vec![],
+                            limits: Limits::default(),
+                            actions: vec![],
                             timeout: Duration::from_secs(10),
                             mode: ProcessMode::Foreground,
                             tee_stdout: false,
                             tee_stderr: false,
+                            check_leftover: false,
                             status_matchers: vec![],
                             stdout_matchers: vec![],
                             stderr_matchers: vec![],
+                            fd_matchers: indexmap! {},
+                            output_matchers: vec![],
+                            usage_matchers: vec![],
+                            signal_matchers: vec![],
+                            timed_out: false,
                         }
                     },
                     files_matchers: indexmap! {
@@ -1051,6 +2004,9 @@ mod tests {
                             (new_stream_test_success(Yaml::Boolean(true)), false),
                         ],
                     },
+                    order: vec![],
+                    parallel: false,
+                    work_dir: None,
                     setup_hooks: vec![],
                     teardown_hooks: vec![],
                 },
@@ -1083,14 +2039,14 @@ mod tests {
 
             let given = TestCaseExprTemplate {
                 name: Some(literal_expr(Yaml::String("test".to_string()))),
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     command: literal_expr(Yaml::String("cat".to_string())),
                     args: vec![Expr::TmpFile(
                         "input.txt".to_string(),
                         Box::new(literal_expr(Yaml::String("hello".to_string()))),
                     )],
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             };
 
@@ -1112,18 +2068,36 @@ mod tests {
                         args: vec![
                             tmp_file_path_buf.to_str().unwrap().to_string(),
                         ],
-                        stdin: "".to_string(),
+                        stdin: vec![],
+                        stdin_file: None,
+                        stdin_chunks: None,
+                        stdin_from: None,
+                        fds: indexmap! {},
                         env: vec![],
+                        dir: None,
+                        tty: None,
+                        dialogue: vec![],
+                        limits: Limits::default(),
+                        actions: vec![],
                         timeout: Duration::from_secs(10),
                         mode: ProcessMode::Foreground,
                         tee_stdout: false,
                         tee_stderr: false,
+                        check_leftover: false,
                         status_matchers: vec![],
                         stdout_matchers: vec![],
                         stderr_matchers: vec![],
+                        fd_matchers: indexmap! {},
+                        output_matchers: vec![],
+                        usage_matchers: vec![],
+                        signal_matchers: vec![],
+                        timed_out: false,
                     }
                 },
                 files_matchers: indexmap! {},
+                order: vec![],
+                parallel: false,
+                work_dir: None,
                 setup_hooks: vec![SetupHook::new_tmp_file(
                     tmp_file_path_buf.clone(),
                     "hello".to_string(),
@@ -1134,6 +2108,73 @@ mod tests {
             assert_eq!(Ok(expected), actual);
         }
 
+        #[rstest]
+        fn success_case_with_work_dir() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let tmp_dir_path_buf = tmp_dir.path().to_path_buf();
+            let mut tmp_dir_supplier = StubTmpDirFactory { tmp_dir: &tmp_dir };
+            let mut tmp_port_reserver = indexmap! {};
+
+            let given = TestCaseExprTemplate {
+                name: Some(literal_expr(Yaml::String("test".to_string()))),
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    dir: Some(Expr::WorkDir),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            };
+
+            let actual = eval_test_expr(
+                &mut tmp_dir_supplier,
+                &mut tmp_port_reserver,
+                &given.build(),
+            );
+
+            let expected = vec![TestCase {
+                name: "test".to_string(),
+                filename: TestCaseExprTemplate::DEFAULT_FILENAME.to_string(),
+                path: TestCaseExprTemplate::DEFAULT_PATH.to_string(),
+                processes: indexmap! {
+                    "main".to_string() => Process {
+                        command: "echo".to_string(),
+                        args: vec!["hello".to_string()],
+                        stdin: vec![],
+                        stdin_file: None,
+                        stdin_chunks: None,
+                        stdin_from: None,
+                        fds: indexmap! {},
+                        env: vec![],
+                        dir: Some(tmp_dir_path_buf.to_str().unwrap().to_string()),
+                        tty: None,
+                        dialogue: vec![],
+                        limits: Limits::default(),
+                        actions: vec![],
+                        timeout: Duration::from_secs(10),
+                        mode: ProcessMode::Foreground,
+                        tee_stdout: false,
+                        tee_stderr: false,
+                        check_leftover: false,
+                        status_matchers: vec![],
+                        stdout_matchers: vec![],
+                        stderr_matchers: vec![],
+                        fd_matchers: indexmap! {},
+                        output_matchers: vec![],
+                        usage_matchers: vec![],
+                        signal_matchers: vec![],
+                        timed_out: false,
+                    }
+                },
+                files_matchers: indexmap! {},
+                order: vec![],
+                parallel: false,
+                work_dir: Some(tmp_dir_path_buf.clone()),
+                setup_hooks: vec![],
+                teardown_hooks: vec![],
+            }];
+
+            assert_eq!(Ok(expected), actual);
+        }
+
         #[rstest]
         #[case("with eval error in name",
             TestCaseExprTemplate {
@@ -1155,11 +2196,11 @@ mod tests {
         )]
         #[case("with eval error in command",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     command: literal_expr(Yaml::Boolean(true)),
                     args: vec![env_var_expr("_undefined")],
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1183,12 +2224,40 @@ mod tests {
                 violation(".process1.command[1]", "eval error: env var _undefined is not defined"),
             ]
         )]
+        #[case("with not string dir",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    dir: Some(literal_expr(Yaml::Boolean(true))),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                violation(".dir", "should be string, but is bool"),
+            ]
+        )]
+        #[case("with eval error in dialogue",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    dialogue: vec![
+                        indexmap! { "send" => env_var_expr("_undefined") },
+                        indexmap! { "expect" => literal_expr(Yaml::String("[y/N".to_string())) },
+                    ],
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                violation(".dialogue[0].send", "eval error: env var _undefined is not defined"),
+                violation(".dialogue[1].expect", "should be valid regular expression pattern"),
+            ]
+        )]
         #[case("with eval error in env",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     env: vec![("MESSAGE1", literal_expr(Yaml::Boolean(true))), ("MESSAGE2", env_var_expr("_undefined_env")), ("MESSAGE3", var_expr("_undefined_var"))],
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1199,17 +2268,18 @@ mod tests {
         )]
         #[case("with eval error in background.wait_for",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     mode: ProcessModeExpr::Background(BackgroundConfigExpr {
                         wait_condition: Some(WaitConditionExpr{
                             name: "success_stub".to_string(),
                             params: indexmap!{
                                 "x".to_string() => env_var_expr("_undefined"),
                             },
-                        })
+                        }),
+                        ..Default::default()
                     }),
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1218,15 +2288,16 @@ mod tests {
         )]
         #[case("with invalid wait condition",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     mode: ProcessModeExpr::Background(BackgroundConfigExpr {
                         wait_condition: Some(WaitConditionExpr{
                             name: "unknown".to_string(),
                             params: indexmap!{},
-                        })
+                        }),
+                        ..Default::default()
                     }),
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1235,10 +2306,10 @@ mod tests {
         )]
         #[case("with not string stdin",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     stdin: literal_expr(Yaml::Boolean(true)),
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1247,24 +2318,50 @@ mod tests {
         )]
         #[case("with eval error in stdin",
             TestCaseExprTemplate {
-                processes: ProcessesExprTemplate::Single(ProcessExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
                     stdin: env_var_expr("_undefined"),
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
                 violation(".stdin", "eval error: env var _undefined is not defined"),
             ]
         )]
+        #[case("with invalid base64 stdin",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    stdin: literal_expr(Yaml::String("aGVsbG8".to_string())),
+                    stdin_format: StdinFormat::Base64,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                violation(".stdin.base64", "should be valid base64, but Invalid padding"),
+            ]
+        )]
+        #[case("with not string hex stdin",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    stdin: literal_expr(Yaml::Integer(1)),
+                    stdin_format: StdinFormat::Hex,
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                violation(".stdin.hex", "should be string, but is uint"),
+            ]
+        )]
         #[case("with eval error in status matcher param",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     status_matcher_exprs: indexmap!{
                         TEST_SUCCESS_NAME => env_var_expr("_undefined"),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1273,26 +2370,57 @@ mod tests {
         )]
         #[case("with undefined status matcher",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     status_matcher_exprs: indexmap!{
                         "unknown" => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
                 violation(".expect.status", "status matcher \"unknown\" is not defined")
             ]
         )]
+        #[case("with invalid fd",
+            TestCaseExprTemplate {
+                processes: ProcessesExprTemplate::Single(Box::new(ProcessExprTemplate {
+                    fds: indexmap! {
+                        3 => indexmap! {
+                            "input" => literal_expr(Yaml::String("hello".to_string())),
+                            "capture" => literal_expr(Yaml::Boolean(true)),
+                        },
+                    },
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                violation(".fds.3", "should have only one of .input and .capture"),
+            ]
+        )]
+        #[case("with invalid fd matcher",
+            TestCaseExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
+                    fd_matcher_exprs: indexmap! {
+                        3 => indexmap! { TEST_PARSE_ERROR_NAME => literal_expr(Yaml::Boolean(true)) },
+                    },
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            vec![
+                violation(".expect.fds.3.test_parse_error", PARSE_ERROR_VIOLATION_MESSAGE)
+            ]
+        )]
         #[case("with invalid status matcher",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     status_matcher_exprs: indexmap! {
                         TEST_PARSE_ERROR_NAME => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1301,12 +2429,12 @@ mod tests {
         )]
         #[case("with eval error in stdout matcher param",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stdout_matcher_exprs: indexmap! {
                         TEST_SUCCESS_NAME => env_var_expr("_undefined"),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1315,12 +2443,12 @@ mod tests {
         )]
         #[case("with undefined stdout matcher",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stdout_matcher_exprs: indexmap! {
                         "unknown" => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1329,12 +2457,12 @@ mod tests {
         )]
         #[case("with invalid stdout matcher",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stdout_matcher_exprs: indexmap! {
                         TEST_PARSE_ERROR_NAME => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1343,12 +2471,12 @@ mod tests {
         )]
         #[case("with eval error in stdout matcher param",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stdout_matcher_exprs: indexmap! {
                         TEST_SUCCESS_NAME => env_var_expr("_undefined"),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1357,12 +2485,12 @@ mod tests {
         )]
         #[case("with undefined stderr matcher",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stderr_matcher_exprs: indexmap! {
                         "unknown" => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
@@ -1371,12 +2499,12 @@ mod tests {
         )]
         #[case("with invalid stderr matcher",
             TestCaseExprTemplate {
-                processes_matchers: ProcessesMatchersExprTemplate::Single(ProcessMatchersExprTemplate {
+                processes_matchers: ProcessesMatchersExprTemplate::Single(Box::new(ProcessMatchersExprTemplate {
                     stderr_matcher_exprs: indexmap! {
                         TEST_PARSE_ERROR_NAME => literal_expr(Yaml::Boolean(true)),
                     },
                     ..Default::default()
-                }),
+                })),
                 ..Default::default()
             },
             vec![
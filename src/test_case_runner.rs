@@ -10,6 +10,7 @@ pub fn run_tests<W: Write>(
     reporter: &mut Reporter<W>,
     tee_stdout: bool,
     tee_stderr: bool,
+    update_snapshots: bool,
 ) -> Result<TestResultSummary, String> {
     reporter.on_run_start()?;
     let test_results = test_case_files
@@ -17,7 +18,12 @@ pub fn run_tests<W: Write>(
         .flat_map(|test_case_file| test_case_file.test_cases)
         .map(|test_case| {
             reporter.on_test_case_start(&test_case)?;
-            let r = test_case.run(tee_stdout, tee_stderr);
+            let r = test_case.run(
+                tee_stdout,
+                tee_stderr,
+                update_snapshots,
+                &reporter.color_marker(),
+            );
             reporter.on_test_case_end(&r)?;
             Ok::<TestResult, String>(r)
         })
@@ -36,7 +42,7 @@ pub fn run_tests<W: Write>(
 mod tests {
     use crate::{
         reporter::Formatter,
-        test_case::testutil::{ProcessTemplate, TestCaseTemplate},
+        test_case::testutil::{without_usages, ProcessTemplate, TestCaseTemplate},
         test_case_runner::run_tests,
     };
 
@@ -93,20 +99,26 @@ mod tests {
                 TestResult {
                     name: "success".to_string(),
                     failures: indexmap! {},
+                    usages: indexmap! {},
                 },
                 TestResult {
                     name: "failure".to_string(),
                     failures: indexmap! {
                         "main:exec".to_string() => vec!["cannot execute [\"/dev/null\"]: Permission denied (os error 13)".to_string()],
                     },
+                    usages: indexmap! {},
                 },
             ],
         };
 
-        assert_eq!(
-            Ok(expected_summary),
-            run_tests(test_case_files, &mut reporter, false, false)
-        );
+        let actual =
+            run_tests(test_case_files, &mut reporter, false, false, false).map(|summary| {
+                TestResultSummary {
+                    results: summary.results.into_iter().map(without_usages).collect(),
+                }
+            });
+
+        assert_eq!(Ok(expected_summary), actual);
 
         let expected_output = "\x1b[32m.\x1b[0m\x1b[31mF\x1b[0m
 Failures:
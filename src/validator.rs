@@ -1,6 +1,7 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
 use crate::ast::{Ast, Map};
+use nix::sys::signal::Signal;
 use saphyr::{Array, Yaml};
 
 #[derive(Clone)]
@@ -169,6 +170,48 @@ impl Validator {
         None
     }
 
+    // accepts bytes as uint or string with unit (e.g. "512KiB", "1MB")
+    pub fn must_be_size(&mut self, x: &Yaml) -> Option<u64> {
+        if let Some(n) = x.as_i64().and_then(|n| n.try_into().ok()) {
+            return Some(n);
+        }
+
+        if let Some(s) = x.as_str() {
+            return if let Some(size) = parse_size(s) {
+                Some(size)
+            } else {
+                self.add_violation(format!("should be size, but is invalid string \"{}\"", s));
+                None
+            };
+        }
+
+        self.add_violation(format!("should be size, but is {}", x.type_name()));
+        None
+    }
+
+    // accepts signal name with or without "SIG" prefix (e.g. "INT", "SIGINT")
+    pub fn must_be_signal(&mut self, x: &Yaml) -> Option<Signal> {
+        if let Some(s) = x.as_str() {
+            let name = if s.starts_with("SIG") {
+                s.to_string()
+            } else {
+                format!("SIG{}", s)
+            };
+            return if let Ok(signal) = Signal::from_str(&name) {
+                Some(signal)
+            } else {
+                self.add_violation(format!(
+                    "should be signal name, but is invalid string \"{}\"",
+                    s
+                ));
+                None
+            };
+        }
+
+        self.add_violation(format!("should be signal name, but is {}", x.type_name()));
+        None
+    }
+
     pub fn may_be_qualified<'a>(&mut self, x: &'a Yaml) -> Option<(&'a str, &'a Yaml)> {
         self.may_be_map(x).and_then(|m| {
             if m.len() == 1 {
@@ -289,6 +332,24 @@ impl Validator {
         self.may_have_duration(m, field)
     }
 
+    pub fn may_have_size<S: AsRef<str> + Copy>(&mut self, m: &Map, field: S) -> Option<u64> {
+        m.get(field.as_ref())
+            .and_then(|x| self.in_field(field, |v| v.must_be_size(x)))
+    }
+
+    pub fn may_have_signal<S: AsRef<str> + Copy>(&mut self, m: &Map, field: S) -> Option<Signal> {
+        m.get(field.as_ref())
+            .and_then(|x| self.in_field(field, |v| v.must_be_signal(x)))
+    }
+
+    pub fn must_have_signal<S: AsRef<str> + Copy>(&mut self, m: &Map, field: S) -> Option<Signal> {
+        if !m.contains_key(field.as_ref()) {
+            self.add_violation(format!("should have .{} as signal name", field.as_ref()));
+            return None;
+        }
+        self.may_have_signal(m, field)
+    }
+
     pub fn map_seq<T>(
         &mut self,
         seq: &Array,
@@ -303,6 +364,22 @@ impl Validator {
     }
 }
 
+fn parse_size(s: &str) -> Option<u64> {
+    let (n, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let n: u64 = n.parse().ok()?;
+    let unit: u64 = match unit.trim_start() {
+        "" | "B" => 1,
+        "KB" => 1000,
+        "MB" => 1000 * 1000,
+        "GB" => 1000 * 1000 * 1000,
+        "KiB" => 1024,
+        "MiB" => 1024 * 1024,
+        "GiB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    n.checked_mul(unit)
+}
+
 #[cfg(test)]
 pub mod testutil {
     use super::*;
@@ -790,6 +867,99 @@ mod tests {
         }
     }
 
+    mod must_be_size {
+        use super::*;
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        #[rstest]
+        #[case(Yaml::Integer(42), 42)]
+        #[case(Yaml::String("42".to_string()), 42)]
+        #[case(Yaml::String("42B".to_string()), 42)]
+        #[case(Yaml::String("2KB".to_string()), 2000)]
+        #[case(Yaml::String("2 KiB".to_string()), 2048)]
+        #[case(Yaml::String("3MB".to_string()), 3_000_000)]
+        #[case(Yaml::String("3MiB".to_string()), 3 * 1024 * 1024)]
+        #[case(Yaml::String("1GB".to_string()), 1_000_000_000)]
+        #[case(Yaml::String("1GiB".to_string()), 1024 * 1024 * 1024)]
+        fn returns_the_bytes_when_value_is_size(#[case] given: Yaml, #[case] expected: u64) {
+            let mut v = Validator::new(FILENAME);
+
+            assert_eq!(Some(expected), v.must_be_size(&given));
+            assert_eq!(Vec::<Violation>::new(), v.violations)
+        }
+
+        #[rstest]
+        #[case(Yaml::Integer(-1), "should be size, but is int")]
+        #[case(Yaml::Boolean(true), "should be size, but is bool")]
+        #[case(
+            Yaml::String("1TiB".to_string()),
+            "should be size, but is invalid string \"1TiB\""
+        )]
+        #[case(
+            Yaml::String("MiB".to_string()),
+            "should be size, but is invalid string \"MiB\""
+        )]
+        fn returns_none_when_value_is_not_valid_size(
+            #[case] given: Yaml,
+            #[case] expected_message: &str,
+        ) {
+            let mut v = Validator::new(FILENAME);
+
+            assert_eq!(None, v.must_be_size(&given));
+            assert_eq!(
+                vec![Violation {
+                    filename: FILENAME.to_string(),
+                    path: "$".to_string(),
+                    message: expected_message.to_string(),
+                }],
+                v.violations,
+            )
+        }
+    }
+
+    mod must_be_signal {
+        use super::*;
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        #[rstest]
+        #[case(Yaml::String("INT".to_string()), Signal::SIGINT)]
+        #[case(Yaml::String("SIGHUP".to_string()), Signal::SIGHUP)]
+        fn returns_the_signal_when_value_is_signal_name(
+            #[case] given: Yaml,
+            #[case] expected: Signal,
+        ) {
+            let mut v = Validator::new(FILENAME);
+
+            assert_eq!(Some(expected), v.must_be_signal(&given));
+            assert_eq!(Vec::<Violation>::new(), v.violations)
+        }
+
+        #[rstest]
+        #[case(Yaml::Integer(2), "should be signal name, but is uint")]
+        #[case(
+            Yaml::String("UNKNOWN".to_string()),
+            "should be signal name, but is invalid string \"UNKNOWN\""
+        )]
+        fn returns_none_when_value_is_not_valid_signal(
+            #[case] given: Yaml,
+            #[case] expected_message: &str,
+        ) {
+            let mut v = Validator::new(FILENAME);
+
+            assert_eq!(None, v.must_be_signal(&given));
+            assert_eq!(
+                vec![Violation {
+                    filename: FILENAME.to_string(),
+                    path: "$".to_string(),
+                    message: expected_message.to_string(),
+                }],
+                v.violations,
+            )
+        }
+    }
+
     mod may_be_qualified {
         use saphyr::Hash;
 
@@ -1485,6 +1655,90 @@ mod tests {
         }
     }
 
+    mod may_have_signal {
+        use super::*;
+        use indexmap::indexmap;
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        fn when_map_contains_signal_return_it() {
+            let mut v = Validator::new(FILENAME);
+            let signal = Yaml::String("INT".to_string());
+            let m = indexmap! { "field" => &signal };
+
+            let actual = v.may_have_signal(&m, "field");
+
+            assert_eq!(Some(Signal::SIGINT), actual);
+            assert_eq!(Vec::<Violation>::new(), v.violations)
+        }
+
+        #[rstest]
+        fn when_map_dosent_contain_return_none() {
+            let mut v = Validator::new(FILENAME);
+            let m = indexmap! {};
+
+            let actual = v.may_have_signal(&m, "field");
+
+            assert_eq!(None, actual);
+            assert_eq!(Vec::<Violation>::new(), v.violations)
+        }
+
+        #[rstest]
+        fn when_map_contains_not_signal_add_violation() {
+            let mut v = Validator::new(FILENAME);
+            let value = Yaml::Boolean(true);
+            let m = indexmap! { "field" => &value };
+
+            let actual = v.may_have_signal(&m, "field");
+
+            assert_eq!(None, actual);
+            assert_eq!(
+                vec![Violation {
+                    filename: FILENAME.to_string(),
+                    path: "$.field".to_string(),
+                    message: "should be signal name, but is bool".to_string(),
+                }],
+                v.violations
+            )
+        }
+    }
+
+    mod must_have_signal {
+        use super::*;
+        use indexmap::indexmap;
+        use pretty_assertions::assert_eq;
+
+        #[rstest]
+        fn when_map_contains_signal_return_it() {
+            let mut v = Validator::new(FILENAME);
+            let signal = Yaml::String("SIGHUP".to_string());
+            let m = indexmap! { "field" => &signal };
+
+            let actual = v.must_have_signal(&m, "field");
+
+            assert_eq!(Some(Signal::SIGHUP), actual);
+            assert_eq!(Vec::<Violation>::new(), v.violations)
+        }
+
+        #[rstest]
+        fn when_map_dosent_contain_add_violation() {
+            let mut v = Validator::new(FILENAME);
+            let m = indexmap! {};
+
+            let actual = v.must_have_signal(&m, "field");
+
+            assert_eq!(None, actual);
+            assert_eq!(
+                vec![Violation {
+                    filename: FILENAME.to_string(),
+                    path: "$".to_string(),
+                    message: "should have .field as signal name".to_string(),
+                }],
+                v.violations
+            )
+        }
+    }
+
     mod map_seq {
         use super::*;
         use pretty_assertions::assert_eq;